@@ -2,14 +2,19 @@ use std::fmt::Display;
 use std::rc::Rc;
 
 use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
 
 pub struct ArrayExpr {
     pub value: Vec<Rc<dyn Expression>>,
+    pub location: Option<Location>,
 }
 
 impl ArrayExpr {
     pub fn new(value: Vec<Rc<dyn Expression>>) -> Self {
-        Self { value }
+        Self {
+            value,
+            location: None,
+        }
     }
 }
 
@@ -17,6 +22,10 @@ impl Node for ArrayExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for ArrayExpr {
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
+use crate::object::bigint::BigInt;
+
+/// An int literal outside the range of `i64`.
+pub struct BigIntExpr {
+    pub value: BigInt,
+    pub location: Option<Location>,
+}
+
+impl BigIntExpr {
+    pub fn new(value: BigInt) -> Self {
+        Self {
+            value,
+            location: None,
+        }
+    }
+}
+
+impl Node for BigIntExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for BigIntExpr {
+    fn get_type(&self) -> Type {
+        Type::Int
+    }
+}
+
+impl Display for BigIntExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
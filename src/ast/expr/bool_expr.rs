@@ -1,14 +1,19 @@
 use std::fmt::Display;
 
 use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
 
 pub struct BoolExpr {
     pub value: bool,
+    pub location: Option<Location>,
 }
 
 impl BoolExpr {
     pub fn new(value: bool) -> Self {
-        Self { value }
+        Self {
+            value,
+            location: None,
+        }
     }
 }
 
@@ -16,6 +21,10 @@ impl Node for BoolExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for BoolExpr {
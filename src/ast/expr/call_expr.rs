@@ -1,10 +1,12 @@
 use std::{fmt::Display, rc::Rc};
 
 use crate::ast::expression::{Expression, Node};
+use crate::lexer::token::Location;
 
 pub struct CallExpr {
     pub function: Rc<dyn Expression>,
     pub arguments: Vec<Rc<dyn Expression>>,
+    pub location: Option<Location>,
 }
 
 impl CallExpr {
@@ -12,6 +14,7 @@ impl CallExpr {
         Self {
             function,
             arguments: vec![],
+            location: None,
         }
     }
 }
@@ -20,6 +23,10 @@ impl Node for CallExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for CallExpr {
@@ -0,0 +1,40 @@
+use std::fmt::Display;
+
+use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
+
+pub struct FloatExpr {
+    pub value: f64,
+    pub location: Option<Location>,
+}
+
+impl FloatExpr {
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            location: None,
+        }
+    }
+}
+
+impl Node for FloatExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for FloatExpr {
+    fn get_type(&self) -> Type {
+        Type::Float
+    }
+}
+
+impl Display for FloatExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.value)
+    }
+}
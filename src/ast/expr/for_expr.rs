@@ -0,0 +1,53 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    stmt::block_stmt::BlockStatement,
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+pub struct ForExpr {
+    pub variable: Rc<dyn Expression>,
+    pub iterable: Rc<dyn Expression>,
+    pub body: Option<Rc<BlockStatement>>,
+    pub location: Option<Location>,
+}
+
+impl ForExpr {
+    pub fn new(variable: Rc<dyn Expression>, iterable: Rc<dyn Expression>) -> Self {
+        Self {
+            variable,
+            iterable,
+            body: None,
+            location: None,
+        }
+    }
+}
+
+impl Node for ForExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for ForExpr {
+    fn get_type(&self) -> Type {
+        Type::Expression
+    }
+}
+
+impl Display for ForExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = String::new();
+        buffer.push_str(&format!("for {} in {} ", self.variable, self.iterable));
+        if let Some(ref body) = self.body {
+            buffer.push_str(&body.to_string());
+        }
+        write!(f, "{}", buffer)
+    }
+}
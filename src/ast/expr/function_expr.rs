@@ -5,21 +5,28 @@ use crate::ast::{
     stmt::block_stmt::BlockStatement,
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct FunctionExpr {
     pub parameters: Vec<Rc<dyn Expression>>,
-    pub name: Rc<dyn Expression>,
+    /// `None` for an anonymous `fn (...) {}` expression.
+    pub name: Option<Rc<dyn Expression>>,
     pub body: Option<Rc<BlockStatement>>,
     pub ret_typ: Type,
+    /// Text of the `///` comments right before a named function.
+    pub doc: Option<String>,
+    pub location: Option<Location>,
 }
 
 impl FunctionExpr {
-    pub fn new(name: Rc<dyn Expression>) -> Self {
+    pub fn new(name: Option<Rc<dyn Expression>>) -> Self {
         Self {
             parameters: vec![],
             body: None,
             name,
             ret_typ: Type::Unknown,
+            doc: None,
+            location: None,
         }
     }
 }
@@ -28,6 +35,10 @@ impl Node for FunctionExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for FunctionExpr {
@@ -44,11 +55,16 @@ impl Display for FunctionExpr {
             parameters.push(format!("{}", param))
         }
 
-        buffer.push_str(&format!("fn {} (", self.name));
+        match &self.name {
+            Some(name) => buffer.push_str(&format!("fn {} (", name)),
+            None => buffer.push_str("fn ("),
+        }
         buffer.push_str(&parameters.join(","));
         buffer.push_str(") ");
         buffer.push_str(&match self.ret_typ {
-            Type::Bool | Type::Int | Type::String => format!("{} ", self.ret_typ),
+            Type::Bool | Type::Int | Type::Float | Type::String | Type::Map | Type::Array => {
+                format!("{} ", self.ret_typ)
+            }
             _ => "".to_string(),
         });
         buffer.push_str(&if let Some(ref body) = self.body {
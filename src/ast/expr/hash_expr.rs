@@ -0,0 +1,50 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
+
+pub struct HashExpr {
+    pub pairs: Vec<(Rc<dyn Expression>, Rc<dyn Expression>)>,
+    pub location: Option<Location>,
+}
+
+impl HashExpr {
+    pub fn new(pairs: Vec<(Rc<dyn Expression>, Rc<dyn Expression>)>) -> Self {
+        Self {
+            pairs,
+            location: None,
+        }
+    }
+}
+
+impl Node for HashExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for HashExpr {
+    fn get_type(&self) -> Type {
+        Type::Map
+    }
+}
+
+impl Display for HashExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = String::new();
+        buffer.push('{');
+        for (pos, (key, value)) in self.pairs.iter().enumerate() {
+            if pos < 1 {
+                buffer.push_str(&format!("{}: {}", key, value))
+            } else {
+                buffer.push_str(&format!(", {}: {}", key, value))
+            }
+        }
+        buffer.push('}');
+        write!(f, "{}", buffer)
+    }
+}
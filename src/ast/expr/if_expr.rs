@@ -4,12 +4,14 @@ use crate::ast::{
     expression::{Expression, Node},
     stmt::block_stmt::BlockStatement,
 };
+use crate::lexer::token::Location;
 
 pub struct IfExpr {
     pub condition: Rc<dyn Expression>,
     pub consequence: Option<Rc<BlockStatement>>,
     pub alternative: Option<Rc<BlockStatement>>,
     pub el_if: Option<Rc<dyn Expression>>,
+    pub location: Option<Location>,
 }
 
 impl IfExpr {
@@ -19,6 +21,7 @@ impl IfExpr {
             consequence: None,
             alternative: None,
             el_if: None,
+            location: None,
         }
     }
 }
@@ -27,6 +30,10 @@ impl Node for IfExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for IfExpr {
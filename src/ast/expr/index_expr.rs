@@ -4,15 +4,21 @@ use crate::ast::{
     expression::{Expression, Node},
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct IndexExpr {
     pub left: Rc<dyn Expression>,
     pub index: Rc<dyn Expression>,
+    pub location: Option<Location>,
 }
 
 impl IndexExpr {
     pub fn new(left: Rc<dyn Expression>, index: Rc<dyn Expression>) -> Self {
-        Self { left, index }
+        Self {
+            left,
+            index,
+            location: None,
+        }
     }
 }
 
@@ -20,6 +26,10 @@ impl Node for IndexExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for IndexExpr {
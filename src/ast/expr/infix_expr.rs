@@ -4,12 +4,14 @@ use crate::ast::{
     expression::{Expression, Node},
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct InfixExpr {
     pub operator: String,
     pub left: Option<Rc<dyn Expression>>,
     pub right: Option<Rc<dyn Expression>>,
     pub typ: Option<Type>,
+    pub location: Option<Location>,
 }
 
 impl InfixExpr {
@@ -19,6 +21,7 @@ impl InfixExpr {
             right: None,
             left: None,
             typ: None,
+            location: None,
         }
     }
 }
@@ -27,12 +30,16 @@ impl Node for InfixExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for InfixExpr {
     fn get_type(&self) -> Type {
-        if let Some(typ) = self.typ {
-            typ
+        if let Some(typ) = &self.typ {
+            typ.clone()
         } else {
             Type::Unknown
         }
@@ -1,14 +1,19 @@
 use std::fmt::Display;
 
 use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
 
 pub struct IntExpr {
     pub value: i64,
+    pub location: Option<Location>,
 }
 
 impl IntExpr {
     pub fn new(value: i64) -> Self {
-        Self { value }
+        Self {
+            value,
+            location: None,
+        }
     }
 }
 
@@ -16,6 +21,10 @@ impl Node for IntExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for IntExpr {
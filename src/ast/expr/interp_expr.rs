@@ -0,0 +1,54 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
+
+use super::str_expr::StrExpr;
+
+/// A string literal with `${}` expressions, `"total = ${a + b}"`.
+pub struct InterpExpr {
+    /// The text between the expressions as `StrExpr`s, and the expressions.
+    pub parts: Vec<Rc<dyn Expression>>,
+    pub location: Option<Location>,
+}
+
+impl InterpExpr {
+    pub fn new(parts: Vec<Rc<dyn Expression>>) -> Self {
+        Self {
+            parts,
+            location: None,
+        }
+    }
+}
+
+impl Node for InterpExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for InterpExpr {
+    fn get_type(&self) -> Type {
+        Type::String
+    }
+}
+
+impl Display for InterpExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = String::new();
+        buffer.push('"');
+        for part in self.parts.iter() {
+            if part.as_any().is::<StrExpr>() {
+                buffer.push_str(&part.to_string());
+            } else {
+                buffer.push_str(&format!("${{{}}}", part));
+            }
+        }
+        buffer.push('"');
+        write!(f, "{}", buffer)
+    }
+}
@@ -0,0 +1,76 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    pattern::Pattern,
+    statement::Statement,
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+/// `pattern if guard => body`, the guard is optional.
+pub struct MatchArm {
+    pub pattern: Rc<Pattern>,
+    pub guard: Option<Rc<dyn Expression>>,
+    /// A block, or an expression statement for an arm without braces.
+    pub body: Rc<dyn Statement>,
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, body: Rc<dyn Statement>) -> Self {
+        Self {
+            pattern: Rc::new(pattern),
+            guard: None,
+            body,
+        }
+    }
+}
+
+pub struct MatchExpr {
+    pub value: Rc<dyn Expression>,
+    pub arms: Vec<MatchArm>,
+    pub location: Option<Location>,
+    /// Where the closing `}` is.
+    pub end: Option<Location>,
+}
+
+impl MatchExpr {
+    pub fn new(value: Rc<dyn Expression>, arms: Vec<MatchArm>) -> Self {
+        Self {
+            value,
+            arms,
+            location: None,
+            end: None,
+        }
+    }
+}
+
+impl Node for MatchExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for MatchExpr {
+    fn get_type(&self) -> Type {
+        Type::Expression
+    }
+}
+
+impl Display for MatchExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arms: Vec<String> = self
+            .arms
+            .iter()
+            .map(|arm| match &arm.guard {
+                Some(guard) => format!("{} if {} => {}", arm.pattern, guard, arm.body),
+                None => format!("{} => {}", arm.pattern, arm.body),
+            })
+            .collect();
+        write!(f, "match {} {{ {} }}", self.value, arms.join(", "))
+    }
+}
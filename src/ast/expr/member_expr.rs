@@ -0,0 +1,45 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+pub struct MemberExpr {
+    pub object: Rc<dyn Expression>,
+    pub member: Rc<dyn Expression>,
+    pub location: Option<Location>,
+}
+
+impl MemberExpr {
+    pub fn new(object: Rc<dyn Expression>, member: Rc<dyn Expression>) -> Self {
+        Self {
+            object,
+            member,
+            location: None,
+        }
+    }
+}
+
+impl Node for MemberExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for MemberExpr {
+    fn get_type(&self) -> Type {
+        Type::Member
+    }
+}
+
+impl Display for MemberExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}.{})", self.object, self.member)
+    }
+}
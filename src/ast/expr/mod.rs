@@ -1,11 +1,21 @@
 pub mod arr_expr;
+pub mod big_int_expr;
 pub mod bool_expr;
 pub mod call_expr;
+pub mod float_expr;
+pub mod for_expr;
 pub mod function_expr;
+pub mod hash_expr;
 pub mod if_expr;
 pub mod index_expr;
 pub mod infix_expr;
 pub mod int_expr;
+pub mod interp_expr;
+pub mod match_expr;
+pub mod member_expr;
 pub mod prefix_expr;
+pub mod range_expr;
 pub mod str_expr;
+pub mod struct_expr;
+pub mod try_expr;
 pub mod while_expr;
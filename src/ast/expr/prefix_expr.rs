@@ -4,11 +4,13 @@ use crate::ast::{
     expression::{Expression, Node},
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct PrefixExpr {
     pub operator: String,
     pub right: Option<Rc<dyn Expression>>,
     pub typ: Option<Type>,
+    pub location: Option<Location>,
 }
 
 impl PrefixExpr {
@@ -17,6 +19,7 @@ impl PrefixExpr {
             operator,
             right: None,
             typ: None,
+            location: None,
         }
     }
 }
@@ -25,6 +28,10 @@ impl Node for PrefixExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for PrefixExpr {
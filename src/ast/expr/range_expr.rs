@@ -0,0 +1,45 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+pub struct RangeExpr {
+    pub start: Rc<dyn Expression>,
+    pub end: Rc<dyn Expression>,
+    pub location: Option<Location>,
+}
+
+impl RangeExpr {
+    pub fn new(start: Rc<dyn Expression>, end: Rc<dyn Expression>) -> Self {
+        Self {
+            start,
+            end,
+            location: None,
+        }
+    }
+}
+
+impl Node for RangeExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for RangeExpr {
+    fn get_type(&self) -> Type {
+        Type::Array
+    }
+}
+
+impl Display for RangeExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}..{})", self.start, self.end)
+    }
+}
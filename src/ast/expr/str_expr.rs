@@ -1,14 +1,19 @@
 use std::fmt::Display;
 
 use crate::ast::{expression::Expression, node::Node, types::Type};
+use crate::lexer::token::Location;
 
 pub struct StrExpr {
     pub value: String,
+    pub location: Option<Location>,
 }
 
 impl StrExpr {
     pub fn new(value: String) -> Self {
-        Self { value }
+        Self {
+            value,
+            location: None,
+        }
     }
 }
 
@@ -16,6 +21,10 @@ impl Node for StrExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for StrExpr {
@@ -0,0 +1,54 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+/// A struct literal, `Point { x: 1, y: 2 }`.
+pub struct StructExpr {
+    pub name: Rc<dyn Expression>,
+    pub fields: Vec<(Rc<dyn Expression>, Rc<dyn Expression>)>,
+    pub location: Option<Location>,
+}
+
+impl StructExpr {
+    pub fn new(
+        name: Rc<dyn Expression>,
+        fields: Vec<(Rc<dyn Expression>, Rc<dyn Expression>)>,
+    ) -> Self {
+        Self {
+            name,
+            fields,
+            location: None,
+        }
+    }
+}
+
+impl Node for StructExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for StructExpr {
+    fn get_type(&self) -> Type {
+        Type::Struct(Rc::from(self.name.to_string()))
+    }
+}
+
+impl Display for StructExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        write!(f, "{} {{ {} }}", self.name, fields.join(", "))
+    }
+}
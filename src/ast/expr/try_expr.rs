@@ -0,0 +1,57 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    stmt::block_stmt::BlockStatement,
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+pub struct TryExpr {
+    pub body: Option<Rc<BlockStatement>>,
+    pub name: Rc<dyn Expression>,
+    pub handler: Option<Rc<BlockStatement>>,
+    pub location: Option<Location>,
+}
+
+impl TryExpr {
+    pub fn new(name: Rc<dyn Expression>) -> Self {
+        Self {
+            body: None,
+            name,
+            handler: None,
+            location: None,
+        }
+    }
+}
+
+impl Node for TryExpr {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Expression for TryExpr {
+    fn get_type(&self) -> Type {
+        Type::Expression
+    }
+}
+
+impl Display for TryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = String::new();
+        buffer.push_str("try ");
+        if let Some(ref body) = self.body {
+            buffer.push_str(&body.to_string());
+        }
+        buffer.push_str(&format!(" catch {} ", self.name));
+        if let Some(ref handler) = self.handler {
+            buffer.push_str(&handler.to_string());
+        }
+        write!(f, "{}", buffer)
+    }
+}
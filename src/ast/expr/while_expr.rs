@@ -5,10 +5,12 @@ use crate::ast::{
     stmt::block_stmt::BlockStatement,
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct WhileExpr {
     pub condition: Rc<dyn Expression>,
     pub consequence: Option<Rc<BlockStatement>>,
+    pub location: Option<Location>,
 }
 
 impl WhileExpr {
@@ -16,6 +18,7 @@ impl WhileExpr {
         Self {
             condition,
             consequence: None,
+            location: None,
         }
     }
 }
@@ -24,6 +27,10 @@ impl Node for WhileExpr {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for WhileExpr {
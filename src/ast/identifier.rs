@@ -1,16 +1,22 @@
 use crate::ast::expression::Expression;
 use crate::ast::node::Node;
 use crate::ast::types::Type;
+use crate::lexer::token::Location;
 use std::{fmt::Display, rc::Rc};
 
 pub struct Identifier {
     pub value: Rc<String>,
     pub typ: Option<Type>,
+    pub location: Option<Location>,
 }
 
 impl Identifier {
     pub fn new(value: Rc<String>) -> Self {
-        Self { value, typ: None }
+        Self {
+            value,
+            typ: None,
+            location: None,
+        }
     }
 }
 
@@ -18,6 +24,10 @@ impl Node for Identifier {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Expression for Identifier {
@@ -2,6 +2,7 @@ pub mod expr;
 pub mod expression;
 pub mod identifier;
 pub mod node;
+pub mod pattern;
 pub mod program;
 pub mod statement;
 pub mod stmt;
@@ -1,5 +1,10 @@
 use std::{any::Any, fmt::Display};
 
+use crate::lexer::token::Location;
+
 pub trait Node: Display {
     fn as_any(&self) -> &dyn Any;
+    fn location(&self) -> Option<Location> {
+        None
+    }
 }
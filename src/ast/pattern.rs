@@ -0,0 +1,101 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::expression::Expression;
+use crate::lexer::token::Location;
+
+/// What the value of a `match` is compared with in an arm.
+#[derive(Clone)]
+pub enum Pattern {
+    /// `_`, fits any value.
+    Wildcard(Option<Location>),
+    /// A name, fits any value and binds it.
+    Binding(Rc<dyn Expression>),
+    /// An int, float, str or bool literal.
+    Literal(Rc<dyn Expression>),
+    /// `start..end`, the ints from start up to, but not including, end.
+    Range(Rc<dyn Expression>, Rc<dyn Expression>),
+    /// `Shape.Circle(r)`, or `Color.Red` for a variant without values.
+    Variant {
+        name: Rc<dyn Expression>,
+        variant: Rc<dyn Expression>,
+        values: Vec<Pattern>,
+    },
+    /// `[a, b]`, or `[a, ..rest]` for arrays with more items than the pattern.
+    Array {
+        items: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+        location: Option<Location>,
+    },
+}
+
+impl Pattern {
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Self::Wildcard(location) | Self::Array { location, .. } => location.clone(),
+            Self::Binding(expr) | Self::Literal(expr) | Self::Range(expr, _) => expr.location(),
+            Self::Variant { name, .. } => name.location(),
+        }
+    }
+
+    /// The names the pattern binds, in the order a match gives their values.
+    pub fn bindings(&self) -> Vec<Rc<dyn Expression>> {
+        let mut names = vec![];
+        self.collect_bindings(&mut names);
+        names
+    }
+
+    fn collect_bindings(&self, names: &mut Vec<Rc<dyn Expression>>) {
+        match self {
+            Self::Binding(name) => names.push(Rc::clone(name)),
+            Self::Variant { values, .. } => {
+                for value in values.iter() {
+                    value.collect_bindings(names);
+                }
+            }
+            Self::Array { items, rest, .. } => {
+                for item in items.iter().chain(rest.as_deref()) {
+                    item.collect_bindings(names);
+                }
+            }
+            Self::Wildcard(_) | Self::Literal(_) | Self::Range(..) => {}
+        }
+    }
+
+    /// Whether the pattern fits any value.
+    pub fn is_catch_all(&self) -> bool {
+        matches!(self, Self::Wildcard(_) | Self::Binding(_))
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |patterns: &[Pattern]| -> Vec<String> {
+            patterns.iter().map(|pattern| pattern.to_string()).collect()
+        };
+        match self {
+            Self::Wildcard(_) => write!(f, "_"),
+            Self::Binding(expr) | Self::Literal(expr) => write!(f, "{}", expr),
+            Self::Range(start, end) => write!(f, "{}..{}", start, end),
+            Self::Variant {
+                name,
+                variant,
+                values,
+            } => {
+                write!(f, "{}.{}", name, variant)?;
+                if !values.is_empty() {
+                    write!(f, "({})", list(values).join(", "))?;
+                }
+                Ok(())
+            }
+            Self::Array { items, rest, .. } => {
+                let mut items = list(items);
+                match rest.as_deref() {
+                    Some(Self::Wildcard(_)) => items.push("..".to_string()),
+                    Some(rest) => items.push(format!("..{}", rest)),
+                    None => {}
+                }
+                write!(f, "[{}]", items.join(", "))
+            }
+        }
+    }
+}
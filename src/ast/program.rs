@@ -1,13 +1,14 @@
 use super::{expression::Node, statement::Statement};
+use crate::diagnostic::Diagnostic;
 use std::{fmt::Display, rc::Rc};
 
 pub struct Program {
     pub statements: Vec<Rc<dyn Statement>>,
-    pub errors: Vec<String>,
+    pub errors: Vec<Diagnostic>,
 }
 
 impl Program {
-    pub fn new(statements: Vec<Rc<dyn Statement>>, errors: Vec<String>) -> Box<Self> {
+    pub fn new(statements: Vec<Rc<dyn Statement>>, errors: Vec<Diagnostic>) -> Box<Self> {
         Box::new(Self { statements, errors })
     }
 }
@@ -1,15 +1,21 @@
 use std::{fmt::Display, rc::Rc};
 
 use crate::ast::{expression::Node, statement::Statement};
+use crate::lexer::token::Location;
 
 pub struct BlockStatement {
     pub statements: Vec<Rc<dyn Statement>>,
+    pub location: Option<Location>,
+    /// Where the closing `}` is.
+    pub end: Option<Location>,
 }
 
 impl BlockStatement {
     pub fn new() -> Self {
         Self {
             statements: Vec::with_capacity(10),
+            location: None,
+            end: None,
         }
     }
 
@@ -22,8 +28,14 @@ impl Node for BlockStatement {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
+impl Statement for BlockStatement {}
+
 impl Display for BlockStatement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut buffer = String::new();
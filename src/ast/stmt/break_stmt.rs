@@ -0,0 +1,38 @@
+use std::fmt::Display;
+
+use crate::ast::{expression::Node, statement::Statement};
+use crate::lexer::token::Location;
+
+pub struct Break {
+    pub location: Option<Location>,
+}
+
+impl Break {
+    pub fn new() -> Self {
+        Self { location: None }
+    }
+}
+
+impl Default for Break {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for Break {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for Break {}
+
+impl Display for Break {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "break;")
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt::Display;
+
+use crate::ast::{expression::Node, statement::Statement};
+use crate::lexer::token::Location;
+
+pub struct Continue {
+    pub location: Option<Location>,
+}
+
+impl Continue {
+    pub fn new() -> Self {
+        Self { location: None }
+    }
+}
+
+impl Default for Continue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Node for Continue {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for Continue {}
+
+impl Display for Continue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "continue;")
+    }
+}
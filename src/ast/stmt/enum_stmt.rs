@@ -0,0 +1,60 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    statement::Statement,
+    types::Type,
+};
+use crate::lexer::token::Location;
+
+pub struct EnumStmt {
+    pub name: Rc<dyn Expression>,
+    /// The name of each variant with the types of the values it holds.
+    pub variants: Vec<(Rc<dyn Expression>, Vec<Type>)>,
+    pub location: Option<Location>,
+    /// Where the closing `}` is.
+    pub end: Option<Location>,
+    /// Text of the `///` comments right before the statement.
+    pub doc: Option<String>,
+}
+
+impl EnumStmt {
+    pub fn new(name: Rc<dyn Expression>, variants: Vec<(Rc<dyn Expression>, Vec<Type>)>) -> Self {
+        Self {
+            name,
+            variants,
+            location: None,
+            end: None,
+            doc: None,
+        }
+    }
+}
+
+impl Node for EnumStmt {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for EnumStmt {}
+
+impl Display for EnumStmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let variants: Vec<String> = self
+            .variants
+            .iter()
+            .map(|(name, types)| {
+                if types.is_empty() {
+                    return name.to_string();
+                }
+                let types: Vec<String> = types.iter().map(|typ| typ.to_string()).collect();
+                format!("{}({})", name, types.join(", "))
+            })
+            .collect();
+        write!(f, "enum {} {{ {} }}", self.name, variants.join(", "))
+    }
+}
@@ -5,10 +5,13 @@ use crate::ast::{
     statement::Statement,
     types::Type,
 };
+use crate::lexer::token::Location;
 
 pub struct ExpressionStatement {
     pub expression: Option<Rc<dyn Expression>>,
     pub typ: Type,
+    /// Whether the source ended the statement with `;`.
+    pub semicolon: bool,
 }
 
 impl ExpressionStatement {
@@ -16,6 +19,7 @@ impl ExpressionStatement {
         Self {
             expression: None,
             typ,
+            semicolon: false,
         }
     }
 }
@@ -24,11 +28,15 @@ impl Node for ExpressionStatement {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.expression.as_ref().and_then(|expr| expr.location())
+    }
 }
 
 impl Expression for ExpressionStatement {
     fn get_type(&self) -> Type {
-        self.typ
+        self.typ.clone()
     }
 
     fn set_type(&mut self, typ: Type) {
@@ -0,0 +1,55 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expr::function_expr::FunctionExpr,
+    expression::{Expression, Node},
+    statement::Statement,
+};
+use crate::lexer::token::Location;
+
+pub struct ImplStmt {
+    /// The struct or enum the methods are added to.
+    pub name: Rc<dyn Expression>,
+    /// Named functions, a method takes the value it is called on first.
+    pub methods: Vec<Rc<FunctionExpr>>,
+    pub location: Option<Location>,
+    /// Where the closing `}` is.
+    pub end: Option<Location>,
+    /// Text of the `///` comments right before the statement.
+    pub doc: Option<String>,
+}
+
+impl ImplStmt {
+    pub fn new(name: Rc<dyn Expression>, methods: Vec<Rc<FunctionExpr>>) -> Self {
+        Self {
+            name,
+            methods,
+            location: None,
+            end: None,
+            doc: None,
+        }
+    }
+}
+
+impl Node for ImplStmt {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for ImplStmt {}
+
+impl Display for ImplStmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let methods: Vec<String> = self
+            .methods
+            .iter()
+            .map(|method| method.to_string())
+            .collect();
+        write!(f, "impl {} {{ {} }}", self.name, methods.join(" "))
+    }
+}
@@ -0,0 +1,42 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    statement::Statement,
+};
+use crate::lexer::token::Location;
+
+pub struct Import {
+    pub path: String,
+    /// Identifier the module namespace is bound to: the alias or the file stem.
+    pub name: Rc<dyn Expression>,
+    pub location: Option<Location>,
+}
+
+impl Import {
+    pub fn new(path: String, name: Rc<dyn Expression>) -> Self {
+        Self {
+            path,
+            name,
+            location: None,
+        }
+    }
+}
+
+impl Node for Import {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for Import {}
+
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "import \"{}\" as {};", self.path, self.name)
+    }
+}
@@ -2,16 +2,33 @@ use std::fmt::Display;
 use std::rc::Rc;
 
 use crate::ast::{expression::Expression, node::Node, statement::Statement, types::Type};
+use crate::lexer::token::Location;
 
 pub struct Let {
     typ: Type,
     pub name: Rc<dyn Expression>,
     pub value: Rc<dyn Expression>,
+    /// Text of the `///` comments right before the statement.
+    pub doc: Option<String>,
 }
 
 impl Let {
     pub fn new(typ: Type, name: Rc<dyn Expression>, value: Rc<dyn Expression>) -> Rc<Self> {
-        Rc::new(Self { typ, name, value })
+        Self::documented(typ, name, value, None)
+    }
+
+    pub fn documented(
+        typ: Type,
+        name: Rc<dyn Expression>,
+        value: Rc<dyn Expression>,
+        doc: Option<String>,
+    ) -> Rc<Self> {
+        Rc::new(Self {
+            typ,
+            name,
+            value,
+            doc,
+        })
     }
 }
 
@@ -19,6 +36,10 @@ impl Node for Let {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.name.location()
+    }
 }
 
 impl Statement for Let {}
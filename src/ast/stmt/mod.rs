@@ -1,5 +1,12 @@
 pub mod block_stmt;
+pub mod break_stmt;
+pub mod continue_stmt;
+pub mod enum_stmt;
 pub mod expression_stmt;
+pub mod impl_stmt;
+pub mod import_stmt;
 pub mod let_stmt;
 pub mod return_stmt;
+pub mod struct_stmt;
+pub mod throw_stmt;
 pub mod var_stmt;
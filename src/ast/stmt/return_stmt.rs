@@ -4,14 +4,19 @@ use crate::ast::{
     expression::{Expression, Node},
     statement::Statement,
 };
+use crate::lexer::token::Location;
 
 pub struct Return {
     pub return_value: Option<Rc<dyn Expression>>,
+    pub location: Option<Location>,
 }
 
 impl Return {
     pub fn new(return_value: Option<Rc<dyn Expression>>) -> Self {
-        Self { return_value }
+        Self {
+            return_value,
+            location: None,
+        }
     }
 }
 
@@ -19,6 +24,10 @@ impl Node for Return {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
 }
 
 impl Statement for Return {}
@@ -0,0 +1,49 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    statement::Statement,
+};
+use crate::lexer::token::Location;
+
+pub struct StructStmt {
+    pub name: Rc<dyn Expression>,
+    /// Identifiers of the fields with their declared types.
+    pub fields: Vec<Rc<dyn Expression>>,
+    pub location: Option<Location>,
+    /// Where the closing `}` is.
+    pub end: Option<Location>,
+    /// Text of the `///` comments right before the statement.
+    pub doc: Option<String>,
+}
+
+impl StructStmt {
+    pub fn new(name: Rc<dyn Expression>, fields: Vec<Rc<dyn Expression>>) -> Self {
+        Self {
+            name,
+            fields,
+            location: None,
+            end: None,
+            doc: None,
+        }
+    }
+}
+
+impl Node for StructStmt {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for StructStmt {}
+
+impl Display for StructStmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self.fields.iter().map(|field| field.to_string()).collect();
+        write!(f, "struct {} {{ {} }}", self.name, fields.join(", "))
+    }
+}
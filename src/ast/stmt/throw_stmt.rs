@@ -0,0 +1,39 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::ast::{
+    expression::{Expression, Node},
+    statement::Statement,
+};
+use crate::lexer::token::Location;
+
+pub struct Throw {
+    pub value: Rc<dyn Expression>,
+    pub location: Option<Location>,
+}
+
+impl Throw {
+    pub fn new(value: Rc<dyn Expression>) -> Self {
+        Self {
+            value,
+            location: None,
+        }
+    }
+}
+
+impl Node for Throw {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn location(&self) -> Option<Location> {
+        self.location.clone()
+    }
+}
+
+impl Statement for Throw {}
+
+impl Display for Throw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "throw {};", self.value)
+    }
+}
@@ -1,34 +1,52 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum Type {
     Int,
+    Float,
+    Map,
     Bool,
     Array,
     Error,
     Index,
+    Member,
+    Module,
     Prefix,
     String,
     BuiltIn,
     Unknown,
     Function,
     Expression,
+    StructDef,
+    Struct(Rc<str>),
+    EnumDef,
+    Enum(Rc<str>),
 }
 
-
 impl PartialEq for Type {
     fn eq(&self, other: &Self) -> bool {
-        core::mem::discriminant(self) == core::mem::discriminant(other)
+        match (self, other) {
+            // a declared type only has a name, which a struct or an enum may have
+            (Self::Struct(name) | Self::Enum(name), Self::Struct(other) | Self::Enum(other)) => {
+                name == other
+            }
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
     }
 }
 
 impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let val = match self {
+            Self::Struct(name) | Self::Enum(name) => name,
             Self::Int => "int",
+            Self::Float => "float",
+            Self::Map => "map",
             Self::Bool => "bool",
             Self::String => "str",
             Self::Index => "Index",
+            Self::Member => "Member",
+            Self::Module => "module",
             Self::Array => "array",
             Self::Error => "error",
             Self::Unknown => "unk",
@@ -36,6 +54,8 @@ impl Display for Type {
             Self::Function => "function",
             Self::Expression => "expression",
             Self::BuiltIn => "built in function",
+            Self::StructDef => "struct",
+            Self::EnumDef => "enum",
         };
         write!(f, "{}", val)
     }
@@ -0,0 +1,321 @@
+use std::fmt::Write;
+
+pub type Instructions = Vec<u8>;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Constant,
+    Pop,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    BitWiseAnd,
+    BitWiseOr,
+    Xor,
+    ShiftLeft,
+    ShiftRight,
+    Eq,
+    Diff,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    And,
+    Or,
+    Minus,
+    Bang,
+    True,
+    False,
+    Null,
+    Jump,
+    JumpNotTruthy,
+    GetGlobal,
+    SetGlobal,
+    GetLocal,
+    SetLocal,
+    GetFree,
+    SetFree,
+    CurrentClosure,
+    Array,
+    Hash,
+    Index,
+    SetIndex,
+    Call,
+    ReturnValue,
+    Return,
+    Closure,
+    SetupTry,
+    PopTry,
+    Throw,
+    Range,
+    Iterable,
+    DefineGlobal,
+    DefineLocal,
+    CaptureGlobal,
+    CaptureLocal,
+    CaptureFree,
+    Import,
+    Module,
+    Member,
+    Interpolate,
+    Struct,
+    SetMember,
+    Match,
+    NoMatch,
+    Impl,
+    TailCall,
+    Method,
+}
+
+const OPCODES: [Opcode; 63] = [
+    Opcode::Constant,
+    Opcode::Pop,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::Mod,
+    Opcode::BitWiseAnd,
+    Opcode::BitWiseOr,
+    Opcode::Xor,
+    Opcode::ShiftLeft,
+    Opcode::ShiftRight,
+    Opcode::Eq,
+    Opcode::Diff,
+    Opcode::Gt,
+    Opcode::Gte,
+    Opcode::Lt,
+    Opcode::Lte,
+    Opcode::And,
+    Opcode::Or,
+    Opcode::Minus,
+    Opcode::Bang,
+    Opcode::True,
+    Opcode::False,
+    Opcode::Null,
+    Opcode::Jump,
+    Opcode::JumpNotTruthy,
+    Opcode::GetGlobal,
+    Opcode::SetGlobal,
+    Opcode::GetLocal,
+    Opcode::SetLocal,
+    Opcode::GetFree,
+    Opcode::SetFree,
+    Opcode::CurrentClosure,
+    Opcode::Array,
+    Opcode::Hash,
+    Opcode::Index,
+    Opcode::SetIndex,
+    Opcode::Call,
+    Opcode::ReturnValue,
+    Opcode::Return,
+    Opcode::Closure,
+    Opcode::SetupTry,
+    Opcode::PopTry,
+    Opcode::Throw,
+    Opcode::Range,
+    Opcode::Iterable,
+    Opcode::DefineGlobal,
+    Opcode::DefineLocal,
+    Opcode::CaptureGlobal,
+    Opcode::CaptureLocal,
+    Opcode::CaptureFree,
+    Opcode::Import,
+    Opcode::Module,
+    Opcode::Member,
+    Opcode::Interpolate,
+    Opcode::Struct,
+    Opcode::SetMember,
+    Opcode::Match,
+    Opcode::NoMatch,
+    Opcode::Impl,
+    Opcode::TailCall,
+    Opcode::Method,
+];
+
+impl Opcode {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        OPCODES.get(byte as usize).copied()
+    }
+
+    pub fn operand_widths(&self) -> &'static [usize] {
+        match self {
+            Self::Constant
+            | Self::Jump
+            | Self::JumpNotTruthy
+            | Self::GetGlobal
+            | Self::SetGlobal
+            | Self::DefineGlobal
+            | Self::CaptureGlobal
+            | Self::Array
+            | Self::Hash
+            | Self::SetupTry
+            | Self::Module
+            | Self::Member
+            | Self::Method
+            | Self::Interpolate
+            | Self::Struct
+            | Self::SetMember
+            | Self::Match
+            | Self::Impl => &[2],
+            Self::GetLocal
+            | Self::SetLocal
+            | Self::DefineLocal
+            | Self::CaptureLocal
+            | Self::GetFree
+            | Self::SetFree
+            | Self::CaptureFree
+            | Self::Call
+            | Self::TailCall => &[1],
+            Self::Closure => &[2, 1],
+            Self::Import => &[2, 2],
+            _ => &[],
+        }
+    }
+
+    /// What the operand at `idx` holds, to name it when it does not fit.
+    fn operand_name(&self, idx: usize) -> &'static str {
+        match (self, idx) {
+            (Self::Jump | Self::JumpNotTruthy | Self::SetupTry, _) => "jump target",
+            (Self::GetLocal | Self::SetLocal | Self::DefineLocal | Self::CaptureLocal, _) => {
+                "local index"
+            }
+            (Self::GetFree | Self::SetFree | Self::CaptureFree, _) => "free index",
+            (Self::GetGlobal | Self::SetGlobal | Self::DefineGlobal | Self::CaptureGlobal, _)
+            | (Self::Import, 1) => "global index",
+            (Self::Closure, 1) => "free count",
+            (Self::Call | Self::TailCall, _) => "argument count",
+            (
+                Self::Array
+                | Self::Hash
+                | Self::Module
+                | Self::Interpolate
+                | Self::Struct
+                | Self::Impl,
+                _,
+            ) => "element count",
+            _ => "constant index",
+        }
+    }
+
+    pub fn from_operator(operator: &str) -> Option<Self> {
+        let op = match operator {
+            "+" => Self::Add,
+            "-" => Self::Sub,
+            "*" => Self::Mul,
+            "/" => Self::Div,
+            "%" => Self::Mod,
+            "&" => Self::BitWiseAnd,
+            "|" => Self::BitWiseOr,
+            "^" => Self::Xor,
+            "<<" => Self::ShiftLeft,
+            ">>" => Self::ShiftRight,
+            "==" => Self::Eq,
+            "!=" => Self::Diff,
+            ">" => Self::Gt,
+            ">=" => Self::Gte,
+            "<" => Self::Lt,
+            "<=" => Self::Lte,
+            "&&" => Self::And,
+            "||" => Self::Or,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub | Self::Minus => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Mod => "%",
+            Self::BitWiseAnd => "&",
+            Self::BitWiseOr => "|",
+            Self::Xor => "^",
+            Self::ShiftLeft => "<<",
+            Self::ShiftRight => ">>",
+            Self::Eq => "==",
+            Self::Diff => "!=",
+            Self::Gt => ">",
+            Self::Gte => ">=",
+            Self::Lt => "<",
+            Self::Lte => "<=",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Bang => "!",
+            _ => "",
+        }
+    }
+}
+
+pub fn make(op: Opcode, operands: &[usize]) -> Instructions {
+    let widths = op.operand_widths();
+    let mut instruction = Vec::with_capacity(1 + widths.iter().sum::<usize>());
+    instruction.push(op as u8);
+    for (operand, width) in operands.iter().zip(widths) {
+        match width {
+            2 => instruction.extend_from_slice(&(*operand as u16).to_be_bytes()),
+            _ => instruction.push(*operand as u8),
+        }
+    }
+    instruction
+}
+
+/// An error naming the first operand too large for its width, which `make`
+/// would otherwise truncate.
+pub fn check_operands(op: Opcode, operands: &[usize]) -> Result<(), String> {
+    for (idx, (operand, width)) in operands.iter().zip(op.operand_widths()).enumerate() {
+        let max = (1 << (8 * width)) - 1;
+        if *operand > max {
+            return Err(format!(
+                "program too large for the vm: {} {} is over {}",
+                op.operand_name(idx),
+                operand,
+                max
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn read_operands(op: Opcode, ins: &[u8]) -> (Vec<usize>, usize) {
+    let mut operands = Vec::with_capacity(2);
+    let mut offset = 0;
+    for width in op.operand_widths() {
+        match width {
+            2 => operands.push(read_u16(ins, offset)),
+            _ => operands.push(ins[offset] as usize),
+        }
+        offset += width;
+    }
+    (operands, offset)
+}
+
+pub fn read_u16(ins: &[u8], offset: usize) -> usize {
+    u16::from_be_bytes([ins[offset], ins[offset + 1]]) as usize
+}
+
+pub fn disassemble(ins: &[u8]) -> String {
+    let mut buffer = String::new();
+    let mut ip = 0;
+    while ip < ins.len() {
+        match Opcode::from_byte(ins[ip]) {
+            Some(op) => {
+                let (operands, read) = read_operands(op, &ins[ip + 1..]);
+                let operands: Vec<String> = operands.iter().map(|o| o.to_string()).collect();
+                let line = format!("{:04} {:?} {}", ip, op, operands.join(" "));
+                let _ = writeln!(buffer, "{}", line.trim_end());
+                ip += 1 + read;
+            }
+            None => {
+                let _ = writeln!(buffer, "{:04} unknown opcode {}", ip, ins[ip]);
+                ip += 1;
+            }
+        }
+    }
+    buffer
+}
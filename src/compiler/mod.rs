@@ -0,0 +1,879 @@
+pub mod code;
+mod symbol_table;
+
+use std::{collections::HashMap, mem, rc::Rc};
+
+use crate::{
+    ast::{
+        expr::{
+            arr_expr::ArrayExpr, big_int_expr::BigIntExpr, bool_expr::BoolExpr,
+            call_expr::CallExpr, float_expr::FloatExpr, for_expr::ForExpr,
+            function_expr::FunctionExpr, hash_expr::HashExpr, if_expr::IfExpr,
+            index_expr::IndexExpr, infix_expr::InfixExpr, int_expr::IntExpr,
+            interp_expr::InterpExpr, match_expr::MatchExpr, member_expr::MemberExpr,
+            prefix_expr::PrefixExpr, range_expr::RangeExpr, str_expr::StrExpr,
+            struct_expr::StructExpr, try_expr::TryExpr, while_expr::WhileExpr,
+        },
+        expression::{Expression, Node},
+        identifier::Identifier,
+        program::Program,
+        statement::Statement,
+        stmt::{
+            block_stmt::BlockStatement, break_stmt::Break, continue_stmt::Continue,
+            enum_stmt::EnumStmt, expression_stmt::ExpressionStatement, impl_stmt::ImplStmt,
+            import_stmt::Import, let_stmt::Let, return_stmt::Return, struct_stmt::StructStmt,
+            throw_stmt::Throw, var_stmt::Var,
+        },
+    },
+    evaluator::Evaluator,
+    lexer::token::Location,
+    loader::{module_name, module_trace_name, Load, Loader},
+    object::{
+        compiled_function::CompiledFunction,
+        environment::{declared_at, Declaration},
+        error::Error,
+        float::Float,
+        function::{self, ANONYMOUS},
+        integer::Integer,
+        pattern::MatchPattern,
+        string::Str,
+        Object,
+    },
+};
+
+use self::{
+    code::{Instructions, Opcode},
+    symbol_table::{Symbol, SymbolScope, SymbolTable},
+};
+
+pub struct Bytecode {
+    pub instructions: Instructions,
+    pub constants: Vec<Rc<dyn Object>>,
+    pub globals: Vec<String>,
+    pub locations: Vec<(usize, Location)>,
+}
+
+/// Jumps out of the loop being compiled, patched once its end is known.
+struct Loop {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+    try_depth: usize,
+}
+
+pub struct Compiler {
+    constants: Vec<Rc<dyn Object>>,
+    symbol_table: SymbolTable,
+    scopes: Vec<Instructions>,
+    locations: Vec<Vec<(usize, Location)>>,
+    location: Option<Location>,
+    loops: Vec<Loop>,
+    try_depth: usize,
+    in_function: bool,
+    // the call being compiled is the value of a `ret` that can hand its frame over
+    tail_call: bool,
+    // the member being compiled is the function of a call
+    method_call: bool,
+    build_in_fns: HashMap<String, Rc<dyn Object>>,
+    // the function constant and hidden global of each imported file
+    loader: Loader<(usize, usize)>,
+    // the first operand that did not fit in its instruction
+    too_large: Option<String>,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            constants: vec![],
+            symbol_table: SymbolTable::new(None),
+            scopes: vec![vec![]],
+            locations: vec![vec![]],
+            location: None,
+            loops: vec![],
+            try_depth: 0,
+            in_function: false,
+            tail_call: false,
+            method_call: false,
+            build_in_fns: Evaluator::default().build_in_fns,
+            loader: Loader::new(),
+            too_large: None,
+        }
+    }
+
+    pub fn compile(mut self, program: &Program) -> Result<Bytecode, String> {
+        self.hoist_globals(&program.statements);
+        self.compile_statements(&program.statements)?;
+        if let Some(error) = self.too_large {
+            return Err(error);
+        }
+        Ok(Bytecode {
+            instructions: self.scopes.pop().unwrap_or_default(),
+            constants: self.constants,
+            globals: self.symbol_table.names,
+            locations: self.locations.pop().unwrap_or_default(),
+        })
+    }
+
+    /// Top level functions and variables are visible before their declaration
+    /// is reached, like the tree walker that resolves names at call time.
+    fn hoist_globals(&mut self, stmts: &[Rc<dyn Statement>]) {
+        for stmt in stmts.iter() {
+            let name = if let Some(let_stmt) = stmt.as_any().downcast_ref::<Let>() {
+                let_stmt.name.to_string()
+            } else if let Some(var) = stmt.as_any().downcast_ref::<Var>() {
+                var.name.to_string()
+            } else if let Some(stmt) = stmt.as_any().downcast_ref::<StructStmt>() {
+                stmt.name.to_string()
+            } else if let Some(stmt) = stmt.as_any().downcast_ref::<EnumStmt>() {
+                stmt.name.to_string()
+            } else if let Some(name) = stmt
+                .as_any()
+                .downcast_ref::<ExpressionStatement>()
+                .and_then(|stmt| stmt.expression.as_ref())
+                .and_then(|expr| expr.as_any().downcast_ref::<FunctionExpr>())
+                .and_then(|function| function.name.as_ref())
+            {
+                name.to_string()
+            } else {
+                continue;
+            };
+            self.symbol_table.bind(&name);
+        }
+    }
+
+    fn compile_statements(&mut self, stmts: &[Rc<dyn Statement>]) -> Result<(), String> {
+        if stmts.is_empty() {
+            self.emit(Opcode::Null, &[]);
+        }
+        for (idx, stmt) in stmts.iter().enumerate() {
+            self.compile_node(stmt.as_ref())?;
+            if idx < stmts.len() - 1 {
+                self.emit(Opcode::Pop, &[]);
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_node(&mut self, node: &dyn Node) -> Result<(), String> {
+        let outer = self.location.clone();
+        if let Some(location) = node.location() {
+            self.location = Some(location);
+        }
+        let result = self.compile_located_node(node);
+        self.location = outer;
+        result
+    }
+
+    fn compile_located_node(&mut self, node: &dyn Node) -> Result<(), String> {
+        if let Some(program) = node.as_any().downcast_ref::<Program>() {
+            self.compile_statements(&program.statements)?;
+        } else if let Some(stmt) = node.as_any().downcast_ref::<ExpressionStatement>() {
+            match &stmt.expression {
+                Some(expr) => self.compile_node(expr.as_ref())?,
+                None => {
+                    self.emit(Opcode::Null, &[]);
+                }
+            }
+        } else if let Some(integer) = node.as_any().downcast_ref::<IntExpr>() {
+            let constant = self.add_constant(Rc::new(Integer::new(integer.value)));
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(big) = node.as_any().downcast_ref::<BigIntExpr>() {
+            let constant = self.add_constant(Rc::new(big.value.clone()));
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(float) = node.as_any().downcast_ref::<FloatExpr>() {
+            let constant = self.add_constant(Rc::new(Float::new(float.value)));
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(boolean) = node.as_any().downcast_ref::<BoolExpr>() {
+            let op = if boolean.value {
+                Opcode::True
+            } else {
+                Opcode::False
+            };
+            self.emit(op, &[]);
+        } else if let Some(string) = node.as_any().downcast_ref::<StrExpr>() {
+            let constant = self.add_constant(Rc::new(Str::new(string.value.clone())));
+            self.emit(Opcode::Constant, &[constant]);
+        } else if let Some(prefix) = node.as_any().downcast_ref::<PrefixExpr>() {
+            let right = prefix
+                .right
+                .as_ref()
+                .ok_or_else(|| format!("missing operand in {}", prefix))?;
+            self.compile_node(right.as_ref())?;
+            match prefix.operator.as_str() {
+                "!" => self.emit(Opcode::Bang, &[]),
+                "-" => self.emit(Opcode::Minus, &[]),
+                operator => return Err(format!("unknown operator {}", operator)),
+            };
+        } else if let Some(infix) = node.as_any().downcast_ref::<InfixExpr>() {
+            self.compile_infix_expr(infix)?;
+        } else if let Some(if_expr) = node.as_any().downcast_ref::<IfExpr>() {
+            self.compile_if_expr(if_expr)?;
+        } else if let Some(while_expr) = node.as_any().downcast_ref::<WhileExpr>() {
+            self.compile_while_expr(while_expr)?;
+        } else if let Some(for_expr) = node.as_any().downcast_ref::<ForExpr>() {
+            self.compile_for_expr(for_expr)?;
+        } else if let Some(range) = node.as_any().downcast_ref::<RangeExpr>() {
+            self.compile_node(range.start.as_ref())?;
+            self.compile_node(range.end.as_ref())?;
+            self.emit(Opcode::Range, &[]);
+        } else if node.as_any().is::<Break>() {
+            self.compile_loop_control("break")?;
+        } else if node.as_any().is::<Continue>() {
+            self.compile_loop_control("continue")?;
+        } else if let Some(block_stmt) = node.as_any().downcast_ref::<BlockStatement>() {
+            self.symbol_table.enter_block();
+            let result = self.compile_statements(&block_stmt.statements);
+            self.symbol_table.leave_block();
+            result?;
+        } else if let Some(ret) = node.as_any().downcast_ref::<Return>() {
+            match &ret.return_value {
+                Some(expr) => {
+                    // a call in a `try` still has to raise inside it
+                    self.tail_call =
+                        self.in_function && self.try_depth == 0 && expr.as_any().is::<CallExpr>();
+                    self.compile_node(expr.as_ref())?;
+                    self.emit(Opcode::ReturnValue, &[]);
+                }
+                None => {
+                    self.emit(Opcode::Return, &[]);
+                }
+            }
+        } else if let Some(throw) = node.as_any().downcast_ref::<Throw>() {
+            self.compile_node(throw.value.as_ref())?;
+            self.emit(Opcode::Throw, &[]);
+        } else if let Some(try_expr) = node.as_any().downcast_ref::<TryExpr>() {
+            self.compile_try_expr(try_expr)?;
+        } else if let Some(let_stmt) = node.as_any().downcast_ref::<Let>() {
+            self.compile_declaration(&let_stmt.name, &let_stmt.value, Declaration::Let)?;
+        } else if let Some(var) = node.as_any().downcast_ref::<Var>() {
+            self.compile_declaration(&var.name, &var.value, Declaration::Var)?;
+        } else if let Some(import) = node.as_any().downcast_ref::<Import>() {
+            self.compile_import(import);
+        } else if let Some(stmt) = node.as_any().downcast_ref::<StructStmt>() {
+            let def = self.add_constant(Rc::new(Evaluator::struct_def(stmt)));
+            self.emit(Opcode::Constant, &[def]);
+            self.bind_declaration(&stmt.name, Declaration::Struct);
+        } else if let Some(stmt) = node.as_any().downcast_ref::<EnumStmt>() {
+            let def = self.add_constant(Rc::new(Evaluator::enum_def(stmt)));
+            self.emit(Opcode::Constant, &[def]);
+            self.bind_declaration(&stmt.name, Declaration::Enum);
+        } else if let Some(stmt) = node.as_any().downcast_ref::<ImplStmt>() {
+            self.compile_node(stmt.name.as_ref())?;
+            for method in stmt.methods.iter() {
+                let name = method.name.as_ref().map(|name| name.to_string());
+                let name = self.add_constant(Rc::new(Str::new(name.unwrap_or_default())));
+                self.emit(Opcode::Constant, &[name]);
+                self.compile_function(method, false)?;
+            }
+            self.emit(Opcode::Impl, &[stmt.methods.len() * 2]);
+        } else if let Some(match_expr) = node.as_any().downcast_ref::<MatchExpr>() {
+            self.symbol_table.enter_block();
+            let result = self.compile_match_expr(match_expr);
+            self.symbol_table.leave_block();
+            result?;
+        } else if let Some(struct_expr) = node.as_any().downcast_ref::<StructExpr>() {
+            self.compile_node(struct_expr.name.as_ref())?;
+            for (name, value) in struct_expr.fields.iter() {
+                let name = self.add_constant(Rc::new(Str::new(name.to_string())));
+                self.emit(Opcode::Constant, &[name]);
+                self.compile_node(value.as_ref())?;
+            }
+            self.emit(Opcode::Struct, &[struct_expr.fields.len() * 2]);
+        } else if let Some(member) = node.as_any().downcast_ref::<MemberExpr>() {
+            let op = if mem::take(&mut self.method_call) {
+                Opcode::Method
+            } else {
+                Opcode::Member
+            };
+            self.compile_node(member.object.as_ref())?;
+            let name = self.add_constant(Rc::new(Str::new(member.member.to_string())));
+            self.emit(op, &[name]);
+        } else if let Some(identifier) = node.as_any().downcast_ref::<Identifier>() {
+            self.compile_identifier(identifier);
+        } else if let Some(interp) = node.as_any().downcast_ref::<InterpExpr>() {
+            for part in interp.parts.iter() {
+                self.compile_node(part.as_ref())?;
+            }
+            self.emit(Opcode::Interpolate, &[interp.parts.len()]);
+        } else if let Some(array) = node.as_any().downcast_ref::<ArrayExpr>() {
+            for element in array.value.iter() {
+                self.compile_node(element.as_ref())?;
+            }
+            self.emit(Opcode::Array, &[array.value.len()]);
+        } else if let Some(hash) = node.as_any().downcast_ref::<HashExpr>() {
+            for (key, value) in hash.pairs.iter() {
+                self.compile_node(key.as_ref())?;
+                self.compile_node(value.as_ref())?;
+            }
+            self.emit(Opcode::Hash, &[hash.pairs.len() * 2]);
+        } else if let Some(idx_expr) = node.as_any().downcast_ref::<IndexExpr>() {
+            self.compile_node(idx_expr.left.as_ref())?;
+            self.compile_node(idx_expr.index.as_ref())?;
+            self.emit(Opcode::Index, &[]);
+        } else if let Some(function) = node.as_any().downcast_ref::<FunctionExpr>() {
+            self.compile_function_expr(function)?;
+        } else if let Some(call) = node.as_any().downcast_ref::<CallExpr>() {
+            let op = if mem::take(&mut self.tail_call) {
+                Opcode::TailCall
+            } else {
+                Opcode::Call
+            };
+            self.method_call = call.function.as_any().is::<MemberExpr>();
+            self.compile_node(call.function.as_ref())?;
+            for arg in call.arguments.iter() {
+                self.compile_node(arg.as_ref())?;
+            }
+            self.emit(op, &[call.arguments.len()]);
+        } else {
+            return Err(format!("unsupported by the vm: {}", node));
+        }
+        Ok(())
+    }
+
+    fn compile_infix_expr(&mut self, infix: &InfixExpr) -> Result<(), String> {
+        let (left, right) = match (&infix.left, &infix.right) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Err(format!("missing operand in {}", infix)),
+        };
+        if infix.operator == "=" {
+            if let Some(idx_expr) = left.as_any().downcast_ref::<IndexExpr>() {
+                self.compile_node(right.as_ref())?;
+                self.compile_node(idx_expr.left.as_ref())?;
+                self.compile_node(idx_expr.index.as_ref())?;
+                self.emit(Opcode::SetIndex, &[]);
+                return Ok(());
+            }
+            if let Some(member) = left.as_any().downcast_ref::<MemberExpr>() {
+                self.compile_node(right.as_ref())?;
+                self.compile_node(member.object.as_ref())?;
+                let name = self.add_constant(Rc::new(Str::new(member.member.to_string())));
+                self.emit(Opcode::SetMember, &[name]);
+                return Ok(());
+            }
+            self.compile_node(right.as_ref())?;
+            let symbol = self.symbol_table.bind(&left.to_string());
+            if symbol.declaration.is_mutable() {
+                self.store_symbol(&symbol);
+            } else {
+                self.emit(Opcode::Pop, &[]);
+                self.emit_error(format!(
+                    "cannot assign to '{}', it was declared with {}{}",
+                    symbol.name,
+                    symbol.declaration,
+                    declared_at(&symbol.location)
+                ));
+            }
+            return Ok(());
+        }
+        let op = Opcode::from_operator(&infix.operator)
+            .ok_or_else(|| format!("unknown operator {}", infix.operator))?;
+        // same evaluation order as the tree walker: right operand first
+        self.compile_node(right.as_ref())?;
+        self.compile_node(left.as_ref())?;
+        self.emit(op, &[]);
+        Ok(())
+    }
+
+    fn compile_declaration(
+        &mut self,
+        name: &Rc<dyn Expression>,
+        value: &Rc<dyn Expression>,
+        declaration: Declaration,
+    ) -> Result<(), String> {
+        let declared = self.symbol_table.resolve_block(&name.to_string());
+        let immutable = declared.filter(|symbol| !symbol.declaration.is_mutable());
+        if immutable.is_none() && value.as_any().is::<FunctionExpr>() {
+            // a function literal can call itself through the name it is bound to
+            let symbol = self
+                .symbol_table
+                .declare(&name.to_string(), declaration, name.location());
+            self.emit(Opcode::Null, &[]);
+            self.define_symbol(&symbol);
+            self.emit(Opcode::Pop, &[]);
+            self.compile_node(value.as_ref())?;
+            self.store_symbol(&symbol);
+            return Ok(());
+        }
+        self.compile_node(value.as_ref())?;
+        self.bind_declaration(name, declaration);
+        Ok(())
+    }
+
+    /// Declares `name` in the current block with the value on top of the stack.
+    fn bind_declaration(&mut self, name: &Rc<dyn Expression>, declaration: Declaration) {
+        let declared = self.symbol_table.resolve_block(&name.to_string());
+        if let Some(declared) = declared.filter(|symbol| !symbol.declaration.is_mutable()) {
+            self.emit(Opcode::Pop, &[]);
+            self.emit_error(format!(
+                "'{}' is already declared with {}{}",
+                declared.name,
+                declared.declaration,
+                declared_at(&declared.location)
+            ));
+            return;
+        }
+        let symbol = self
+            .symbol_table
+            .declare(&name.to_string(), declaration, name.location());
+        self.define_symbol(&symbol);
+    }
+
+    fn compile_import(&mut self, import: &Import) {
+        let module = match self.loader.load(&import.path, import.location.as_ref()) {
+            Ok(Load::Cached(module)) => Ok(module),
+            Ok(Load::Parsed(key, program)) => {
+                let module = self.compile_module(&import.path, &program);
+                self.loader.finish(key, module.as_ref().ok().copied());
+                module
+            }
+            Err(msg) => Err(msg),
+        };
+        match module {
+            Ok((function, slot)) => {
+                self.emit(Opcode::Import, &[function, slot]);
+                self.bind_declaration(&import.name, Declaration::Import);
+            }
+            Err(msg) => self.emit_error(msg),
+        }
+    }
+
+    /// Compiles an imported file to a function that runs its top level code and
+    /// keeps its module in a hidden global, the vm calls it on the first import.
+    /// The globals of the file share the numbering of the program globals.
+    fn compile_module(&mut self, path: &str, program: &Program) -> Result<(usize, usize), String> {
+        let name = module_trace_name(path);
+        let slot = self.symbol_table.root_mut().define_hidden(&name);
+        let names = mem::take(&mut self.symbol_table.root_mut().names);
+        let outer = mem::replace(&mut self.symbol_table, SymbolTable::with_globals(names));
+        self.scopes.push(vec![]);
+        self.locations.push(vec![]);
+        let loops = mem::take(&mut self.loops);
+        let try_depth = mem::take(&mut self.try_depth);
+        let in_function = mem::take(&mut self.in_function);
+        self.hoist_globals(&program.statements);
+        let result = self.compile_statements(&program.statements);
+        self.emit(Opcode::Pop, &[]);
+        let members = self.symbol_table.top_level();
+        let constant = self.add_constant(Rc::new(Str::new(module_name(path))));
+        self.emit(Opcode::Constant, &[constant]);
+        for member in members.iter() {
+            let constant = self.add_constant(Rc::new(Str::new(member.name.clone())));
+            self.emit(Opcode::Constant, &[constant]);
+            self.emit(Opcode::CaptureGlobal, &[member.index]);
+        }
+        self.emit(Opcode::Module, &[members.len()]);
+        self.emit(Opcode::DefineGlobal, &[slot]);
+        self.emit(Opcode::ReturnValue, &[]);
+        self.loops = loops;
+        self.try_depth = try_depth;
+        self.in_function = in_function;
+
+        let instructions = self.scopes.pop().unwrap_or_default();
+        let locations = self.locations.pop().unwrap_or_default();
+        let module = mem::replace(&mut self.symbol_table, outer);
+        let root = self.symbol_table.root_mut();
+        root.num_definitions = module.names.len();
+        root.names = module.names;
+        result?;
+
+        let mut compiled = CompiledFunction::new(instructions, 0, 0, name);
+        compiled.locations = locations.into();
+        Ok((self.add_constant(Rc::new(compiled)), slot))
+    }
+
+    fn compile_if_expr(&mut self, if_expr: &IfExpr) -> Result<(), String> {
+        self.compile_node(if_expr.condition.as_ref())?;
+        let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0]);
+        match &if_expr.consequence {
+            Some(consequence) => self.compile_node(consequence.as_ref())?,
+            None => {
+                self.emit(Opcode::Null, &[]);
+            }
+        }
+        let jump = self.emit(Opcode::Jump, &[0]);
+        self.change_operand(jump_not_truthy, self.current_instructions().len());
+        if let Some(ref el_if) = if_expr.el_if {
+            self.compile_node(el_if.as_ref())?;
+        } else if let Some(ref alternative) = if_expr.alternative {
+            self.compile_node(alternative.as_ref())?;
+        } else {
+            self.emit(Opcode::Null, &[]);
+        }
+        self.change_operand(jump, self.current_instructions().len());
+        Ok(())
+    }
+
+    fn compile_while_expr(&mut self, while_expr: &WhileExpr) -> Result<(), String> {
+        // the loop keeps the value of the last iteration on the stack
+        self.emit(Opcode::Null, &[]);
+        let loop_start = self.current_instructions().len();
+        self.compile_node(while_expr.condition.as_ref())?;
+        let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0]);
+        self.emit(Opcode::Pop, &[]);
+        self.enter_loop();
+        self.symbol_table.enter_block();
+        let result = match &while_expr.consequence {
+            Some(consequence) => self.compile_statements(&consequence.statements),
+            None => self.compile_statements(&[]),
+        };
+        self.symbol_table.leave_block();
+        self.emit(Opcode::Jump, &[loop_start]);
+        let loop_end = self.current_instructions().len();
+        self.change_operand(jump_not_truthy, loop_end);
+        self.leave_loop(loop_start, loop_end);
+        result
+    }
+
+    /// Walks an array copy of the iterable with a hidden index, the loop
+    /// variable gets a fresh slot in the block of each iteration.
+    fn compile_for_expr(&mut self, for_expr: &ForExpr) -> Result<(), String> {
+        self.symbol_table.enter_block();
+        let result = self.compile_for_loop(for_expr);
+        self.symbol_table.leave_block();
+        result
+    }
+
+    fn compile_for_loop(&mut self, for_expr: &ForExpr) -> Result<(), String> {
+        self.emit(Opcode::Null, &[]);
+        self.compile_node(for_expr.iterable.as_ref())?;
+        if !for_expr.iterable.as_any().is::<RangeExpr>() {
+            let outer = self.location.clone();
+            if let Some(location) = for_expr.iterable.location() {
+                self.location = Some(location);
+            }
+            self.emit(Opcode::Iterable, &[]);
+            self.location = outer;
+        }
+        let items = self.symbol_table.define("for items");
+        self.store_symbol(&items);
+        self.emit(Opcode::Pop, &[]);
+        let zero = self.add_constant(Rc::new(Integer::new(0)));
+        self.emit(Opcode::Constant, &[zero]);
+        let index = self.symbol_table.define("for index");
+        self.store_symbol(&index);
+        self.emit(Opcode::Pop, &[]);
+
+        let loop_start = self.current_instructions().len();
+        let len = self.add_constant(Rc::clone(&self.build_in_fns["len"]));
+        self.emit(Opcode::Constant, &[len]);
+        self.load_symbol(&items);
+        self.emit(Opcode::Call, &[1]);
+        self.load_symbol(&index);
+        self.emit(Opcode::Lt, &[]);
+        let jump_not_truthy = self.emit(Opcode::JumpNotTruthy, &[0]);
+        self.emit(Opcode::Pop, &[]);
+
+        self.enter_loop();
+        self.symbol_table.enter_block();
+        self.load_symbol(&items);
+        self.load_symbol(&index);
+        self.emit(Opcode::Index, &[]);
+        let variable = self.symbol_table.define(&for_expr.variable.to_string());
+        self.define_symbol(&variable);
+        self.emit(Opcode::Pop, &[]);
+        let result = match &for_expr.body {
+            Some(body) => self.compile_statements(&body.statements),
+            None => self.compile_statements(&[]),
+        };
+        self.symbol_table.leave_block();
+
+        let next = self.current_instructions().len();
+        let one = self.add_constant(Rc::new(Integer::new(1)));
+        self.emit(Opcode::Constant, &[one]);
+        self.load_symbol(&index);
+        self.emit(Opcode::Add, &[]);
+        self.store_symbol(&index);
+        self.emit(Opcode::Pop, &[]);
+        self.emit(Opcode::Jump, &[loop_start]);
+        let loop_end = self.current_instructions().len();
+        self.change_operand(jump_not_truthy, loop_end);
+        self.leave_loop(next, loop_end);
+        result
+    }
+
+    /// Tests the value kept in a hidden slot against each arm in turn, the
+    /// values an arm binds get fresh slots in the block of the arm.
+    fn compile_match_expr(&mut self, match_expr: &MatchExpr) -> Result<(), String> {
+        self.compile_node(match_expr.value.as_ref())?;
+        let value = self.symbol_table.define("match value");
+        self.store_symbol(&value);
+        self.emit(Opcode::Pop, &[]);
+        let mut jumps = vec![];
+        for arm in match_expr.arms.iter() {
+            let pattern = self.add_constant(Rc::new(MatchPattern::new(Rc::clone(&arm.pattern))));
+            self.load_symbol(&value);
+            self.emit(Opcode::Match, &[pattern]);
+            let mut next_arm = vec![self.emit(Opcode::JumpNotTruthy, &[0])];
+            self.symbol_table.enter_block();
+            for name in arm.pattern.bindings() {
+                let binding = self.symbol_table.define(&name.to_string());
+                self.define_symbol(&binding);
+                self.emit(Opcode::Pop, &[]);
+            }
+            let result = self.compile_arm(arm.guard.as_ref(), arm.body.as_ref(), &mut next_arm);
+            self.symbol_table.leave_block();
+            result?;
+            jumps.push(self.emit(Opcode::Jump, &[0]));
+            for jump in next_arm {
+                self.change_operand(jump, self.current_instructions().len());
+            }
+        }
+        self.load_symbol(&value);
+        self.emit(Opcode::NoMatch, &[]);
+        for jump in jumps {
+            self.change_operand(jump, self.current_instructions().len());
+        }
+        Ok(())
+    }
+
+    fn compile_arm(
+        &mut self,
+        guard: Option<&Rc<dyn Expression>>,
+        body: &dyn Node,
+        next_arm: &mut Vec<usize>,
+    ) -> Result<(), String> {
+        if let Some(guard) = guard {
+            self.compile_node(guard.as_ref())?;
+            next_arm.push(self.emit(Opcode::JumpNotTruthy, &[0]));
+        }
+        self.compile_node(body)
+    }
+
+    fn enter_loop(&mut self) {
+        self.loops.push(Loop {
+            breaks: vec![],
+            continues: vec![],
+            try_depth: self.try_depth,
+        });
+    }
+
+    fn leave_loop(&mut self, next: usize, end: usize) {
+        if let Some(current) = self.loops.pop() {
+            for position in current.continues {
+                self.change_operand(position, next);
+            }
+            for position in current.breaks {
+                self.change_operand(position, end);
+            }
+        }
+    }
+
+    /// `break` and `continue` leave a null as the value of the iteration and
+    /// drop the handlers of the `try` blocks they jump out of.
+    fn compile_loop_control(&mut self, keyword: &str) -> Result<(), String> {
+        let try_depth = match self.loops.last() {
+            Some(current) => current.try_depth,
+            None => return Err(format!("'{}' outside of a loop", keyword)),
+        };
+        self.emit(Opcode::Null, &[]);
+        for _ in try_depth..self.try_depth {
+            self.emit(Opcode::PopTry, &[]);
+        }
+        let jump = self.emit(Opcode::Jump, &[0]);
+        if let Some(current) = self.loops.last_mut() {
+            if keyword == "break" {
+                current.breaks.push(jump);
+            } else {
+                current.continues.push(jump);
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_try_expr(&mut self, try_expr: &TryExpr) -> Result<(), String> {
+        let setup_try = self.emit(Opcode::SetupTry, &[0]);
+        self.try_depth += 1;
+        let result = match &try_expr.body {
+            Some(body) => self.compile_node(body.as_ref()),
+            None => self.compile_statements(&[]),
+        };
+        self.try_depth -= 1;
+        result?;
+        // a body that ends on an error still raises it here
+        self.emit(Opcode::PopTry, &[]);
+        let jump = self.emit(Opcode::Jump, &[0]);
+        self.change_operand(setup_try, self.current_instructions().len());
+        // the vm enters the handler with the caught error map on the stack
+        self.symbol_table.enter_block();
+        let symbol = self.symbol_table.define(&try_expr.name.to_string());
+        self.define_symbol(&symbol);
+        self.emit(Opcode::Pop, &[]);
+        let result = match &try_expr.handler {
+            Some(handler) => self.compile_statements(&handler.statements),
+            None => self.compile_statements(&[]),
+        };
+        self.symbol_table.leave_block();
+        result?;
+        self.change_operand(jump, self.current_instructions().len());
+        Ok(())
+    }
+
+    fn compile_identifier(&mut self, identifier: &Identifier) {
+        let name = identifier.to_string();
+        if let Some(symbol) = self.symbol_table.resolve(&name) {
+            self.load_symbol(&symbol);
+        } else if let Some(built_in) = self.build_in_fns.get(&name) {
+            let constant = self.add_constant(Rc::clone(built_in));
+            self.emit(Opcode::Constant, &[constant]);
+        } else {
+            self.emit_error(format!("unknown word '{}'", identifier));
+        }
+    }
+
+    /// Errors the compiler can spot are raised when the vm reaches them, like the tree walker does.
+    fn emit_error(&mut self, message: String) {
+        let constant = self.add_constant(Rc::new(Error::new(message)));
+        self.emit(Opcode::Constant, &[constant]);
+    }
+
+    fn compile_function_expr(&mut self, function: &FunctionExpr) -> Result<(), String> {
+        let symbol = function
+            .name
+            .as_ref()
+            .map(|name| self.symbol_table.bind(&name.to_string()));
+        self.compile_function(function, true)?;
+        if let Some(symbol) = symbol {
+            self.store_symbol(&symbol);
+        }
+        Ok(())
+    }
+
+    /// Pushes a closure of `function`, its body can call it by its name when
+    /// `recursive`. Methods are not, their name is only a member.
+    fn compile_function(&mut self, function: &FunctionExpr, recursive: bool) -> Result<(), String> {
+        let name = function.name.as_ref().map(|name| name.to_string());
+        let outer = mem::replace(&mut self.symbol_table, SymbolTable::new(None));
+        self.symbol_table = SymbolTable::new(Some(Box::new(outer)));
+        self.scopes.push(vec![]);
+        self.locations.push(vec![]);
+        let loops = mem::take(&mut self.loops);
+        let try_depth = mem::take(&mut self.try_depth);
+        let in_function = mem::replace(&mut self.in_function, true);
+        if let Some(name) = name.as_ref().filter(|_| recursive) {
+            self.symbol_table.define_function_name(name);
+        }
+        for param in function.parameters.iter() {
+            self.symbol_table.define(&param.to_string());
+        }
+        let result = match &function.body {
+            Some(body) => self.compile_statements(&body.statements),
+            None => self.compile_statements(&[]),
+        };
+        self.emit(Opcode::ReturnValue, &[]);
+        self.loops = loops;
+        self.try_depth = try_depth;
+        self.in_function = in_function;
+
+        let instructions = self.scopes.pop().unwrap_or_default();
+        let locations = self.locations.pop().unwrap_or_default();
+        let free_symbols = mem::take(&mut self.symbol_table.free_symbols);
+        let num_locals = self.symbol_table.num_definitions;
+        let outer = self.symbol_table.outer.take();
+        self.symbol_table = *outer.ok_or("function scope without outer scope")?;
+        result?;
+
+        for free in free_symbols.iter() {
+            self.capture_symbol(free);
+        }
+        let mut compiled = CompiledFunction::new(
+            instructions,
+            num_locals,
+            function.parameters.len(),
+            name.unwrap_or_else(|| ANONYMOUS.to_string()),
+        );
+        compiled.locations = locations.into();
+        compiled.source = function::source(&function.parameters, function.body.as_deref()).into();
+        let constant = self.add_constant(Rc::new(compiled));
+        self.emit(Opcode::Closure, &[constant, free_symbols.len()]);
+        Ok(())
+    }
+
+    fn load_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::GetGlobal, &[symbol.index]),
+            SymbolScope::Local => self.emit(Opcode::GetLocal, &[symbol.index]),
+            SymbolScope::Free => self.emit(Opcode::GetFree, &[symbol.index]),
+            SymbolScope::Function => self.emit(Opcode::CurrentClosure, &[]),
+        };
+    }
+
+    /// Pushes the cell of a variable a new closure captures, so the closure
+    /// shares later writes with the scope that declared it.
+    fn capture_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::CaptureGlobal, &[symbol.index]),
+            SymbolScope::Local => self.emit(Opcode::CaptureLocal, &[symbol.index]),
+            SymbolScope::Free => self.emit(Opcode::CaptureFree, &[symbol.index]),
+            SymbolScope::Function => self.emit(Opcode::CurrentClosure, &[]),
+        };
+    }
+
+    /// Binds a declared name to a new slot value, leaving behind the cell any
+    /// closure captured from an earlier binding.
+    fn define_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => {
+                self.emit(Opcode::DefineGlobal, &[symbol.index]);
+            }
+            SymbolScope::Local => {
+                self.emit(Opcode::DefineLocal, &[symbol.index]);
+            }
+            _ => self.store_symbol(symbol),
+        }
+    }
+
+    fn store_symbol(&mut self, symbol: &Symbol) {
+        match symbol.scope {
+            SymbolScope::Global => self.emit(Opcode::SetGlobal, &[symbol.index]),
+            SymbolScope::Local => self.emit(Opcode::SetLocal, &[symbol.index]),
+            SymbolScope::Free => self.emit(Opcode::SetFree, &[symbol.index]),
+            SymbolScope::Function => return,
+        };
+    }
+
+    fn add_constant(&mut self, obj: Rc<dyn Object>) -> usize {
+        self.constants.push(obj);
+        self.constants.len() - 1
+    }
+
+    fn emit(&mut self, op: Opcode, operands: &[usize]) -> usize {
+        self.check_operands(op, operands);
+        let instruction = code::make(op, operands);
+        let instructions = self.current_instructions_mut();
+        let position = instructions.len();
+        instructions.extend(instruction);
+        if let (Some(location), Some(locations)) = (&self.location, self.locations.last_mut()) {
+            if locations.last().map(|(_, last)| last) != Some(location) {
+                locations.push((position, location.clone()));
+            }
+        }
+        position
+    }
+
+    fn change_operand(&mut self, position: usize, operand: usize) {
+        let op = Opcode::from_byte(self.current_instructions()[position]).unwrap();
+        self.check_operands(op, &[operand]);
+        let instruction = code::make(op, &[operand]);
+        let instructions = self.current_instructions_mut();
+        instructions[position..position + instruction.len()].copy_from_slice(&instruction);
+    }
+
+    fn check_operands(&mut self, op: Opcode, operands: &[usize]) {
+        if self.too_large.is_none() {
+            self.too_large = code::check_operands(op, operands).err();
+        }
+    }
+
+    fn current_instructions(&self) -> &Instructions {
+        self.scopes.last().unwrap()
+    }
+
+    fn current_instructions_mut(&mut self) -> &mut Instructions {
+        self.scopes.last_mut().unwrap()
+    }
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use crate::{lexer::token::Location, object::environment::Declaration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolScope {
+    Global,
+    Local,
+    Free,
+    Function,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub scope: SymbolScope,
+    pub index: usize,
+    pub declaration: Declaration,
+    pub location: Option<Location>,
+    /// A global declared inside a block: each run of the block makes a new
+    /// binding, so closures capture it like a local instead of reading the slot.
+    pub nested: bool,
+}
+
+pub struct SymbolTable {
+    pub outer: Option<Box<SymbolTable>>,
+    pub free_symbols: Vec<Symbol>,
+    pub num_definitions: usize,
+    pub names: Vec<String>,
+    blocks: Vec<HashMap<String, Symbol>>,
+}
+
+impl SymbolTable {
+    pub fn new(outer: Option<Box<SymbolTable>>) -> Self {
+        Self {
+            outer,
+            free_symbols: vec![],
+            num_definitions: 0,
+            names: vec![],
+            blocks: vec![HashMap::new()],
+        }
+    }
+
+    /// A root table for an imported file, its globals are numbered after `names`.
+    pub fn with_globals(names: Vec<String>) -> Self {
+        let mut table = Self::new(None);
+        table.num_definitions = names.len();
+        table.names = names;
+        table
+    }
+
+    pub fn root_mut(&mut self) -> &mut SymbolTable {
+        match self.outer {
+            Some(ref mut outer) => outer.root_mut(),
+            None => self,
+        }
+    }
+
+    pub fn enter_block(&mut self) {
+        self.blocks.push(HashMap::new());
+    }
+
+    pub fn leave_block(&mut self) {
+        if self.blocks.len() > 1 {
+            self.blocks.pop();
+        }
+    }
+
+    pub fn define(&mut self, name: &str) -> Symbol {
+        let scope = if self.outer.is_some() {
+            SymbolScope::Local
+        } else {
+            SymbolScope::Global
+        };
+        let symbol = Symbol {
+            name: name.to_string(),
+            scope,
+            index: self.num_definitions,
+            declaration: Declaration::Var,
+            location: None,
+            nested: scope == SymbolScope::Global && self.blocks.len() > 1,
+        };
+        self.num_definitions += 1;
+        self.names.push(name.to_string());
+        self.insert(symbol.clone());
+        symbol
+    }
+
+    /// A slot no name resolves to, for values only the compiler refers to.
+    pub fn define_hidden(&mut self, name: &str) -> usize {
+        self.num_definitions += 1;
+        self.names.push(name.to_string());
+        self.num_definitions - 1
+    }
+
+    /// Mirrors `Environment::set`: reuses a visible binding, otherwise
+    /// defines a new one in the innermost block.
+    pub fn bind(&mut self, name: &str) -> Symbol {
+        match self.resolve(name) {
+            Some(symbol) if symbol.scope != SymbolScope::Function => symbol,
+            _ => self.define(name),
+        }
+    }
+
+    /// Mirrors `Environment::declare`: reuses a binding of the innermost block,
+    /// otherwise defines a new one that shadows outer blocks.
+    pub fn declare(
+        &mut self,
+        name: &str,
+        declaration: Declaration,
+        location: Option<Location>,
+    ) -> Symbol {
+        let mut symbol = match self.resolve_block(name) {
+            Some(symbol) if symbol.scope != SymbolScope::Function => symbol,
+            _ => self.define(name),
+        };
+        symbol.declaration = declaration;
+        symbol.location = location;
+        self.insert(symbol.clone());
+        symbol
+    }
+
+    /// The bindings of the outermost block, in declaration order.
+    pub fn top_level(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self.blocks[0].values().cloned().collect();
+        symbols.sort_by_key(|symbol| symbol.index);
+        symbols
+    }
+
+    pub fn resolve_block(&self, name: &str) -> Option<Symbol> {
+        self.blocks.last()?.get(name).cloned()
+    }
+
+    pub fn define_function_name(&mut self, name: &str) -> Symbol {
+        let symbol = Symbol {
+            name: name.to_string(),
+            scope: SymbolScope::Function,
+            index: 0,
+            declaration: Declaration::Var,
+            location: None,
+            nested: false,
+        };
+        self.blocks[0].insert(name.to_string(), symbol.clone());
+        symbol
+    }
+
+    pub fn resolve(&mut self, name: &str) -> Option<Symbol> {
+        for block in self.blocks.iter().rev() {
+            if let Some(symbol) = block.get(name) {
+                return Some(symbol.clone());
+            }
+        }
+        let symbol = self.outer.as_mut()?.resolve(name)?;
+        if symbol.scope == SymbolScope::Global && !symbol.nested {
+            Some(symbol)
+        } else {
+            Some(self.define_free(symbol))
+        }
+    }
+
+    fn define_free(&mut self, original: Symbol) -> Symbol {
+        let symbol = Symbol {
+            name: original.name.clone(),
+            scope: SymbolScope::Free,
+            index: self.free_symbols.len(),
+            declaration: original.declaration,
+            location: original.location.clone(),
+            nested: false,
+        };
+        self.free_symbols.push(original);
+        self.blocks[0].insert(symbol.name.clone(), symbol.clone());
+        symbol
+    }
+
+    fn insert(&mut self, symbol: Symbol) {
+        let block = self.blocks.last_mut().unwrap();
+        block.insert(symbol.name.clone(), symbol);
+    }
+}
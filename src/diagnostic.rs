@@ -0,0 +1,59 @@
+use std::fmt::Display;
+
+use crate::lexer::token::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+}
+
+/// A problem found in the source, with where it is and how it might be fixed.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Location>,
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String) -> Self {
+        Self {
+            severity,
+            message,
+            span: None,
+            hint: None,
+        }
+    }
+
+    pub fn error(message: String, span: Option<Location>) -> Self {
+        let mut diagnostic = Self::new(Severity::Error, message);
+        diagnostic.span = span;
+        diagnostic
+    }
+
+    /// Like `Display`, with the source line and a caret under the span.
+    pub fn report(&self, source: &str) -> String {
+        let mut report = match &self.span {
+            Some(span) => span.report(&self.message, source),
+            None => self.message.clone(),
+        };
+        if let Some(hint) = &self.hint {
+            report.push_str(&format!("\nhint: {}", hint));
+        }
+        report
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}: {}", span, self.message)?,
+            None => write!(f, "{}", self.message)?,
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nhint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
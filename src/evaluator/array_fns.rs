@@ -0,0 +1,338 @@
+use std::{cmp::Ordering, rc::Rc};
+
+use crate::{
+    ast::types::Type,
+    object::{
+        array::Array,
+        bigint::{as_big, BigInt},
+        boolean::Boolean,
+        built_in::{BuildInFn, Caller, CallingFn},
+        float::as_f64,
+        integer::Integer,
+        null::Null,
+        string::Str,
+        Object,
+    },
+};
+
+use super::{
+    built_in_fns::{error, expected, wrong_arguments},
+    string_fns,
+};
+
+pub(super) const FUNCTIONS: &[(&str, BuildInFn)] =
+    &[("reverse", reverse), ("zip", zip), ("enumerate", enumerate)];
+
+pub(super) const CALLING: &[(&str, CallingFn)] = &[
+    ("map", map),
+    ("filter", filter),
+    ("reduce", reduce),
+    ("find", find),
+    ("any", any),
+    ("all", all),
+    ("sort", sort),
+];
+
+pub fn map(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    let elements = match array_and_callback("map", args) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    let mut mapped = Vec::with_capacity(elements.len());
+    for element in elements {
+        let result = caller.call_function(Rc::clone(function), vec![element]);
+        if result.get_type() == Type::Error {
+            return result;
+        }
+        mapped.push(result);
+    }
+    Rc::new(Array::new(mapped))
+}
+
+pub fn filter(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    let elements = match array_and_callback("filter", args) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    let mut kept = vec![];
+    for element in elements {
+        match test("filter", caller, function, &element) {
+            Ok(true) => kept.push(element),
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+    Rc::new(Array::new(kept))
+}
+
+/// Folds the array with `f(acc, element)`, starting from the third argument
+/// or else the first element.
+pub fn reduce(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    if args.len() != 2 && args.len() != 3 {
+        return error(
+            "wrong number of arguments, reduce needs 2 or 3 args, array, fn, initial".to_string(),
+        );
+    }
+    let elements = match array_and_callback("reduce", &args[..2]) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    let mut elements = elements.into_iter();
+    let mut acc = match args.get(2).cloned().or_else(|| elements.next()) {
+        Some(initial) => initial,
+        None => return error("reduce of an empty array needs an initial value".to_string()),
+    };
+    for element in elements {
+        acc = caller.call_function(Rc::clone(function), vec![acc, element]);
+        if acc.get_type() == Type::Error {
+            return acc;
+        }
+    }
+    acc
+}
+
+/// The first element the callback accepts or null; on a str, the index of a
+/// part like the string `find`.
+pub fn find(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    if args
+        .first()
+        .is_some_and(|arg| arg.get_type() == Type::String)
+    {
+        return string_fns::find(args);
+    }
+    let elements = match array_and_callback("find", args) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    for element in elements {
+        match test("find", caller, function, &element) {
+            Ok(true) => return element,
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+    Rc::new(Null)
+}
+
+pub fn any(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    let elements = match array_and_callback("any", args) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    for element in elements {
+        match test("any", caller, function, &element) {
+            Ok(true) => return Rc::new(Boolean::new(true)),
+            Ok(false) => {}
+            Err(err) => return err,
+        }
+    }
+    Rc::new(Boolean::new(false))
+}
+
+pub fn all(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    let elements = match array_and_callback("all", args) {
+        Ok(elements) => elements,
+        Err(err) => return err,
+    };
+    let function = &args[1];
+    for element in elements {
+        match test("all", caller, function, &element) {
+            Ok(true) => {}
+            Ok(false) => return Rc::new(Boolean::new(false)),
+            Err(err) => return err,
+        }
+    }
+    Rc::new(Boolean::new(true))
+}
+
+/// A sorted copy of the array. Without a comparator the elements must be all
+/// numbers or all strs; `cmp(a, b)` returns an int below 0 when `a` goes first.
+pub fn sort(args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+    if args.len() != 1 && args.len() != 2 {
+        return error("wrong number of arguments, sort needs 1 or 2 args, array, fn".to_string());
+    }
+    let elements = match args[0].as_any().downcast_ref::<Array>() {
+        Some(array) => array.elements.borrow().clone(),
+        None => return expected("sort", 0, "array", args),
+    };
+    let sorted = match args.get(1) {
+        Some(function) if function.get_type() != Type::Function => {
+            return expected("sort", 1, "fn", args)
+        }
+        Some(function) => merge_sort(elements, &mut |a, b| {
+            let result = caller.call_function(Rc::clone(function), vec![a, b]);
+            match as_big(result.as_ref()) {
+                Some(integer) => Ok(integer.cmp(&BigInt::from(0))),
+                None if result.get_type() == Type::Error => Err(result),
+                None => Err(error(format!(
+                    "sort comparator must return int, got {}",
+                    result.get_type()
+                ))),
+            }
+        }),
+        None => merge_sort(elements, &mut |a, b| compare(&a, &b)),
+    };
+    match sorted {
+        Ok(sorted) => Rc::new(Array::new(sorted)),
+        Err(err) => err,
+    }
+}
+
+/// A reversed copy of an array or str.
+pub fn reverse(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return wrong_arguments("reverse", 1);
+    }
+    if let Some(array) = args[0].as_any().downcast_ref::<Array>() {
+        let mut elements = array.elements.borrow().clone();
+        elements.reverse();
+        Rc::new(Array::new(elements))
+    } else if let Some(string) = args[0].as_any().downcast_ref::<Str>() {
+        Rc::new(Str::new(string.val.chars().rev().collect()))
+    } else {
+        expected("reverse", 0, "array or str", args)
+    }
+}
+
+/// Pairs up the elements of two arrays, as long as the shorter one.
+pub fn zip(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 2 {
+        return wrong_arguments("zip", 2);
+    }
+    let (left, right) = match (
+        args[0].as_any().downcast_ref::<Array>(),
+        args[1].as_any().downcast_ref::<Array>(),
+    ) {
+        (Some(left), Some(right)) => (left, right),
+        (None, _) => return expected("zip", 0, "array", args),
+        (_, None) => return expected("zip", 1, "array", args),
+    };
+    let pairs = left
+        .elements
+        .borrow()
+        .iter()
+        .zip(right.elements.borrow().iter())
+        .map(|(a, b)| -> Rc<dyn Object> { Rc::new(Array::new(vec![Rc::clone(a), Rc::clone(b)])) })
+        .collect();
+    Rc::new(Array::new(pairs))
+}
+
+/// Pairs each element with its index.
+pub fn enumerate(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return wrong_arguments("enumerate", 1);
+    }
+    match args[0].as_any().downcast_ref::<Array>() {
+        Some(array) => {
+            let pairs = array
+                .elements
+                .borrow()
+                .iter()
+                .enumerate()
+                .map(|(idx, element)| -> Rc<dyn Object> {
+                    let idx: Rc<dyn Object> = Rc::new(Integer::new(idx as i64));
+                    Rc::new(Array::new(vec![idx, Rc::clone(element)]))
+                })
+                .collect();
+            Rc::new(Array::new(pairs))
+        }
+        None => expected("enumerate", 0, "array", args),
+    }
+}
+
+/// A copy of the elements of the array checking the second argument is a
+/// callback, so callbacks may change the array while it is visited.
+fn array_and_callback(
+    name: &str,
+    args: &[Rc<dyn Object>],
+) -> Result<Vec<Rc<dyn Object>>, Rc<dyn Object>> {
+    if args.len() != 2 {
+        return Err(wrong_arguments(name, 2));
+    }
+    let elements = match args[0].as_any().downcast_ref::<Array>() {
+        Some(array) => array.elements.borrow().clone(),
+        None => return Err(expected(name, 0, "array", args)),
+    };
+    if args[1].get_type() != Type::Function {
+        return Err(expected(name, 1, "fn", args));
+    }
+    Ok(elements)
+}
+
+/// Calls a callback that must say yes or no.
+fn test(
+    name: &str,
+    caller: &mut dyn Caller,
+    function: &Rc<dyn Object>,
+    element: &Rc<dyn Object>,
+) -> Result<bool, Rc<dyn Object>> {
+    let result = caller.call_function(Rc::clone(function), vec![Rc::clone(element)]);
+    match result.as_any().downcast_ref::<Boolean>() {
+        Some(boolean) => Ok(boolean.val),
+        None if result.get_type() == Type::Error => Err(result),
+        None => Err(error(format!(
+            "{} callback must return bool, got {}",
+            name,
+            result.get_type()
+        ))),
+    }
+}
+
+/// Orders two numbers or two strs.
+fn compare(a: &Rc<dyn Object>, b: &Rc<dyn Object>) -> Result<Ordering, Rc<dyn Object>> {
+    if let (Some(a), Some(b)) = (
+        a.as_any().downcast_ref::<Integer>(),
+        b.as_any().downcast_ref::<Integer>(),
+    ) {
+        return Ok(a.val.cmp(&b.val));
+    }
+    if let (Some(a), Some(b)) = (
+        a.as_any().downcast_ref::<Str>(),
+        b.as_any().downcast_ref::<Str>(),
+    ) {
+        return Ok(a.val.cmp(&b.val));
+    }
+    match (as_f64(a.as_ref()), as_f64(b.as_ref())) {
+        (Some(a), Some(b)) => Ok(a.total_cmp(&b)),
+        _ => Err(error(format!(
+            "sort can't compare {} and {}",
+            a.get_type(),
+            b.get_type()
+        ))),
+    }
+}
+
+/// A stable sort that stops at the first error of `cmp`; unlike the std sorts
+/// it can't panic when a user comparator is inconsistent.
+fn merge_sort<F>(
+    mut elements: Vec<Rc<dyn Object>>,
+    cmp: &mut F,
+) -> Result<Vec<Rc<dyn Object>>, Rc<dyn Object>>
+where
+    F: FnMut(Rc<dyn Object>, Rc<dyn Object>) -> Result<Ordering, Rc<dyn Object>>,
+{
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+    let right = elements.split_off(elements.len() / 2);
+    let left = merge_sort(elements, cmp)?;
+    let right = merge_sort(right, cmp)?;
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        if cmp(Rc::clone(a), Rc::clone(b))? == Ordering::Greater {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
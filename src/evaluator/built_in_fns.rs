@@ -1,16 +1,27 @@
 use std::rc::Rc;
 
 use crate::object::{
-    array::Array, boolean::Boolean, error::Error, integer::Integer, null::Null, string::Str, Object,
+    array::Array,
+    bigint::{as_big, int_object, BigInt},
+    boolean::Boolean,
+    error::Error,
+    float::{as_f64, Float},
+    hash::Hash,
+    integer::Integer,
+    null::Null,
+    string::Str,
+    Object,
 };
 
 pub fn len(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
     let mut len = 0;
     if args.len() == 1 {
         if let Some(string) = args[0].as_any().downcast_ref::<Str>() {
-            len = string.val.len()
+            len = string.val.chars().count()
         } else if let Some(arr) = args[0].as_any().downcast_ref::<Array>() {
             len = arr.elements.borrow_mut().len()
+        } else if let Some(hash) = args[0].as_any().downcast_ref::<Hash>() {
+            len = hash.pairs.borrow().len()
         }
         Rc::new(Integer::new(len as i64))
     } else {
@@ -95,16 +106,125 @@ pub fn to_int(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
             "invalid number of arguments, needs 1 arguments".to_string(),
         ));
     }
+    if let Some(float) = args[0].as_any().downcast_ref::<Float>() {
+        return float_to_int(float.val);
+    }
     let buffer = args[0].to_string();
-    match buffer.trim().parse() {
+    let text = buffer.trim();
+    match text.parse() {
         Ok(val) => Rc::new(Integer::new(val)),
+        // like a literal, an int too big for 64 bits is a big integer
+        Err(_) => match BigInt::parse(text) {
+            Some(big) => int_object(big),
+            None => Rc::new(Error::new(format!(
+                "invalid value to parse int: {}",
+                args[0]
+            ))),
+        },
+    }
+}
+
+pub fn to_float(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return Rc::new(Error::new(
+            "invalid number of arguments, needs 1 arguments".to_string(),
+        ));
+    }
+    if let Some(val) = as_f64(args[0].as_ref()) {
+        return Rc::new(Float::new(val));
+    }
+    let buffer = args[0].to_string();
+    match buffer.trim().parse() {
+        Ok(val) => Rc::new(Float::new(val)),
         Err(_) => Rc::new(Error::new(format!(
-            "invalid value to parse int: {}",
+            "invalid value to parse float: {}",
             args[0]
         ))),
     }
 }
 
+pub fn sqrt(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match number_arg("sqrt", args) {
+        Ok(val) if val < 0.0 => Rc::new(Error::new(format!("sqrt of negative number {:?}", val))),
+        Ok(val) => Rc::new(Float::new(val.sqrt())),
+        Err(err) => err,
+    }
+}
+
+pub fn floor(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match number_arg("floor", args) {
+        Ok(val) => float_to_int(val.floor()),
+        Err(err) => err,
+    }
+}
+
+pub fn ceil(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match number_arg("ceil", args) {
+        Ok(val) => float_to_int(val.ceil()),
+        Err(err) => err,
+    }
+}
+
+pub fn round(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match number_arg("round", args) {
+        Ok(val) => float_to_int(val.round()),
+        Err(err) => err,
+    }
+}
+
+pub fn abs(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() == 1 {
+        if let Some(big) = as_big(args[0].as_ref()) {
+            return int_object(big.abs());
+        }
+    }
+    match number_arg("abs", args) {
+        Ok(val) => Rc::new(Float::new(val.abs())),
+        Err(err) => err,
+    }
+}
+
+pub fn pow(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 2 {
+        return Rc::new(Error::new(
+            "wrong number of arguments, needs 2 args, base, exponent".to_string(),
+        ));
+    }
+    match (as_f64(args[0].as_ref()), as_f64(args[1].as_ref())) {
+        (Some(base), Some(exp)) => Rc::new(Float::new(base.powf(exp))),
+        _ => Rc::new(Error::new(format!(
+            "pow expects numbers, got {} and {}",
+            args[0].get_type(),
+            args[1].get_type()
+        ))),
+    }
+}
+
+fn number_arg(name: &str, args: &[Rc<dyn Object>]) -> Result<f64, Rc<dyn Object>> {
+    if args.len() != 1 {
+        return Err(Rc::new(Error::new(
+            "invalid number of arguments, needs 1 argument".to_string(),
+        )));
+    }
+    as_f64(args[0].as_ref()).ok_or_else(|| {
+        Rc::new(Error::new(format!(
+            "{} expects a number, got {}",
+            name,
+            args[0].get_type()
+        ))) as Rc<dyn Object>
+    })
+}
+
+fn float_to_int(val: f64) -> Rc<dyn Object> {
+    // i64::MAX as f64 rounds up to 2^63, which is already out of range
+    if val.is_finite() && val >= i64::MIN as f64 && val < i64::MAX as f64 {
+        Rc::new(Integer::new(val as i64))
+    } else {
+        Rc::new(Error::new(format!("{:?} does not fit in an int", val)))
+    }
+}
+
+/// The chars or elements from `start` up to `end`.
 pub fn slice(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
     if args.len() != 3 {
         return Rc::new(Error::new(
@@ -112,28 +232,42 @@ pub fn slice(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
         ));
     }
     let start = if let Some(start) = args[1].as_any().downcast_ref::<Integer>() {
-        start.val as usize
+        start.val
     } else {
         return Rc::new(Error::new(format!("invalid start {}", args[1])));
     };
 
     let end = if let Some(end) = args[2].as_any().downcast_ref::<Integer>() {
-        end.val as usize
+        end.val
     } else {
         return Rc::new(Error::new(format!("invalid end {}", args[2])));
     };
-    if let Some(string) = args[0].as_any().downcast_ref::<Str>() {
-        let slice = &string.val[start..end];
-        Rc::new(Str::new(slice.to_string()))
-    } else if let Some(arr) = args[0].as_any().downcast_ref::<Array>() {
-        let arr_borrow: &[Rc<dyn Object>] = &arr.elements.borrow_mut()[start..end];
-        let mut elements = Vec::with_capacity(arr_borrow.len());
-        for item in arr_borrow.iter() {
-            elements.push(Rc::clone(item));
+    let string = args[0].as_any().downcast_ref::<Str>();
+    let arr = args[0].as_any().downcast_ref::<Array>();
+    let len = match (string, arr) {
+        (Some(string), _) => string.val.chars().count(),
+        (_, Some(arr)) => arr.elements.borrow().len(),
+        _ => {
+            return Rc::new(Error::new(format!(
+                "slice expects a str or array, got {}",
+                args[0].get_type()
+            )))
         }
-        Rc::new(Array::new(elements))
-    } else {
-        Rc::new(Null)
+    };
+    if start < 0 || start > end || end > len as i64 {
+        return Rc::new(Error::new(format!(
+            "slice {}..{} out of bounds for length {}",
+            start, end, len
+        )));
+    }
+    let (start, end) = (start as usize, end as usize);
+    match (string, arr) {
+        (Some(string), _) => {
+            let slice: String = string.val.chars().skip(start).take(end - start).collect();
+            Rc::new(Str::new(slice))
+        }
+        (_, Some(arr)) => Rc::new(Array::new(arr.elements.borrow()[start..end].to_vec())),
+        _ => Rc::new(Null),
     }
 }
 
@@ -155,8 +289,10 @@ pub fn read(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
         ));
     }
     let mut buffer = String::new();
-    std::io::stdin().read_line(&mut buffer).unwrap();
-    Rc::new(Str::new(buffer))
+    match std::io::stdin().read_line(&mut buffer) {
+        Ok(_) => Rc::new(Str::new(buffer)),
+        Err(err) => Rc::new(Error::new(format!("read: {}", err))),
+    }
 }
 
 pub fn trim(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
@@ -191,3 +327,117 @@ pub fn replace(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
         Rc::new(Error::new("first argument must be array".to_string()))
     }
 }
+
+pub fn keys(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return Rc::new(Error::new(
+            "invalid number of arguments, needs 1 argument".to_string(),
+        ));
+    }
+    if let Some(hash) = args[0].as_any().downcast_ref::<Hash>() {
+        let keys = hash
+            .pairs
+            .borrow()
+            .values()
+            .map(|pair| Rc::clone(&pair.key))
+            .collect();
+        Rc::new(Array::new(keys))
+    } else {
+        Rc::new(Error::new("first argument must be map".to_string()))
+    }
+}
+
+pub fn values(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return Rc::new(Error::new(
+            "invalid number of arguments, needs 1 argument".to_string(),
+        ));
+    }
+    if let Some(hash) = args[0].as_any().downcast_ref::<Hash>() {
+        let values = hash
+            .pairs
+            .borrow()
+            .values()
+            .map(|pair| Rc::clone(&pair.value))
+            .collect();
+        Rc::new(Array::new(values))
+    } else {
+        Rc::new(Error::new("first argument must be map".to_string()))
+    }
+}
+
+pub fn has_key(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 2 {
+        return Rc::new(Error::new(
+            "wrong number of arguments, needs 2 args, map, key".to_string(),
+        ));
+    }
+    if let Some(hash) = args[0].as_any().downcast_ref::<Hash>() {
+        match args[1].hash_key() {
+            Some(key) => Rc::new(Boolean::new(hash.pairs.borrow().contains_key(&key))),
+            None => Rc::new(Error::new(format!(
+                "unusable as map key: {}",
+                args[1].get_type()
+            ))),
+        }
+    } else {
+        Rc::new(Error::new("first argument must be map".to_string()))
+    }
+}
+
+pub fn delete(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 2 {
+        return Rc::new(Error::new(
+            "wrong number of arguments, needs 2 args, map, key".to_string(),
+        ));
+    }
+    if let Some(hash) = args[0].as_any().downcast_ref::<Hash>() {
+        match args[1].hash_key() {
+            Some(key) => {
+                hash.pairs.borrow_mut().remove(&key);
+                Rc::clone(&args[0])
+            }
+            None => Rc::new(Error::new(format!(
+                "unusable as map key: {}",
+                args[1].get_type()
+            ))),
+        }
+    } else {
+        Rc::new(Error::new("first argument must be map".to_string()))
+    }
+}
+
+pub(super) fn wrong_arguments(name: &str, count: usize) -> Rc<dyn Object> {
+    error(format!(
+        "wrong number of arguments, {} needs {} args",
+        name, count
+    ))
+}
+
+/// The error for argument `idx` not being a `typ`.
+pub(super) fn expected(
+    name: &str,
+    idx: usize,
+    typ: &str,
+    args: &[Rc<dyn Object>],
+) -> Rc<dyn Object> {
+    if typ == "int" && args[idx].as_any().is::<BigInt>() {
+        return error(format!(
+            "{} argument {} {} is out of range",
+            name,
+            idx + 1,
+            args[idx]
+        ));
+    }
+    error(format!(
+        "{} expects {} as argument {}, got {}",
+        name,
+        typ,
+        idx + 1,
+        args[idx].get_type()
+    ))
+}
+
+pub(super) fn error(message: String) -> Rc<dyn Object> {
+    Rc::new(Error::new(message))
+}
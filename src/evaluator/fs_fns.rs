@@ -0,0 +1,139 @@
+use std::{fs, io::Write, rc::Rc};
+
+use crate::object::{
+    array::Array, boolean::Boolean, built_in::BuildInFn, null::Null, string::Str, Object,
+};
+
+use super::{built_in_fns::error, string_fns::str_arg};
+
+pub(super) const FUNCTIONS: &[(&str, BuildInFn)] = &[
+    ("read_file", read_file),
+    ("write_file", write_file),
+    ("append_file", append_file),
+    ("read_lines", read_lines),
+    ("exists", exists),
+    ("list_dir", list_dir),
+    ("remove_file", remove_file),
+    ("mkdir", mkdir),
+];
+
+pub fn read_file(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let path = match str_arg("read_file", args, 0, 1) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    match fs::read_to_string(path) {
+        Ok(content) => Rc::new(Str::new(content)),
+        Err(err) => io_error("read_file", path, err),
+    }
+}
+
+/// Replaces the content of a file, creating it when missing.
+pub fn write_file(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (path, content) = match (
+        str_arg("write_file", args, 0, 2),
+        str_arg("write_file", args, 1, 2),
+    ) {
+        (Ok(path), Ok(content)) => (path, content),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    match fs::write(path, content) {
+        Ok(()) => Rc::new(Null),
+        Err(err) => io_error("write_file", path, err),
+    }
+}
+
+pub fn append_file(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (path, content) = match (
+        str_arg("append_file", args, 0, 2),
+        str_arg("append_file", args, 1, 2),
+    ) {
+        (Ok(path), Ok(content)) => (path, content),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    let appended = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()));
+    match appended {
+        Ok(()) => Rc::new(Null),
+        Err(err) => io_error("append_file", path, err),
+    }
+}
+
+/// The lines of a file without their `\n` or `\r\n`.
+pub fn read_lines(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let path = match str_arg("read_lines", args, 0, 1) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    match fs::read_to_string(path) {
+        Ok(content) => Rc::new(Array::new(
+            content
+                .lines()
+                .map(|line| -> Rc<dyn Object> { Rc::new(Str::new(line.to_string())) })
+                .collect(),
+        )),
+        Err(err) => io_error("read_lines", path, err),
+    }
+}
+
+pub fn exists(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match str_arg("exists", args, 0, 1) {
+        Ok(path) => Rc::new(Boolean::new(fs::metadata(path).is_ok())),
+        Err(err) => err,
+    }
+}
+
+/// The names of the entries of a directory, sorted.
+pub fn list_dir(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let path = match str_arg("list_dir", args, 0, 1) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let names: Result<Vec<String>, _> = fs::read_dir(path).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect()
+    });
+    match names {
+        Ok(mut names) => {
+            names.sort();
+            Rc::new(Array::new(
+                names
+                    .into_iter()
+                    .map(|name| -> Rc<dyn Object> { Rc::new(Str::new(name)) })
+                    .collect(),
+            ))
+        }
+        Err(err) => io_error("list_dir", path, err),
+    }
+}
+
+pub fn remove_file(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let path = match str_arg("remove_file", args, 0, 1) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    match fs::remove_file(path) {
+        Ok(()) => Rc::new(Null),
+        Err(err) => io_error("remove_file", path, err),
+    }
+}
+
+/// Creates a directory and the missing ones above it, like `mkdir -p`.
+pub fn mkdir(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let path = match str_arg("mkdir", args, 0, 1) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    match fs::create_dir_all(path) {
+        Ok(()) => Rc::new(Null),
+        Err(err) => io_error("mkdir", path, err),
+    }
+}
+
+fn io_error(name: &str, path: &str, err: std::io::Error) -> Rc<dyn Object> {
+    error(format!("{} '{}': {}", name, path, err))
+}
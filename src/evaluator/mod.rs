@@ -1,33 +1,86 @@
-use std::{cell::RefCell, collections::HashMap, process, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
+mod array_fns;
 mod built_in_fns;
+mod fs_fns;
+mod process_fns;
+mod string_fns;
+
+pub use process_fns::set_args;
 
 use crate::{
     ast::{
         expr::{
-            arr_expr::ArrayExpr, bool_expr::BoolExpr, call_expr::CallExpr,
-            function_expr::FunctionExpr, if_expr::IfExpr, index_expr::IndexExpr,
-            infix_expr::InfixExpr, int_expr::IntExpr, prefix_expr::PrefixExpr, str_expr::StrExpr,
-            while_expr::WhileExpr,
+            arr_expr::ArrayExpr, big_int_expr::BigIntExpr, bool_expr::BoolExpr,
+            call_expr::CallExpr, float_expr::FloatExpr, for_expr::ForExpr,
+            function_expr::FunctionExpr, hash_expr::HashExpr, if_expr::IfExpr,
+            index_expr::IndexExpr, infix_expr::InfixExpr, int_expr::IntExpr,
+            interp_expr::InterpExpr, match_expr::MatchExpr, member_expr::MemberExpr,
+            prefix_expr::PrefixExpr, range_expr::RangeExpr, str_expr::StrExpr,
+            struct_expr::StructExpr, try_expr::TryExpr, while_expr::WhileExpr,
         },
         expression::{Expression, Node},
         identifier::Identifier,
+        pattern::Pattern,
         program::Program,
         statement::Statement,
         stmt::{
-            block_stmt::BlockStatement, expression_stmt::ExpressionStatement, let_stmt::Let,
-            return_stmt::Return, var_stmt::Var,
+            block_stmt::BlockStatement, break_stmt::Break, continue_stmt::Continue,
+            enum_stmt::EnumStmt, expression_stmt::ExpressionStatement, impl_stmt::ImplStmt,
+            import_stmt::Import, let_stmt::Let, return_stmt::Return, struct_stmt::StructStmt,
+            throw_stmt::Throw, var_stmt::Var,
         },
         types::Type,
     },
+    lexer::token::Location,
+    loader::{module_name, module_trace_name, Load, Loader},
     object::{
-        array::Array, boolean::Boolean, built_in::BuiltIn, environment::Environment, error::Error,
-        function::Function, integer::Integer, null::Null, ret::Ret, string::Str, Object,
+        array::Array,
+        bigint::{as_big, int_object, BigInt},
+        boolean::Boolean,
+        built_in::{BuiltIn, Caller},
+        enumeration::{self, Constructor, EnumDef, Variant},
+        environment::{Declaration, Environment},
+        error::Error,
+        float::{as_f64, Float},
+        function::Function,
+        hash::{Hash, HashKey, HashPair},
+        integer::Integer,
+        loop_control::LoopControl,
+        method::{self, BoundMethod},
+        module::Module,
+        null::Null,
+        ret::{Ret, TailCall},
+        string::Str,
+        structure::{build_struct, Struct, StructDef},
+        Object,
     },
 };
 
+/// How many calls deep a program can go before it fails with a stack
+/// overflow, unless the evaluator or vm is given another limit.
+pub const MAX_DEPTH: usize = 10_000;
+
+/// How many bytes of native stack the evaluator's calls can take before they
+/// fail with a stack overflow, unless it is given another size. It is half
+/// the stack Rust gives a spawned thread, a deeper `max_depth` needs a
+/// thread with a bigger stack and a `stack_size` to match.
+pub const STACK_SIZE: usize = 1024 * 1024;
+
+type Arguments = Vec<Option<Rc<dyn Object>>>;
+
 pub struct Evaluator {
     pub build_in_fns: HashMap<String, Rc<dyn Object>>,
+    pub max_depth: usize,
+    pub stack_size: usize,
+    depth: Cell<usize>,
+    // the native stack address where the outermost call started
+    stack_base: Cell<usize>,
+    loader: RefCell<Loader<Rc<dyn Object>>>,
 }
 
 impl Evaluator {
@@ -42,6 +95,28 @@ impl Evaluator {
             "to_int".to_string(),
             Rc::new(BuiltIn::new(built_in_fns::to_int)),
         );
+        build_in_fns.insert(
+            "to_float".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::to_float)),
+        );
+        build_in_fns.insert(
+            "sqrt".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::sqrt)),
+        );
+        build_in_fns.insert(
+            "floor".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::floor)),
+        );
+        build_in_fns.insert(
+            "ceil".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::ceil)),
+        );
+        build_in_fns.insert(
+            "round".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::round)),
+        );
+        build_in_fns.insert("pow".to_string(), Rc::new(BuiltIn::new(built_in_fns::pow)));
+        build_in_fns.insert("abs".to_string(), Rc::new(BuiltIn::new(built_in_fns::abs)));
         build_in_fns.insert(
             "puts".to_string(),
             Rc::new(BuiltIn::new(built_in_fns::puts)),
@@ -74,15 +149,48 @@ impl Evaluator {
             "trim".to_string(),
             Rc::new(BuiltIn::new(built_in_fns::trim)),
         );
-        build_in_fns.insert(
-            "is_error".to_string(),
-            Rc::new(BuiltIn::new(built_in_fns::is_error)),
-        );
+        let mut is_error = BuiltIn::new(built_in_fns::is_error);
+        is_error.accepts_errors = true;
+        build_in_fns.insert("is_error".to_string(), Rc::new(is_error));
         build_in_fns.insert(
             "replace".to_string(),
             Rc::new(BuiltIn::new(built_in_fns::replace)),
         );
-        Self { build_in_fns }
+        build_in_fns.insert(
+            "keys".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::keys)),
+        );
+        build_in_fns.insert(
+            "values".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::values)),
+        );
+        build_in_fns.insert(
+            "has_key".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::has_key)),
+        );
+        build_in_fns.insert(
+            "delete".to_string(),
+            Rc::new(BuiltIn::new(built_in_fns::delete)),
+        );
+        for (name, function) in string_fns::FUNCTIONS
+            .iter()
+            .chain(array_fns::FUNCTIONS)
+            .chain(fs_fns::FUNCTIONS)
+            .chain(process_fns::FUNCTIONS)
+        {
+            build_in_fns.insert(name.to_string(), Rc::new(BuiltIn::new(*function)));
+        }
+        for (name, function) in array_fns::CALLING {
+            build_in_fns.insert(name.to_string(), Rc::new(BuiltIn::calling(*function)));
+        }
+        Self {
+            build_in_fns,
+            max_depth: MAX_DEPTH,
+            stack_size: STACK_SIZE,
+            depth: Cell::new(0),
+            stack_base: Cell::new(0),
+            loader: RefCell::new(Loader::new()),
+        }
     }
 
     pub fn set(&self, name: String, obj: Rc<dyn Object>, env: Rc<RefCell<Environment>>) {
@@ -97,10 +205,24 @@ impl Evaluator {
         &self,
         node: Option<&dyn Node>,
         env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        let evaluated = self.eval_node(node, env);
+        // errors take the location of the innermost node that produced them
+        match (evaluated, node) {
+            (Some(obj), Some(node)) => Some(Error::locate(obj, || node.location())),
+            (evaluated, _) => evaluated,
+        }
+    }
+
+    fn eval_node(
+        &self,
+        node: Option<&dyn Node>,
+        env: Rc<RefCell<Environment>>,
     ) -> Option<Rc<dyn Object>> {
         if let Some(node) = node {
             if let Some(program) = node.as_any().downcast_ref::<Program>() {
-                Some(self.eval_statements(&program.statements, Rc::clone(&env)))
+                let result = self.eval_statements(&program.statements, Rc::clone(&env));
+                self.extract_ret_val(self.finish_tail_call(Some(result)))
             } else if let Some(stmt) = node.as_any().downcast_ref::<ExpressionStatement>() {
                 match &stmt.expression {
                     Some(expr) => self.eval(Some(expr.as_ref()), env),
@@ -108,10 +230,26 @@ impl Evaluator {
                 }
             } else if let Some(integer) = node.as_any().downcast_ref::<IntExpr>() {
                 Some(Rc::new(Integer::new(integer.value)))
+            } else if let Some(big) = node.as_any().downcast_ref::<BigIntExpr>() {
+                Some(Rc::new(big.value.clone()))
+            } else if let Some(float) = node.as_any().downcast_ref::<FloatExpr>() {
+                Some(Rc::new(Float::new(float.value)))
             } else if let Some(boolean) = node.as_any().downcast_ref::<BoolExpr>() {
                 Some(Rc::new(Boolean::new(boolean.value)))
             } else if let Some(string) = node.as_any().downcast_ref::<StrExpr>() {
                 Some(Rc::new(Str::new(string.value.clone())))
+            } else if let Some(interp) = node.as_any().downcast_ref::<InterpExpr>() {
+                let mut buffer = String::new();
+                for part in interp.parts.iter() {
+                    let value = self.eval(Some(part.as_ref()), Rc::clone(&env));
+                    if self.is_error(&value) {
+                        return value;
+                    }
+                    if let Some(value) = value {
+                        buffer.push_str(&value.to_string());
+                    }
+                }
+                Some(Rc::new(Str::new(buffer)))
             } else if let Some(prefix) = node.as_any().downcast_ref::<PrefixExpr>() {
                 let right = self.eval(
                     Some(prefix.right.as_ref().unwrap().as_ref()),
@@ -130,16 +268,42 @@ impl Evaluator {
                     return right;
                 }
                 if infix.operator == "=" {
-                    let right = self.eval(
-                        Some(infix.right.as_ref().unwrap().as_ref()),
-                        Rc::clone(&env),
-                    );
-                    self.set(
-                        infix.left.as_ref().unwrap().to_string(),
-                        Rc::clone(right.as_ref().unwrap()),
-                        Rc::clone(&env),
-                    );
-                    right
+                    let left = infix.left.as_ref().unwrap();
+                    if let Some(idx_expr) = left.as_any().downcast_ref::<IndexExpr>() {
+                        let target = self.eval(Some(idx_expr.left.as_ref()), Rc::clone(&env));
+                        if self.is_error(&target) {
+                            return target;
+                        }
+                        let idx = self.eval(Some(idx_expr.index.as_ref()), Rc::clone(&env));
+                        if self.is_error(&idx) {
+                            return idx;
+                        }
+                        return self.eval_index_assign(
+                            target.unwrap(),
+                            idx.unwrap(),
+                            right.unwrap(),
+                        );
+                    }
+                    if let Some(member) = left.as_any().downcast_ref::<MemberExpr>() {
+                        let object = self.eval(Some(member.object.as_ref()), Rc::clone(&env));
+                        if self.is_error(&object) {
+                            return object;
+                        }
+                        let member = member.member.to_string();
+                        return Some(self.eval_member_assign(
+                            object.unwrap(),
+                            &member,
+                            right.unwrap(),
+                        ));
+                    }
+                    let name = infix.left.as_ref().unwrap().to_string();
+                    let assigned = env
+                        .borrow_mut()
+                        .assign(name, Rc::clone(right.as_ref().unwrap()));
+                    match assigned {
+                        Ok(()) => right,
+                        Err(msg) => Some(Rc::new(Error::new(msg))),
+                    }
                 } else {
                     let left =
                         self.eval(Some(infix.left.as_ref().unwrap().as_ref()), Rc::clone(&env));
@@ -152,33 +316,82 @@ impl Evaluator {
                 self.eval_if_expression(if_expr, Rc::clone(&env))
             } else if let Some(while_expr) = node.as_any().downcast_ref::<WhileExpr>() {
                 self.eval_while_expression(while_expr, Rc::clone(&env))
+            } else if let Some(for_expr) = node.as_any().downcast_ref::<ForExpr>() {
+                self.eval_for_expression(for_expr, Rc::clone(&env))
+            } else if let Some(range) = node.as_any().downcast_ref::<RangeExpr>() {
+                let start = self.eval(Some(range.start.as_ref()), Rc::clone(&env));
+                if self.is_error(&start) {
+                    return start;
+                }
+                let end = self.eval(Some(range.end.as_ref()), Rc::clone(&env));
+                if self.is_error(&end) {
+                    return end;
+                }
+                Some(self.eval_range(start.unwrap(), end.unwrap()))
+            } else if node.as_any().is::<Break>() {
+                Some(Rc::new(LoopControl::Break))
+            } else if node.as_any().is::<Continue>() {
+                Some(Rc::new(LoopControl::Continue))
             } else if let Some(block_stmt) = node.as_any().downcast_ref::<BlockStatement>() {
                 Some(self.eval_statements(&block_stmt.statements, env))
             } else if let Some(ret) = node.as_any().downcast_ref::<Return>() {
                 self.eval_ret_stmt(ret, Rc::clone(&env))
-            } else if let Some(let_stmt) = node.as_any().downcast_ref::<Let>() {
-                //TODO: make let immutable
-                let val = self.eval(Some(let_stmt.value.as_ref()), Rc::clone(&env));
+            } else if let Some(throw) = node.as_any().downcast_ref::<Throw>() {
+                let val = self.eval(Some(throw.value.as_ref()), Rc::clone(&env));
                 if self.is_error(&val) {
                     return val;
                 }
-                self.set(
-                    let_stmt.name.to_string(),
-                    Rc::clone(val.as_ref().unwrap()),
-                    Rc::clone(&env),
-                );
-                val
+                val.map(|val| self.thrown_error(val))
+            } else if let Some(try_expr) = node.as_any().downcast_ref::<TryExpr>() {
+                self.eval_try_expression(try_expr, Rc::clone(&env))
+            } else if let Some(let_stmt) = node.as_any().downcast_ref::<Let>() {
+                self.eval_declaration(&let_stmt.name, &let_stmt.value, Declaration::Let, env)
             } else if let Some(var) = node.as_any().downcast_ref::<Var>() {
-                let val = self.eval(Some(var.value.as_ref()), Rc::clone(&env));
-                if self.is_error(&val) {
-                    return val;
+                self.eval_declaration(&var.name, &var.value, Declaration::Var, env)
+            } else if let Some(import) = node.as_any().downcast_ref::<Import>() {
+                let module = self.eval_import(import);
+                if self.is_error(&module) {
+                    return module;
                 }
-                self.set(
-                    var.name.to_string(),
-                    Rc::clone(val.as_ref().unwrap()),
-                    Rc::clone(&env),
-                );
-                val
+                self.bind_declaration(&import.name, module.unwrap(), Declaration::Import, env)
+            } else if let Some(stmt) = node.as_any().downcast_ref::<StructStmt>() {
+                let def = Rc::new(Self::struct_def(stmt));
+                self.bind_declaration(&stmt.name, def, Declaration::Struct, env)
+            } else if let Some(stmt) = node.as_any().downcast_ref::<EnumStmt>() {
+                let def = Rc::new(Self::enum_def(stmt));
+                self.bind_declaration(&stmt.name, def, Declaration::Enum, env)
+            } else if let Some(stmt) = node.as_any().downcast_ref::<ImplStmt>() {
+                let def = self.eval(Some(stmt.name.as_ref()), Rc::clone(&env));
+                if self.is_error(&def) {
+                    return def;
+                }
+                let methods = stmt
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let function = Self::function(method, Rc::clone(&env));
+                        (function.trace_name(), Rc::new(function) as Rc<dyn Object>)
+                    })
+                    .collect();
+                Some(self.eval_impl(def.unwrap(), methods))
+            } else if let Some(match_expr) = node.as_any().downcast_ref::<MatchExpr>() {
+                self.eval_match_expression(match_expr, env)
+            } else if let Some(struct_expr) = node.as_any().downcast_ref::<StructExpr>() {
+                let def = self.eval(Some(struct_expr.name.as_ref()), Rc::clone(&env));
+                if self.is_error(&def) {
+                    return def;
+                }
+                let mut fields = Vec::with_capacity(struct_expr.fields.len());
+                for (name, value) in struct_expr.fields.iter() {
+                    let value = self.eval(Some(value.as_ref()), Rc::clone(&env));
+                    if self.is_error(&value) {
+                        return value;
+                    }
+                    fields.push((name.to_string(), value.unwrap()));
+                }
+                Some(self.eval_struct(def.unwrap(), fields))
+            } else if let Some(member) = node.as_any().downcast_ref::<MemberExpr>() {
+                self.eval_member(member, env, false)
             } else if let Some(identifier) = node.as_any().downcast_ref::<Identifier>() {
                 self.eval_identifier(identifier, env)
             } else if let Some(array) = node.as_any().downcast_ref::<ArrayExpr>() {
@@ -188,6 +401,20 @@ impl Evaluator {
                     elems.push(elem.unwrap());
                 }
                 Some(Rc::new(Array::new(elems)))
+            } else if let Some(hash) = node.as_any().downcast_ref::<HashExpr>() {
+                let mut pairs = Vec::with_capacity(hash.pairs.len());
+                for (key, value) in hash.pairs.iter() {
+                    let key = self.eval(Some(key.as_ref()), Rc::clone(&env));
+                    if self.is_error(&key) {
+                        return key;
+                    }
+                    let value = self.eval(Some(value.as_ref()), Rc::clone(&env));
+                    if self.is_error(&value) {
+                        return value;
+                    }
+                    pairs.push((key.unwrap(), value.unwrap()));
+                }
+                Some(self.build_hash(pairs))
             } else if let Some(idx_expr) = node.as_any().downcast_ref::<IndexExpr>() {
                 let left = self.eval(Some(idx_expr.left.as_ref()), Rc::clone(&env));
                 if self.is_error(&left) {
@@ -199,26 +426,17 @@ impl Evaluator {
                 }
                 self.eval_index_expr(left.unwrap(), idx.unwrap())
             } else if let Some(function) = node.as_any().downcast_ref::<FunctionExpr>() {
-                let env = Rc::clone(&env);
-                let body = function.body.as_ref().map(Rc::clone);
-                let parameters: Vec<Rc<dyn Expression>> =
-                    function.parameters.iter().map(Rc::clone).collect();
-                let function =
-                    Function::new(parameters, Rc::clone(&function.name), body, Rc::clone(&env));
-                let function_name = function.name.to_string();
-                let function_ref: Rc<dyn Object> = Rc::new(function);
-                self.set(function_name, Rc::clone(&function_ref), Rc::clone(&env));
+                let function_ref: Rc<dyn Object> =
+                    Rc::new(Self::function(function, Rc::clone(&env)));
+                if let Some(name) = &function.name {
+                    self.set(name.to_string(), Rc::clone(&function_ref), Rc::clone(&env));
+                }
                 Some(function_ref)
             } else if let Some(call) = node.as_any().downcast_ref::<CallExpr>() {
-                let function = self.eval(Some(call.function.as_ref()), Rc::clone(&env));
-                if self.is_error(&function) {
-                    return function;
+                match self.eval_call(call, env) {
+                    Ok(call) => self.apply_function(call.function, call.args, call.location),
+                    Err(error) => error,
                 }
-                let arguments = self.eval_expressions(&call.arguments, Rc::clone(&env));
-                if arguments.len() == 1 && self.is_error(arguments.first().unwrap()) {
-                    return function;
-                }
-                self.apply_function(function.unwrap(), arguments)
             } else {
                 Some(Rc::new(Null))
             }
@@ -227,12 +445,23 @@ impl Evaluator {
         }
     }
 
-    fn eval_infix_expr(
+    pub(crate) fn eval_infix_expr(
         &self,
         left: Rc<dyn Object>,
         right: Rc<dyn Object>,
         operator: &str,
     ) -> Option<Rc<dyn Object>> {
+        if left.get_type() == Type::Float || right.get_type() == Type::Float {
+            if let (Some(l), Some(r)) = (as_f64(left.as_ref()), as_f64(right.as_ref())) {
+                return Some(Self::eval_float_infix_expr(l, r, operator));
+            }
+        }
+        // null equals only null, so a missing value compares with anything
+        let (left_null, right_null) = (left.as_any().is::<Null>(), right.as_any().is::<Null>());
+        if (left_null || right_null) && (operator == "==" || operator == "!=") {
+            let equal = left_null && right_null;
+            return Some(Rc::new(Boolean::new(equal == (operator == "=="))));
+        }
         if left.get_type() != right.get_type() {
             return Some(Rc::new(Error::new(format!(
                 "incompatible types {} and {}",
@@ -240,36 +469,13 @@ impl Evaluator {
                 left.get_type()
             ))));
         }
-        if let Some(left) = left.as_any().downcast_ref::<Integer>() {
-            if let Some(right) = right.as_any().downcast_ref::<Integer>() {
-                let left = left.val;
-                let right = right.val;
-
-                match operator {
-                    "%" => Some(Rc::new(Integer::new(left % right))),
-                    "+" => Some(Rc::new(Integer::new(left + right))),
-                    "-" => Some(Rc::new(Integer::new(left - right))),
-                    "*" => Some(Rc::new(Integer::new(left * right))),
-                    "/" => Some(Rc::new(Integer::new(left / right))),
-                    "&" => Some(Rc::new(Integer::new(left & right))),
-                    "|" => Some(Rc::new(Integer::new(left | right))),
-                    "<<" => Some(Rc::new(Integer::new(left << right))),
-                    ">>" => Some(Rc::new(Integer::new(left >> right))),
-                    "^" => Some(Rc::new(Integer::new(left ^ right))),
-                    "!=" => Some(Rc::new(Boolean::new(left != right))),
-                    "==" => Some(Rc::new(Boolean::new(left == right))),
-                    ">=" => Some(Rc::new(Boolean::new(left >= right))),
-                    "<=" => Some(Rc::new(Boolean::new(left <= right))),
-                    ">" => Some(Rc::new(Boolean::new(left > right))),
-                    "<" => Some(Rc::new(Boolean::new(left < right))),
-                    _ => Some(Rc::new(Error::new(format!(
-                        "unknown operator {}",
-                        operator
-                    )))),
-                }
-            } else {
-                Some(Rc::new(Error::new(format!("miss right operator {}", left))))
-            }
+        if let (Some(l), Some(r)) = (
+            left.as_any().downcast_ref::<Integer>(),
+            right.as_any().downcast_ref::<Integer>(),
+        ) {
+            Some(Self::eval_integer_infix_expr(l.val, r.val, operator))
+        } else if let (Some(l), Some(r)) = (as_big(left.as_ref()), as_big(right.as_ref())) {
+            Some(Self::eval_big_infix_expr(&l, &r, operator))
         } else if let Some(left) = left.as_any().downcast_ref::<Boolean>() {
             if let Some(right) = right.as_any().downcast_ref::<Boolean>() {
                 let left = left.val;
@@ -313,6 +519,96 @@ impl Evaluator {
         }
     }
 
+    /// Int arithmetic that gives a `BigInt` instead of overflowing.
+    fn eval_integer_infix_expr(left: i64, right: i64, operator: &str) -> Rc<dyn Object> {
+        let val = match operator {
+            "/" | "%" if right == 0 => return Rc::new(Error::new("division by zero".to_string())),
+            "<<" | ">>" if right < 0 => {
+                return Rc::new(Error::new(format!("invalid shift amount {}", right)))
+            }
+            "+" => left.checked_add(right),
+            "-" => left.checked_sub(right),
+            "*" => left.checked_mul(right),
+            "/" => left.checked_div(right),
+            "%" => left.checked_rem(right),
+            "<<" if left == 0 => Some(0),
+            "<<" => Some(left << right.min(63)).filter(|val| right < 64 && val >> right == left),
+            ">>" => Some(left >> right.min(63)),
+            "&" => Some(left & right),
+            "|" => Some(left | right),
+            "^" => Some(left ^ right),
+            "!=" => return Rc::new(Boolean::new(left != right)),
+            "==" => return Rc::new(Boolean::new(left == right)),
+            ">=" => return Rc::new(Boolean::new(left >= right)),
+            "<=" => return Rc::new(Boolean::new(left <= right)),
+            ">" => return Rc::new(Boolean::new(left > right)),
+            "<" => return Rc::new(Boolean::new(left < right)),
+            _ => return Rc::new(Error::new(format!("unknown operator {}", operator))),
+        };
+        match val {
+            Some(val) => Rc::new(Integer::new(val)),
+            None => Self::eval_big_infix_expr(&BigInt::from(left), &BigInt::from(right), operator),
+        }
+    }
+
+    fn eval_big_infix_expr(left: &BigInt, right: &BigInt, operator: &str) -> Rc<dyn Object> {
+        match operator {
+            "+" => int_object(left.add(right)),
+            "-" => int_object(left.sub(right)),
+            "*" => int_object(left.mul(right)),
+            "/" | "%" => match left.div_rem(right) {
+                Some((quotient, _)) if operator == "/" => int_object(quotient),
+                Some((_, remainder)) => int_object(remainder),
+                None => Rc::new(Error::new("division by zero".to_string())),
+            },
+            "<<" | ">>" if *right < BigInt::from(0) => {
+                Rc::new(Error::new(format!("invalid shift amount {}", right)))
+            }
+            "<<" if left.is_zero() => int_object(left.clone()),
+            "<<" => match right.to_i64().and_then(|bits| u32::try_from(bits).ok()) {
+                Some(bits) => int_object(left.shl(bits)),
+                None => Rc::new(Error::new(format!("shift amount {} is too large", right))),
+            },
+            ">>" => {
+                let bits = right.to_i64().and_then(|bits| u32::try_from(bits).ok());
+                int_object(left.shr(bits.unwrap_or(u32::MAX)))
+            }
+            "&" => int_object(left.bitand(right)),
+            "|" => int_object(left.bitor(right)),
+            "^" => int_object(left.bitxor(right)),
+            "!=" => Rc::new(Boolean::new(left != right)),
+            "==" => Rc::new(Boolean::new(left == right)),
+            ">=" => Rc::new(Boolean::new(left >= right)),
+            "<=" => Rc::new(Boolean::new(left <= right)),
+            ">" => Rc::new(Boolean::new(left > right)),
+            "<" => Rc::new(Boolean::new(left < right)),
+            _ => Rc::new(Error::new(format!(
+                "unsupported operation {} {} {}",
+                left, operator, right
+            ))),
+        }
+    }
+
+    fn eval_float_infix_expr(left: f64, right: f64, operator: &str) -> Rc<dyn Object> {
+        match operator {
+            "+" => Rc::new(Float::new(left + right)),
+            "-" => Rc::new(Float::new(left - right)),
+            "*" => Rc::new(Float::new(left * right)),
+            "/" => Rc::new(Float::new(left / right)),
+            "%" => Rc::new(Float::new(left % right)),
+            "!=" => Rc::new(Boolean::new(left != right)),
+            "==" => Rc::new(Boolean::new(left == right)),
+            ">=" => Rc::new(Boolean::new(left >= right)),
+            "<=" => Rc::new(Boolean::new(left <= right)),
+            ">" => Rc::new(Boolean::new(left > right)),
+            "<" => Rc::new(Boolean::new(left < right)),
+            _ => Rc::new(Error::new(format!(
+                "unsupported operation {:?} {} {:?}",
+                left, operator, right
+            ))),
+        }
+    }
+
     fn eval_if_expression(
         &self,
         if_expr: &IfExpr,
@@ -330,19 +626,19 @@ impl Evaluator {
                         if let Some(ref consequence) = if_expr.consequence {
                             self.eval(Some(consequence.as_ref()), Rc::clone(&new_env))
                         } else {
-                            None
+                            Some(Rc::new(Null))
                         }
                     } else if let Some(ref el_if) = if_expr.el_if {
                         self.eval(Some(el_if.as_ref()), Rc::clone(&new_env))
                     } else if let Some(ref alternative) = if_expr.alternative {
                         self.eval(Some(alternative.as_ref()), Rc::clone(&new_env))
                     } else {
-                        None
+                        Some(Rc::new(Null))
                     }
                 }
-                None => None,
+                None => Some(Rc::new(Null)),
             },
-            None => None,
+            None => Some(Rc::new(Null)),
         }
     }
 
@@ -356,7 +652,7 @@ impl Evaluator {
         if self.is_error(&original_condition) {
             return original_condition;
         }
-        let mut obj: Option<Rc<dyn Object>> = None;
+        let mut obj: Option<Rc<dyn Object>> = Some(Rc::new(Null));
         loop {
             obj = match original_condition {
                 Some(ref condition) => match condition.as_any().downcast_ref::<Boolean>() {
@@ -366,23 +662,612 @@ impl Evaluator {
                         if condition.val {
                             let ret = self.eval(
                                 Some(while_expr.consequence.as_ref().unwrap().as_ref()),
-                                Rc::clone(&env),
+                                Rc::clone(&new_env),
                             );
+                            if self.is_error(&ret) || self.is_ret(&ret) {
+                                return ret;
+                            }
+                            let ret: Option<Rc<dyn Object>> = match self.loop_control(&ret) {
+                                Some(LoopControl::Break) => {
+                                    obj = Some(Rc::new(Null));
+                                    break;
+                                }
+                                Some(LoopControl::Continue) => Some(Rc::new(Null)),
+                                None => ret,
+                            };
                             original_condition =
-                                self.eval(Some(while_expr.condition.as_ref()), Rc::clone(&new_env));
+                                self.eval(Some(while_expr.condition.as_ref()), Rc::clone(&env));
                             ret
                         } else {
                             break;
                         }
                     }
-                    None => None,
+                    None => break,
                 },
-                None => None,
+                None => break,
             }
         }
         obj
     }
 
+    fn eval_declaration(
+        &self,
+        name: &Rc<dyn Expression>,
+        value: &Rc<dyn Expression>,
+        declaration: Declaration,
+        env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        let val = self.eval(Some(value.as_ref()), Rc::clone(&env));
+        if self.is_error(&val) {
+            return val;
+        }
+        self.bind_declaration(name, val.unwrap(), declaration, env)
+    }
+
+    fn bind_declaration(
+        &self,
+        name: &Rc<dyn Expression>,
+        val: Rc<dyn Object>,
+        declaration: Declaration,
+        env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        let declared = env.borrow_mut().declare(
+            name.to_string(),
+            Rc::clone(&val),
+            declaration,
+            name.location(),
+        );
+        match declared {
+            Ok(()) => Some(val),
+            Err(msg) => Some(Rc::new(Error::new(msg))),
+        }
+    }
+
+    /// Runs the imported file in a scope of its own the first time, later
+    /// imports of the same file share its module.
+    fn eval_import(&self, import: &Import) -> Option<Rc<dyn Object>> {
+        let loaded = self
+            .loader
+            .borrow_mut()
+            .load(&import.path, import.location.as_ref());
+        let (key, program) = match loaded {
+            Ok(Load::Cached(module)) => return Some(module),
+            Ok(Load::Parsed(key, program)) => (key, program),
+            Err(msg) => return Some(Rc::new(Error::new(msg))),
+        };
+        let env = Rc::new(RefCell::new(Environment::default()));
+        let evaluated = self.eval_statements(&program.statements, Rc::clone(&env));
+        let evaluated = self.finish_tail_call(Some(evaluated));
+        if self.is_error(&evaluated) {
+            self.loader.borrow_mut().finish(key, None);
+            let name = module_trace_name(&import.path);
+            return evaluated.map(|error| Error::trace(error, name, import.location()));
+        }
+        if self.is_ret(&evaluated) {
+            // like the end of a call, `ret` gives its value in place of the module
+            self.loader.borrow_mut().finish(key, None);
+            return self.extract_ret_val(evaluated);
+        }
+        let module: Rc<dyn Object> = Rc::new(Module::new(module_name(&import.path), env));
+        self.loader
+            .borrow_mut()
+            .finish(key, Some(Rc::clone(&module)));
+        Some(module)
+    }
+
+    /// `object.member`, `called` when it is the function of a call, so a
+    /// missing member is a missing method.
+    fn eval_member(
+        &self,
+        member: &MemberExpr,
+        env: Rc<RefCell<Environment>>,
+        called: bool,
+    ) -> Option<Rc<dyn Object>> {
+        let object = self.eval(Some(member.object.as_ref()), Rc::clone(&env));
+        if self.is_error(&object) {
+            return object;
+        }
+        let name = member.member.to_string();
+        Some(self.eval_member_expr(object.unwrap(), &name, called))
+    }
+
+    pub(crate) fn eval_member_expr(
+        &self,
+        object: Rc<dyn Object>,
+        member: &str,
+        called: bool,
+    ) -> Rc<dyn Object> {
+        let missing = if called { "method" } else { "field" };
+        if let Some(module) = object.as_any().downcast_ref::<Module>() {
+            module.member(member).unwrap_or_else(|| {
+                Rc::new(Error::new(format!(
+                    "module '{}' has no member '{}'",
+                    module.name, member
+                )))
+            })
+        } else if let Some(value) = object.as_any().downcast_ref::<Struct>() {
+            value
+                .get(member)
+                .or_else(|| method::method(&object, member))
+                .unwrap_or_else(|| {
+                    Rc::new(Error::new(format!(
+                        "{} has no {} '{}'",
+                        value.def().name,
+                        missing,
+                        member
+                    )))
+                })
+        } else if let Some(def) = object.as_any().downcast_ref::<EnumDef>() {
+            match method::method(&object, member) {
+                Some(method) if def.variant(member).is_none() => method,
+                _ => enumeration::variant(&object, member)
+                    .unwrap_or_else(|msg| Rc::new(Error::new(msg))),
+            }
+        } else if let Some(method) = method::method(&object, member) {
+            method
+        } else if let Some(function) = self.built_in_method(&object, member) {
+            Rc::new(BoundMethod::new(member.to_string(), object, function))
+        } else {
+            Rc::new(Error::new(format!(
+                "{} has no {} '{}'",
+                object.get_type(),
+                if called { "method" } else { "member" },
+                member
+            )))
+        }
+    }
+
+    /// The built-in function `object.name` calls with `object` first, only
+    /// values of built-in types have them.
+    fn built_in_method(&self, object: &Rc<dyn Object>, name: &str) -> Option<Rc<dyn Object>> {
+        match object.get_type() {
+            Type::Struct(_) | Type::StructDef | Type::Enum(_) | Type::EnumDef => None,
+            _ => self.build_in_fns.get(name).cloned(),
+        }
+    }
+
+    pub(crate) fn eval_member_assign(
+        &self,
+        object: Rc<dyn Object>,
+        member: &str,
+        value: Rc<dyn Object>,
+    ) -> Rc<dyn Object> {
+        match object.as_any().downcast_ref::<Struct>() {
+            Some(target) => match target.set(member, Rc::clone(&value)) {
+                Ok(()) => value,
+                Err(msg) => Rc::new(Error::new(msg)),
+            },
+            None => Rc::new(Error::new(format!(
+                "cannot assign to member '{}' of {}",
+                member,
+                object.get_type()
+            ))),
+        }
+    }
+
+    pub(crate) fn struct_def(stmt: &StructStmt) -> StructDef {
+        let fields = stmt
+            .fields
+            .iter()
+            .map(|field| {
+                let typ = field
+                    .as_any()
+                    .downcast_ref::<Identifier>()
+                    .and_then(|field| field.typ.clone())
+                    .unwrap_or(Type::Unknown);
+                (field.to_string(), typ)
+            })
+            .collect();
+        StructDef::new(Rc::from(stmt.name.to_string()), fields)
+    }
+
+    pub(crate) fn eval_struct(
+        &self,
+        def: Rc<dyn Object>,
+        fields: Vec<(String, Rc<dyn Object>)>,
+    ) -> Rc<dyn Object> {
+        match build_struct(&def, fields) {
+            Ok(value) => Rc::new(value),
+            Err(msg) => Rc::new(Error::new(msg)),
+        }
+    }
+
+    pub(crate) fn eval_impl(
+        &self,
+        def: Rc<dyn Object>,
+        methods: Vec<(String, Rc<dyn Object>)>,
+    ) -> Rc<dyn Object> {
+        match method::implement(&def, methods) {
+            Ok(()) => def,
+            Err(msg) => Rc::new(Error::new(msg)),
+        }
+    }
+
+    pub(crate) fn enum_def(stmt: &EnumStmt) -> EnumDef {
+        let variants = stmt
+            .variants
+            .iter()
+            .map(|(name, types)| (name.to_string(), types.clone()))
+            .collect();
+        EnumDef::new(Rc::from(stmt.name.to_string()), variants)
+    }
+
+    pub(crate) fn eval_variant(
+        &self,
+        constructor: &Constructor,
+        args: Vec<Rc<dyn Object>>,
+    ) -> Rc<dyn Object> {
+        match constructor.call(args) {
+            Ok(value) => Rc::new(value),
+            Err(msg) => Rc::new(Error::new(msg)),
+        }
+    }
+
+    fn eval_match_expression(
+        &self,
+        match_expr: &MatchExpr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        let value = self.eval(Some(match_expr.value.as_ref()), Rc::clone(&env));
+        if self.is_error(&value) {
+            return value;
+        }
+        let value = value.unwrap();
+        for arm in match_expr.arms.iter() {
+            let values = match self.match_pattern(&arm.pattern, &value) {
+                Some(values) => values,
+                None => continue,
+            };
+            let arm_env = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(&env)))));
+            for (name, value) in arm.pattern.bindings().iter().zip(values) {
+                arm_env.borrow_mut().store.insert(name.to_string(), value);
+            }
+            if let Some(guard) = &arm.guard {
+                let guard = self.eval(Some(guard.as_ref()), Rc::clone(&arm_env));
+                if self.is_error(&guard) {
+                    return guard;
+                }
+                let passed = guard
+                    .as_ref()
+                    .and_then(|guard| guard.as_any().downcast_ref::<Boolean>())
+                    .is_some_and(|guard| guard.val);
+                if !passed {
+                    continue;
+                }
+            }
+            return self.eval(Some(arm.body.as_ref()), arm_env);
+        }
+        Some(self.no_match(&value))
+    }
+
+    /// The values the names of `pattern` bind, in the order of
+    /// `Pattern::bindings`, if `value` fits it.
+    pub(crate) fn match_pattern(
+        &self,
+        pattern: &Pattern,
+        value: &Rc<dyn Object>,
+    ) -> Option<Vec<Rc<dyn Object>>> {
+        let mut values = vec![];
+        self.collect_matches(pattern, value, &mut values)
+            .then_some(values)
+    }
+
+    fn collect_matches(
+        &self,
+        pattern: &Pattern,
+        value: &Rc<dyn Object>,
+        values: &mut Vec<Rc<dyn Object>>,
+    ) -> bool {
+        match pattern {
+            Pattern::Wildcard(_) => true,
+            Pattern::Binding(_) => {
+                values.push(Rc::clone(value));
+                true
+            }
+            Pattern::Literal(literal) => self.matches_literal(literal.as_ref(), value),
+            Pattern::Range(start, end) => {
+                let bound = |expr: &Rc<dyn Expression>| {
+                    expr.as_any()
+                        .downcast_ref::<IntExpr>()
+                        .map_or(0, |int_expr| int_expr.value)
+                };
+                value
+                    .as_any()
+                    .downcast_ref::<Integer>()
+                    .is_some_and(|integer| (bound(start)..bound(end)).contains(&integer.val))
+            }
+            Pattern::Variant {
+                name,
+                variant,
+                values: patterns,
+            } => {
+                let found = match value.as_any().downcast_ref::<Variant>() {
+                    Some(found) => found,
+                    None => return false,
+                };
+                *found.def().name == *name.to_string()
+                    && found.name() == variant.to_string()
+                    && found.values.len() == patterns.len()
+                    && patterns
+                        .iter()
+                        .zip(found.values.iter())
+                        .all(|(pattern, value)| self.collect_matches(pattern, value, values))
+            }
+            Pattern::Array { items, rest, .. } => {
+                let array = match value.as_any().downcast_ref::<Array>() {
+                    Some(array) => array,
+                    None => return false,
+                };
+                let elements = array.elements.borrow().clone();
+                let fits = match rest {
+                    Some(_) => elements.len() >= items.len(),
+                    None => elements.len() == items.len(),
+                };
+                if !fits
+                    || !items
+                        .iter()
+                        .zip(elements.iter())
+                        .all(|(pattern, value)| self.collect_matches(pattern, value, values))
+                {
+                    return false;
+                }
+                match rest {
+                    Some(rest) => {
+                        let rest_value: Rc<dyn Object> =
+                            Rc::new(Array::new(elements[items.len()..].to_vec()));
+                        self.collect_matches(rest, &rest_value, values)
+                    }
+                    None => true,
+                }
+            }
+        }
+    }
+
+    fn matches_literal(&self, literal: &dyn Expression, value: &Rc<dyn Object>) -> bool {
+        let value = value.as_any();
+        if let Some(int_expr) = literal.as_any().downcast_ref::<IntExpr>() {
+            value
+                .downcast_ref::<Integer>()
+                .is_some_and(|integer| integer.val == int_expr.value)
+        } else if let Some(big_int_expr) = literal.as_any().downcast_ref::<BigIntExpr>() {
+            value
+                .downcast_ref::<BigInt>()
+                .is_some_and(|big| *big == big_int_expr.value)
+        } else if let Some(float_expr) = literal.as_any().downcast_ref::<FloatExpr>() {
+            value
+                .downcast_ref::<Float>()
+                .is_some_and(|float| float.val == float_expr.value)
+        } else if let Some(str_expr) = literal.as_any().downcast_ref::<StrExpr>() {
+            value
+                .downcast_ref::<Str>()
+                .is_some_and(|string| string.val == str_expr.value)
+        } else if let Some(bool_expr) = literal.as_any().downcast_ref::<BoolExpr>() {
+            value
+                .downcast_ref::<Boolean>()
+                .is_some_and(|boolean| boolean.val == bool_expr.value)
+        } else {
+            false
+        }
+    }
+
+    /// The error of a `match` that has no arm for `value`.
+    pub(crate) fn no_match(&self, value: &Rc<dyn Object>) -> Rc<dyn Object> {
+        Rc::new(Error::new(format!("no match arm for {}", value.inspect())))
+    }
+
+    fn eval_for_expression(
+        &self,
+        for_expr: &ForExpr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        // ranges are walked lazily, anything else is copied before the first iteration
+        let items: Box<dyn Iterator<Item = Rc<dyn Object>>> =
+            if let Some(range) = for_expr.iterable.as_any().downcast_ref::<RangeExpr>() {
+                let start = self.eval(Some(range.start.as_ref()), Rc::clone(&env));
+                if self.is_error(&start) {
+                    return start;
+                }
+                let end = self.eval(Some(range.end.as_ref()), Rc::clone(&env));
+                if self.is_error(&end) {
+                    return end;
+                }
+                let (start, end) = match self.range_bounds(start.unwrap(), end.unwrap()) {
+                    Ok(bounds) => bounds,
+                    Err(error) => return Some(Error::locate(error, || range.location())),
+                };
+                Box::new((start..end).map(|val| -> Rc<dyn Object> { Rc::new(Integer::new(val)) }))
+            } else {
+                let iterable = self.eval(Some(for_expr.iterable.as_ref()), Rc::clone(&env));
+                if self.is_error(&iterable) {
+                    return iterable;
+                }
+                let items = self.iteration_items(iterable.unwrap());
+                match items.as_any().downcast_ref::<Array>() {
+                    Some(array) => Box::new(array.elements.take().into_iter()),
+                    None => return Some(Error::locate(items, || for_expr.iterable.location())),
+                }
+            };
+        let mut result: Option<Rc<dyn Object>> = Some(Rc::new(Null));
+        for item in items {
+            let iteration_env = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(&env)))));
+            iteration_env
+                .borrow_mut()
+                .store
+                .insert(for_expr.variable.to_string(), item);
+            let evaluated: Option<Rc<dyn Object>> = match for_expr.body {
+                Some(ref body) => self.eval(Some(body.as_ref()), iteration_env),
+                None => Some(Rc::new(Null)),
+            };
+            if self.is_error(&evaluated) || self.is_ret(&evaluated) {
+                return evaluated;
+            }
+            result = match self.loop_control(&evaluated) {
+                Some(LoopControl::Break) => {
+                    result = Some(Rc::new(Null));
+                    break;
+                }
+                Some(LoopControl::Continue) => Some(Rc::new(Null)),
+                None => evaluated,
+            };
+        }
+        result
+    }
+
+    /// The ints from `start` up to, but not including, `end`.
+    pub(crate) fn eval_range(&self, start: Rc<dyn Object>, end: Rc<dyn Object>) -> Rc<dyn Object> {
+        match self.range_bounds(start, end) {
+            Ok((start, end)) => Rc::new(Array::new(
+                (start..end)
+                    .map(|val| -> Rc<dyn Object> { Rc::new(Integer::new(val)) })
+                    .collect(),
+            )),
+            Err(error) => error,
+        }
+    }
+
+    fn range_bounds(
+        &self,
+        start: Rc<dyn Object>,
+        end: Rc<dyn Object>,
+    ) -> Result<(i64, i64), Rc<dyn Object>> {
+        match (
+            start.as_any().downcast_ref::<Integer>(),
+            end.as_any().downcast_ref::<Integer>(),
+        ) {
+            (Some(start), Some(end)) => Ok((start.val, end.val)),
+            _ if start.get_type() == Type::Int && end.get_type() == Type::Int => {
+                let big = if start.as_any().is::<BigInt>() {
+                    start
+                } else {
+                    end
+                };
+                Err(Rc::new(Error::new(format!(
+                    "range bound {} is out of range",
+                    big
+                ))))
+            }
+            _ => Err(Rc::new(Error::new(format!(
+                "range bounds must be int, got {}..{}",
+                start.get_type(),
+                end.get_type()
+            )))),
+        }
+    }
+
+    /// A fresh array with the items a `for` loop visits: array elements or the chars of a str.
+    pub(crate) fn iteration_items(&self, iterable: Rc<dyn Object>) -> Rc<dyn Object> {
+        if let Some(array) = iterable.as_any().downcast_ref::<Array>() {
+            Rc::new(Array::new(array.elements.borrow().clone()))
+        } else if let Some(string) = iterable.as_any().downcast_ref::<Str>() {
+            Rc::new(Array::new(
+                string
+                    .val
+                    .chars()
+                    .map(|ch| -> Rc<dyn Object> { Rc::new(Str::new(ch.to_string())) })
+                    .collect(),
+            ))
+        } else {
+            Rc::new(Error::new(format!(
+                "cannot iterate over {}",
+                iterable.get_type()
+            )))
+        }
+    }
+
+    fn eval_try_expression(
+        &self,
+        try_expr: &TryExpr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Option<Rc<dyn Object>> {
+        let body_env = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(&env)))));
+        let result: Option<Rc<dyn Object>> = match try_expr.body {
+            Some(ref body) => self.eval(Some(body.as_ref()), body_env),
+            None => Some(Rc::new(Null)),
+        };
+        // a call in tail position still raises inside the try
+        let result = self.finish_tail_call(result);
+        let error = match result {
+            Some(ref obj) => match obj.as_any().downcast_ref::<Error>() {
+                Some(error) => error,
+                None => return result,
+            },
+            None => return result,
+        };
+        let handler_env = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(&env)))));
+        // the caught value shadows any outer binding of the same name
+        handler_env
+            .borrow_mut()
+            .store
+            .insert(try_expr.name.to_string(), self.error_value(error));
+        match try_expr.handler {
+            Some(ref handler) => self.eval(Some(handler.as_ref()), handler_env),
+            None => Some(Rc::new(Null)),
+        }
+    }
+
+    /// Turns a thrown value into an error; maps with a "message" key keep it as
+    /// the message, their other keys, and the location of a caught error.
+    pub(crate) fn thrown_error(&self, val: Rc<dyn Object>) -> Rc<dyn Object> {
+        let message = if let Some(string) = val.as_any().downcast_ref::<Str>() {
+            string.val.clone()
+        } else if let Some(hash) = val.as_any().downcast_ref::<Hash>() {
+            let pairs = hash.pairs.borrow();
+            let get = |key: &str| {
+                pairs
+                    .get(&HashKey::Str(key.to_string()))
+                    .map(|pair| Rc::clone(&pair.value))
+            };
+            let mut error = Error::new(match get("message") {
+                Some(message) => message.to_string(),
+                None => val.inspect(),
+            });
+            error.location = caught_location(get);
+            error.value = Some(Rc::clone(&val));
+            return Rc::new(error);
+        } else {
+            val.inspect()
+        };
+        Rc::new(Error::new(message))
+    }
+
+    /// The map a `catch` binds: the keys of a thrown map, with message, location,
+    /// line and column of the caught error.
+    pub(crate) fn error_value(&self, error: &Error) -> Rc<dyn Object> {
+        let field = |name: &str| -> Rc<dyn Object> { Rc::new(Str::new(name.to_string())) };
+        let (location, line, column): (Rc<dyn Object>, Rc<dyn Object>, Rc<dyn Object>) =
+            match error.location {
+                Some(ref location) => (
+                    Rc::new(Str::new(location.to_string())),
+                    Rc::new(Integer::new(location.line as i64)),
+                    Rc::new(Integer::new(location.position as i64)),
+                ),
+                None => (Rc::new(Null), Rc::new(Null), Rc::new(Null)),
+            };
+        let mut pairs = match error
+            .value
+            .as_ref()
+            .and_then(|value| value.as_any().downcast_ref::<Hash>())
+        {
+            Some(hash) => hash
+                .pairs
+                .borrow()
+                .values()
+                .map(|pair| (Rc::clone(&pair.key), Rc::clone(&pair.value)))
+                .collect(),
+            None => vec![],
+        };
+        pairs.extend([
+            (
+                field("message"),
+                Rc::new(Str::new(error.val.clone())) as Rc<dyn Object>,
+            ),
+            (field("location"), location),
+            (field("line"), line),
+            (field("column"), column),
+        ]);
+        self.build_hash(pairs)
+    }
+
     fn eval_statements(
         &self,
         stmts: &[Rc<dyn Statement>],
@@ -391,22 +1276,27 @@ impl Evaluator {
         let mut result = None;
         for stmt in stmts.iter() {
             result = self.eval(Some(stmt.as_ref()), Rc::clone(&env));
-            if let Some(ref res) = result {
-                if let Some(ret) = res.as_any().downcast_ref::<Ret>() {
-                    let return_value = Rc::clone(&ret.val);
-                    return return_value;
-                }
+            // a `ret` leaves every enclosing block until the function call unwraps it,
+            // `break` and `continue` until the enclosing loop does
+            if self.is_ret(&result)
+                || self.is_error(&result)
+                || self.loop_control(&result).is_some()
+            {
+                return result.unwrap();
             }
         }
-        if let Some(result) = result {
-            result
-        } else {
-            process::exit(1)
-        }
+        result.unwrap_or_else(|| Rc::new(Null))
     }
 
     fn eval_ret_stmt(&self, ret: &Return, env: Rc<RefCell<Environment>>) -> Option<Rc<dyn Object>> {
         match &ret.return_value {
+            Some(expr) if expr.as_any().is::<CallExpr>() => {
+                let call = expr.as_any().downcast_ref::<CallExpr>().unwrap();
+                match self.eval_call(call, env) {
+                    Ok(tail) => Some(Rc::new(Ret::new(Rc::new(tail)))),
+                    Err(error) => error,
+                }
+            }
             Some(expr) => {
                 let val = self.eval(Some(expr.as_ref()), env);
                 match val {
@@ -440,7 +1330,7 @@ impl Evaluator {
         }
     }
 
-    fn eval_index_expr(
+    pub(crate) fn eval_index_expr(
         &self,
         left: Rc<dyn Object>,
         index: Rc<dyn Object>,
@@ -449,6 +1339,20 @@ impl Evaluator {
             && index.get_type() == Type::Int
         {
             self.eval_array_index_expr(left, index)
+        } else if let Some(hash) = left.as_any().downcast_ref::<Hash>() {
+            let key = match index.hash_key() {
+                Some(key) => key,
+                None => {
+                    return Some(Rc::new(Error::new(format!(
+                        "unusable as map key: {}",
+                        index.get_type()
+                    ))))
+                }
+            };
+            match hash.pairs.borrow().get(&key) {
+                Some(pair) => Some(Rc::clone(&pair.value)),
+                None => Some(Rc::new(Null)),
+            }
         } else {
             Some(Rc::new(Error::new(format!(
                 "index operation not suported: {}",
@@ -457,27 +1361,100 @@ impl Evaluator {
         }
     }
 
+    pub(crate) fn eval_index_assign(
+        &self,
+        left: Rc<dyn Object>,
+        index: Rc<dyn Object>,
+        value: Rc<dyn Object>,
+    ) -> Option<Rc<dyn Object>> {
+        if let Some(hash) = left.as_any().downcast_ref::<Hash>() {
+            let key = match index.hash_key() {
+                Some(key) => key,
+                None => {
+                    return Some(Rc::new(Error::new(format!(
+                        "unusable as map key: {}",
+                        index.get_type()
+                    ))))
+                }
+            };
+            let pair = HashPair {
+                key: index,
+                value: Rc::clone(&value),
+            };
+            hash.pairs.borrow_mut().insert(key, pair);
+            Some(value)
+        } else if let Some(array) = left.as_any().downcast_ref::<Array>() {
+            let mut elements = array.elements.borrow_mut();
+            let pos = match index.as_any().downcast_ref::<Integer>() {
+                Some(pos) if pos.val >= 0 && (pos.val as usize) < elements.len() => {
+                    pos.val as usize
+                }
+                _ => {
+                    return Some(Rc::new(Error::new(format!(
+                        "invalid array index {}",
+                        index
+                    ))))
+                }
+            };
+            elements[pos] = Rc::clone(&value);
+            Some(value)
+        } else {
+            Some(Rc::new(Error::new(format!(
+                "index assignment not suported: {}",
+                left.get_type()
+            ))))
+        }
+    }
+
+    pub(crate) fn build_hash(
+        &self,
+        pairs: Vec<(Rc<dyn Object>, Rc<dyn Object>)>,
+    ) -> Rc<dyn Object> {
+        let mut map = BTreeMap::new();
+        for (key, value) in pairs {
+            let hash_key = match key.hash_key() {
+                Some(hash_key) => hash_key,
+                None => {
+                    return Rc::new(Error::new(format!(
+                        "unusable as map key: {}",
+                        key.get_type()
+                    )))
+                }
+            };
+            map.insert(hash_key, HashPair { key, value });
+        }
+        Rc::new(Hash::new(map))
+    }
+
     fn eval_array_index_expr(
         &self,
         left: Rc<dyn Object>,
         index: Rc<dyn Object>,
     ) -> Option<Rc<dyn Object>> {
-        let index = index.as_any().downcast_ref::<Integer>().unwrap().val;
+        let index = match index.as_any().downcast_ref::<Integer>() {
+            Some(index) => index.val,
+            None => {
+                let msg = format!("invalid {} index {}", left.get_type(), index);
+                return Some(Rc::new(Error::new(msg)));
+            }
+        };
         if let Some(array) = left.as_any().downcast_ref::<Array>() {
             let arr = array.elements.borrow_mut();
-            let max = arr.len() as i64 - 1;
-            if index < 0 || index > max || max < 0 {
-                return Some(Rc::new(Null));
+            match usize::try_from(index).ok().and_then(|index| arr.get(index)) {
+                Some(element) => Some(Rc::clone(element)),
+                None => Some(Rc::new(Error::new(format!(
+                    "invalid array index {}",
+                    index
+                )))),
             }
-            let element = arr.get(index as usize).unwrap();
-            Some(Rc::clone(element))
         } else if let Some(string) = left.as_any().downcast_ref::<Str>() {
-            let max = string.val.len() as i64 - 1;
-            if index < 0 || index > max || max < 0 {
-                return Some(Rc::new(Null));
+            let ch = usize::try_from(index)
+                .ok()
+                .and_then(|index| string.val.chars().nth(index));
+            match ch {
+                Some(ch) => Some(Rc::new(Str::new(ch.to_string()))),
+                None => Some(Rc::new(Error::new(format!("invalid str index {}", index)))),
             }
-            let ch = string.val.chars().nth(index as usize).unwrap();
-            Some(Rc::new(Str::new(ch.to_string())))
         } else {
             Some(Rc::new(Error::new(format!(
                 "index operation not suported: {}[{}]",
@@ -487,7 +1464,11 @@ impl Evaluator {
         }
     }
 
-    fn eval_prefix_expr(&self, right: Rc<dyn Object>, operator: &str) -> Option<Rc<dyn Object>> {
+    pub(crate) fn eval_prefix_expr(
+        &self,
+        right: Rc<dyn Object>,
+        operator: &str,
+    ) -> Option<Rc<dyn Object>> {
         match operator {
             "!" => self.eval_bang_operator(right),
             "-" => self.eval_minus_prefix_operator(right),
@@ -512,7 +1493,14 @@ impl Evaluator {
 
     fn eval_minus_prefix_operator(&self, right: Rc<dyn Object>) -> Option<Rc<dyn Object>> {
         if let Some(integer) = right.as_any().downcast_ref::<Integer>() {
-            Some(Rc::new(Integer::new(-integer.val)))
+            match integer.val.checked_neg() {
+                Some(val) => Some(Rc::new(Integer::new(val))),
+                None => Some(int_object(BigInt::from(integer.val).neg())),
+            }
+        } else if let Some(big) = right.as_any().downcast_ref::<BigInt>() {
+            Some(int_object(big.neg()))
+        } else if let Some(float) = right.as_any().downcast_ref::<Float>() {
+            Some(Rc::new(Float::new(-float.val)))
         } else {
             Some(Rc::new(Error::new(format!(
                 "invalid expression '-{}'",
@@ -525,28 +1513,65 @@ impl Evaluator {
         matches!(to_check, Some(check) if check.get_type() == Type::Error)
     }
 
+    fn is_ret(&self, to_check: &Option<Rc<dyn Object>>) -> bool {
+        matches!(to_check, Some(check) if check.as_any().is::<Ret>())
+    }
+
+    fn loop_control(&self, to_check: &Option<Rc<dyn Object>>) -> Option<LoopControl> {
+        to_check
+            .as_ref()
+            .and_then(|check| check.as_any().downcast_ref::<LoopControl>().copied())
+    }
+
+    fn function(function: &FunctionExpr, env: Rc<RefCell<Environment>>) -> Function {
+        let body = function.body.as_ref().map(Rc::clone);
+        let parameters: Vec<Rc<dyn Expression>> =
+            function.parameters.iter().map(Rc::clone).collect();
+        let name = function.name.as_ref().map(Rc::clone);
+        Function::new(parameters, name, body, env)
+    }
+
     fn apply_function(
         &self,
         function: Rc<dyn Object>,
         args: Vec<Option<Rc<dyn Object>>>,
+        location: Option<Location>,
     ) -> Option<Rc<dyn Object>> {
-        if let Some(function) = function.as_any().downcast_ref::<Function>() {
-            let new_env = self.create_function_environment(function, &args);
-            let evaluated = self.eval(
-                Some(function.body.as_ref().unwrap().as_ref()),
-                Rc::clone(&new_env),
-            );
-            if self.is_error(&evaluated) {
-                return evaluated;
+        if let Some(method) = function.as_any().downcast_ref::<BoundMethod>() {
+            let args = method.arguments(args);
+            return self.apply_function(Rc::clone(&method.function), args, location);
+        }
+        if let Some(callee) = function.as_any().downcast_ref::<Function>() {
+            if callee.parameters.len() != args.len() {
+                return Some(Rc::new(Error::new(format!(
+                    "wrong number of arguments: want={}, got={}",
+                    callee.parameters.len(),
+                    args.len()
+                ))));
+            }
+            let here = stack_address();
+            if self.depth.get() == 0 {
+                self.stack_base.set(here);
+            }
+            // each call nests native calls, stop before the thread runs out
+            let stack_used = self.stack_base.get().abs_diff(here);
+            if self.depth.get() >= self.max_depth || stack_used > self.stack_size {
+                return Some(Rc::new(Error::new("stack overflow".to_string())));
             }
-            self.extract_ret_val(evaluated)
+            self.depth.set(self.depth.get() + 1);
+            let result = self.eval_function_calls(function, args, location);
+            self.depth.set(self.depth.get() - 1);
+            result
         } else if let Some(built_in) = function.as_any().downcast_ref::<BuiltIn>() {
             let mut arguments = Vec::with_capacity(5);
             for arg in args {
                 arguments.push(arg.unwrap());
             }
-            let func = built_in.function;
-            Some(func(&arguments))
+            let mut caller = self;
+            Some(built_in.call(&arguments, &mut caller))
+        } else if let Some(constructor) = function.as_any().downcast_ref::<Constructor>() {
+            let args = args.into_iter().flatten().collect();
+            Some(self.eval_variant(constructor, args))
         } else {
             Some(Rc::new(Error::new(format!(
                 "not a function {}",
@@ -555,9 +1580,96 @@ impl Evaluator {
         }
     }
 
+    /// Runs `function`, then in its place each function it calls in tail
+    /// position, without nesting the calls.
+    fn eval_function_calls(
+        &self,
+        mut function: Rc<dyn Object>,
+        mut args: Arguments,
+        location: Option<Location>,
+    ) -> Option<Rc<dyn Object>> {
+        loop {
+            let callee = function
+                .as_any()
+                .downcast_ref::<Function>()
+                .expect("only functions run in a loop");
+            let new_env = self.create_function_environment(callee, &args);
+            let evaluated = self.eval(
+                Some(callee.body.as_ref().unwrap().as_ref()),
+                Rc::clone(&new_env),
+            );
+            if self.is_error(&evaluated) {
+                let name = callee.trace_name();
+                return evaluated.map(|error| Error::trace(error, name, location));
+            }
+            let val = self.extract_ret_val(evaluated);
+            let tail = match val
+                .as_ref()
+                .and_then(|val| val.as_any().downcast_ref::<TailCall>())
+            {
+                Some(tail) => tail,
+                None => return val,
+            };
+            let (next, next_args) = match tail.function.as_any().downcast_ref::<BoundMethod>() {
+                Some(method) => (
+                    Rc::clone(&method.function),
+                    method.arguments(tail.args.clone()),
+                ),
+                None => (Rc::clone(&tail.function), tail.args.clone()),
+            };
+            let loops = matches!(
+                next.as_any().downcast_ref::<Function>(),
+                Some(next) if next.parameters.len() == next_args.len()
+            );
+            if !loops {
+                // anything else is called as if the `ret` had made the call
+                let result = self.apply_function(next, next_args, tail.location.clone());
+                let result = result.map(|obj| Error::locate(obj, || tail.location.clone()));
+                if self.is_error(&result) {
+                    let name = callee.trace_name();
+                    return result.map(|error| Error::trace(error, name, location));
+                }
+                return result;
+            }
+            function = next;
+            args = next_args;
+        }
+    }
+
+    /// Makes the call a `ret` left pending where no function call unwraps it,
+    /// giving an error or the `ret` of the result.
+    pub(crate) fn finish_tail_call(
+        &self,
+        evaluated: Option<Rc<dyn Object>>,
+    ) -> Option<Rc<dyn Object>> {
+        let tail = match evaluated
+            .as_ref()
+            .and_then(|obj| obj.as_any().downcast_ref::<Ret>())
+            .and_then(|ret| ret.val.as_any().downcast_ref::<TailCall>())
+        {
+            Some(tail) => tail,
+            None => return evaluated,
+        };
+        let result = self.apply_function(
+            Rc::clone(&tail.function),
+            tail.args.clone(),
+            tail.location.clone(),
+        )?;
+        let result = Error::locate(result, || tail.location.clone());
+        if result.get_type() == Type::Error {
+            return Some(result);
+        }
+        Some(Rc::new(Ret::new(result)))
+    }
+
     fn extract_ret_val(&self, evaluated: Option<Rc<dyn Object>>) -> Option<Rc<dyn Object>> {
         if let Some(ret) = evaluated.as_ref().unwrap().as_any().downcast_ref::<Ret>() {
             Some(Rc::clone(&ret.val))
+        } else if let Some(control) = self.loop_control(&evaluated) {
+            Some(Rc::new(Error::new(format!(
+                "'{}' outside of a loop",
+                control
+            ))))
         } else {
             evaluated
         }
@@ -572,14 +1684,44 @@ impl Evaluator {
             &function.env,
         )))));
         for (idx, arg) in args.iter().enumerate() {
-            env.borrow_mut().set(
+            // parameters shadow outer bindings, a fresh scope can't refuse them
+            let _ = env.borrow_mut().declare(
                 function.parameters.get(idx).unwrap().to_string(),
                 Rc::clone(arg.as_ref().unwrap()),
-            )
+                Declaration::Var,
+                None,
+            );
         }
         env
     }
 
+    /// The call with its function and arguments evaluated, or the error
+    /// evaluating them gave.
+    fn eval_call(
+        &self,
+        call: &CallExpr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<TailCall, Option<Rc<dyn Object>>> {
+        let function = match call.function.as_any().downcast_ref::<MemberExpr>() {
+            Some(member) => self
+                .eval_member(member, Rc::clone(&env), true)
+                .map(|obj| Error::locate(obj, || member.location())),
+            None => self.eval(Some(call.function.as_ref()), Rc::clone(&env)),
+        };
+        if self.is_error(&function) {
+            return Err(function);
+        }
+        let accepts_errors = matches!(
+            function.as_ref().unwrap().as_any().downcast_ref::<BuiltIn>(),
+            Some(built_in) if built_in.accepts_errors
+        );
+        let mut arguments = self.eval_expressions(&call.arguments, Rc::clone(&env));
+        if !accepts_errors && arguments.len() == 1 && self.is_error(&arguments[0]) {
+            return Err(arguments.pop().unwrap());
+        }
+        Ok(TailCall::new(function.unwrap(), arguments, call.location()))
+    }
+
     fn eval_expressions(
         &self,
         args: &[Rc<dyn Expression>],
@@ -595,7 +1737,18 @@ impl Evaluator {
         }
         evaluated_args
     }
+}
 
+impl Caller for &Evaluator {
+    fn call_function(
+        &mut self,
+        function: Rc<dyn Object>,
+        args: Vec<Rc<dyn Object>>,
+    ) -> Rc<dyn Object> {
+        let args = args.into_iter().map(Some).collect();
+        self.apply_function(function, args, None)
+            .unwrap_or_else(|| Rc::new(Null))
+    }
 }
 
 impl Default for Evaluator {
@@ -603,3 +1756,28 @@ impl Default for Evaluator {
         Self::new()
     }
 }
+
+/// The location of the error a `catch` bound to a map, read back from its
+/// location, line and column keys so throwing the map again keeps it.
+fn caught_location<F>(get: F) -> Option<Location>
+where
+    F: Fn(&str) -> Option<Rc<dyn Object>>,
+{
+    let int = |key: &str| {
+        let val = get(key)?.as_any().downcast_ref::<Integer>()?.val;
+        usize::try_from(val).ok()
+    };
+    let (line, column) = (int("line")?, int("column")?);
+    let location = get("location")?;
+    let location = location.as_any().downcast_ref::<Str>()?;
+    let filename = location
+        .val
+        .strip_suffix(&format!(":{}:{}", line, column))?;
+    Some(Location::new(column, line, Rc::new(filename.to_string())))
+}
+
+/// The address of a local, how deep the native stack is at the caller.
+fn stack_address() -> usize {
+    let local = 0u8;
+    std::hint::black_box(&local) as *const u8 as usize
+}
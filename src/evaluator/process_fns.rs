@@ -0,0 +1,96 @@
+use std::{
+    cell::RefCell,
+    env,
+    io::{self, Write},
+    process,
+    rc::Rc,
+};
+
+use crate::object::{
+    array::Array, built_in::BuildInFn, integer::Integer, null::Null, string::Str, Object,
+};
+
+use super::{
+    built_in_fns::{error, expected, wrong_arguments},
+    string_fns::str_arg,
+};
+
+pub(super) const FUNCTIONS: &[(&str, BuildInFn)] = &[
+    ("args", args),
+    ("env_get", env_get),
+    ("env_set", env_set),
+    ("exit", exit),
+];
+
+thread_local! {
+    static SCRIPT_ARGS: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+/// Sets what `args()` returns, the arguments after the script name.
+pub fn set_args(script_args: Vec<String>) {
+    SCRIPT_ARGS.with(|args| *args.borrow_mut() = script_args);
+}
+
+pub fn args(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if !args.is_empty() {
+        return wrong_arguments("args", 0);
+    }
+    let elements = SCRIPT_ARGS.with(|args| {
+        args.borrow()
+            .iter()
+            .map(|arg| -> Rc<dyn Object> { Rc::new(Str::new(arg.clone())) })
+            .collect()
+    });
+    Rc::new(Array::new(elements))
+}
+
+/// The value of an environment variable, or null when it isn't set.
+pub fn env_get(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let name = match str_arg("env_get", args, 0, 1) {
+        Ok(name) => name,
+        Err(err) => return err,
+    };
+    match env::var(name) {
+        Ok(value) => Rc::new(Str::new(value)),
+        Err(env::VarError::NotPresent) => Rc::new(Null),
+        Err(err) => error(format!("env_get '{}': {}", name, err)),
+    }
+}
+
+pub fn env_set(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (name, value) = match (
+        str_arg("env_set", args, 0, 2),
+        str_arg("env_set", args, 1, 2),
+    ) {
+        (Ok(name), Ok(value)) => (name, value),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    // `set_var` panics on names and values the OS can't store
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return error(format!("env_set invalid variable name '{}'", name));
+    }
+    if value.contains('\0') {
+        return error(format!("env_set value of '{}' can't contain '\\0'", name));
+    }
+    env::set_var(name, value);
+    Rc::new(Null)
+}
+
+/// Ends the process with the exit code, flushing what was printed.
+pub fn exit(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 1 {
+        return wrong_arguments("exit", 1);
+    }
+    let code = match args[0].as_any().downcast_ref::<Integer>() {
+        Some(integer) => integer.val,
+        None => return expected("exit", 0, "int", args),
+    };
+    match i32::try_from(code) {
+        Ok(code) => {
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+            process::exit(code)
+        }
+        Err(_) => error(format!("exit code {} is out of range", code)),
+    }
+}
@@ -0,0 +1,323 @@
+use std::rc::Rc;
+
+use crate::object::{
+    array::Array, boolean::Boolean, built_in::BuildInFn, integer::Integer, string::Str, Object,
+};
+
+use super::built_in_fns::{error, expected, wrong_arguments};
+
+// longer results are almost certainly a bug and would abort on allocation
+const MAX_LEN: usize = 1 << 30;
+
+pub(super) const FUNCTIONS: &[(&str, BuildInFn)] = &[
+    ("split", split),
+    ("join", join),
+    ("contains", contains),
+    ("starts_with", starts_with),
+    ("ends_with", ends_with),
+    ("replace_all", replace_all),
+    ("upper", upper),
+    ("lower", lower),
+    ("repeat", repeat),
+    ("chars", chars),
+    ("bytes", bytes),
+    ("pad_left", pad_left),
+    ("pad_right", pad_right),
+    ("format", format),
+];
+
+pub fn split(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (string, separator) = match (str_arg("split", args, 0, 2), str_arg("split", args, 1, 2)) {
+        (Ok(string), Ok(separator)) => (string, separator),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    let parts: Vec<Rc<dyn Object>> = if separator.is_empty() {
+        string.chars().map(|ch| str_obj(ch.to_string())).collect()
+    } else {
+        string
+            .split(separator)
+            .map(|part| str_obj(part.to_string()))
+            .collect()
+    };
+    Rc::new(Array::new(parts))
+}
+
+pub fn join(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.len() != 2 {
+        return wrong_arguments("join", 2);
+    }
+    let separator = match str_arg("join", args, 1, 2) {
+        Ok(separator) => separator,
+        Err(err) => return err,
+    };
+    match args[0].as_any().downcast_ref::<Array>() {
+        Some(array) => {
+            let parts: Vec<String> = array
+                .elements
+                .borrow()
+                .iter()
+                .map(|e| e.to_string())
+                .collect();
+            Rc::new(Str::new(parts.join(separator)))
+        }
+        None => expected("join", 0, "array", args),
+    }
+}
+
+pub fn contains(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    str_predicate("contains", args, |string, pattern| string.contains(pattern))
+}
+
+pub fn starts_with(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    str_predicate("starts_with", args, |string, pattern| {
+        string.starts_with(pattern)
+    })
+}
+
+pub fn ends_with(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    str_predicate("ends_with", args, |string, pattern| {
+        string.ends_with(pattern)
+    })
+}
+
+/// The char index where the pattern first appears, or -1.
+pub fn find(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (string, pattern) = match (str_arg("find", args, 0, 2), str_arg("find", args, 1, 2)) {
+        (Ok(string), Ok(pattern)) => (string, pattern),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    let index = match string.find(pattern) {
+        Some(byte) => string[..byte].chars().count() as i64,
+        None => -1,
+    };
+    Rc::new(Integer::new(index))
+}
+
+pub fn replace_all(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let string = str_arg("replace_all", args, 0, 3);
+    let from = str_arg("replace_all", args, 1, 3);
+    let to = str_arg("replace_all", args, 2, 3);
+    match (string, from, to) {
+        (Ok(_), Ok(""), Ok(_)) => error("replace_all can't replace an empty str".to_string()),
+        (Ok(string), Ok(from), Ok(to)) => {
+            let count = string.matches(from).count();
+            let len = string.len() - count * from.len() + count * to.len();
+            if len > MAX_LEN {
+                return error(format!("replace_all result of {} bytes is too long", len));
+            }
+            Rc::new(Str::new(string.replace(from, to)))
+        }
+        (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => err,
+    }
+}
+
+pub fn upper(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match str_arg("upper", args, 0, 1) {
+        Ok(string) => Rc::new(Str::new(string.to_uppercase())),
+        Err(err) => err,
+    }
+}
+
+pub fn lower(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match str_arg("lower", args, 0, 1) {
+        Ok(string) => Rc::new(Str::new(string.to_lowercase())),
+        Err(err) => err,
+    }
+}
+
+pub fn repeat(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    let (string, count) = match (str_arg("repeat", args, 0, 2), int_arg("repeat", args, 1, 2)) {
+        (Ok(string), Ok(count)) => (string, count),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    if count < 0 {
+        return error(format!("repeat count must not be negative, got {}", count));
+    }
+    match string.len().checked_mul(count as usize) {
+        Some(len) if len <= MAX_LEN => Rc::new(Str::new(string.repeat(count as usize))),
+        _ => error(format!(
+            "repeat result of {} x {} bytes is too long",
+            count,
+            string.len()
+        )),
+    }
+}
+
+pub fn chars(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match str_arg("chars", args, 0, 1) {
+        Ok(string) => Rc::new(Array::new(
+            string.chars().map(|ch| str_obj(ch.to_string())).collect(),
+        )),
+        Err(err) => err,
+    }
+}
+
+/// The UTF-8 bytes of a str, as ints.
+pub fn bytes(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    match str_arg("bytes", args, 0, 1) {
+        Ok(string) => Rc::new(Array::new(
+            string
+                .bytes()
+                .map(|byte| -> Rc<dyn Object> { Rc::new(Integer::new(byte as i64)) })
+                .collect(),
+        )),
+        Err(err) => err,
+    }
+}
+
+pub fn pad_left(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    pad("pad_left", args, true)
+}
+
+pub fn pad_right(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    pad("pad_right", args, false)
+}
+
+/// Pads a str with a fill char, a space by default, up to a width in chars.
+fn pad(name: &str, args: &[Rc<dyn Object>], left: bool) -> Rc<dyn Object> {
+    if args.len() != 2 && args.len() != 3 {
+        return error(format!(
+            "wrong number of arguments, {} needs 2 or 3 args, str, width, fill",
+            name
+        ));
+    }
+    let (string, width) = match (str_arg(name, args, 0, 0), int_arg(name, args, 1, 0)) {
+        (Ok(string), Ok(width)) => (string, width),
+        (Err(err), _) | (_, Err(err)) => return err,
+    };
+    let fill = match args.len() {
+        3 => match str_arg(name, args, 2, 0) {
+            Ok(fill) if fill.chars().count() == 1 => fill.chars().next().unwrap_or(' '),
+            Ok(fill) => return error(format!("{} fill must be one char, got '{}'", name, fill)),
+            Err(err) => return err,
+        },
+        _ => ' ',
+    };
+    if width > MAX_LEN as i64 {
+        return error(format!("{} width {} is too large", name, width));
+    }
+    let missing = (width.max(0) as usize).saturating_sub(string.chars().count());
+    let padding = fill.to_string().repeat(missing);
+    if left {
+        Rc::new(Str::new(padding + string))
+    } else {
+        Rc::new(Str::new(string.to_string() + &padding))
+    }
+}
+
+/// Fills the `{}` of a template with the other arguments in order, or the
+/// one at `{n}`; `{{` and `}}` stand for braces.
+pub fn format(args: &[Rc<dyn Object>]) -> Rc<dyn Object> {
+    if args.is_empty() {
+        return error("wrong number of arguments, format needs a template".to_string());
+    }
+    let template = match str_arg("format", args, 0, 0) {
+        Ok(template) => template,
+        Err(err) => return err,
+    };
+    let values = &args[1..];
+    let mut used = vec![false; values.len()];
+    let mut next = 0;
+    let mut result = String::new();
+    let mut chars = template.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('{') if placeholder.is_empty() => {
+                            result.push('{');
+                            break;
+                        }
+                        Some('}') => {
+                            let index = if placeholder.is_empty() {
+                                next += 1;
+                                next - 1
+                            } else {
+                                match placeholder.trim().parse::<usize>() {
+                                    Ok(index) => index,
+                                    Err(_) => {
+                                        return error(format!(
+                                            "invalid placeholder '{{{}}}' in format",
+                                            placeholder
+                                        ))
+                                    }
+                                }
+                            };
+                            match values.get(index) {
+                                Some(value) => result.push_str(&value.to_string()),
+                                None => {
+                                    return error(format!(
+                                        "format placeholder {} has no argument, got {}",
+                                        index,
+                                        values.len()
+                                    ))
+                                }
+                            }
+                            used[index] = true;
+                            break;
+                        }
+                        Some(ch) => placeholder.push(ch),
+                        None => return error("unclosed '{' in format".to_string()),
+                    }
+                }
+            }
+            '}' => match chars.next() {
+                Some('}') => result.push('}'),
+                _ => return error("unmatched '}' in format, use '}}'".to_string()),
+            },
+            ch => result.push(ch),
+        }
+    }
+    match used.iter().position(|used| !used) {
+        Some(index) => error(format!("format argument {} is never used", index)),
+        None => Rc::new(Str::new(result)),
+    }
+}
+
+fn str_predicate<F>(name: &str, args: &[Rc<dyn Object>], predicate: F) -> Rc<dyn Object>
+where
+    F: Fn(&str, &str) -> bool,
+{
+    match (str_arg(name, args, 0, 2), str_arg(name, args, 1, 2)) {
+        (Ok(string), Ok(pattern)) => Rc::new(Boolean::new(predicate(string, pattern))),
+        (Err(err), _) | (_, Err(err)) => err,
+    }
+}
+
+/// The str argument at `idx`, checking first there are `count` arguments
+/// when `count` isn't 0.
+pub(super) fn str_arg<'a>(
+    name: &str,
+    args: &'a [Rc<dyn Object>],
+    idx: usize,
+    count: usize,
+) -> Result<&'a str, Rc<dyn Object>> {
+    if count != 0 && args.len() != count {
+        return Err(wrong_arguments(name, count));
+    }
+    match args[idx].as_any().downcast_ref::<Str>() {
+        Some(string) => Ok(&string.val),
+        None => Err(expected(name, idx, "str", args)),
+    }
+}
+
+fn int_arg(
+    name: &str,
+    args: &[Rc<dyn Object>],
+    idx: usize,
+    count: usize,
+) -> Result<i64, Rc<dyn Object>> {
+    if count != 0 && args.len() != count {
+        return Err(wrong_arguments(name, count));
+    }
+    match args[idx].as_any().downcast_ref::<Integer>() {
+        Some(integer) => Ok(integer.val),
+        None => Err(expected(name, idx, "int", args)),
+    }
+}
+
+fn str_obj(string: String) -> Rc<dyn Object> {
+    Rc::new(Str::new(string))
+}
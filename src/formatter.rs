@@ -0,0 +1,746 @@
+use std::rc::Rc;
+
+use crate::{
+    ast::{
+        expr::{
+            arr_expr::ArrayExpr, big_int_expr::BigIntExpr, bool_expr::BoolExpr,
+            call_expr::CallExpr, float_expr::FloatExpr, for_expr::ForExpr,
+            function_expr::FunctionExpr, hash_expr::HashExpr, if_expr::IfExpr,
+            index_expr::IndexExpr, infix_expr::InfixExpr, int_expr::IntExpr,
+            interp_expr::InterpExpr, match_expr::MatchExpr, member_expr::MemberExpr,
+            prefix_expr::PrefixExpr, range_expr::RangeExpr, str_expr::StrExpr,
+            struct_expr::StructExpr, try_expr::TryExpr, while_expr::WhileExpr,
+        },
+        expression::Expression,
+        identifier::Identifier,
+        pattern::Pattern,
+        program::Program,
+        statement::Statement,
+        stmt::{
+            block_stmt::BlockStatement, break_stmt::Break, continue_stmt::Continue,
+            enum_stmt::EnumStmt, expression_stmt::ExpressionStatement, impl_stmt::ImplStmt,
+            import_stmt::Import, let_stmt::Let, return_stmt::Return, struct_stmt::StructStmt,
+            throw_stmt::Throw, var_stmt::Var,
+        },
+        types::Type,
+    },
+    diagnostic::Diagnostic,
+    lexer::{
+        token::{Comment, Location, Token},
+        Lexer,
+    },
+    loader::module_name,
+    parser::{
+        precedence::{operator_precedence, Precedence},
+        Parser,
+    },
+};
+
+const INDENT: &str = "    ";
+
+/// Prints a parsed program back as canonical source: four space indents, one
+/// statement per line, spaced binary operators and opening braces kept on the
+/// line of the statement they belong to. Comments and single blank lines are
+/// preserved.
+pub struct Formatter<'a> {
+    lines: Vec<&'a str>,
+    indent: usize,
+    comments: Vec<Comment>,
+    // the first comment not printed yet
+    next_comment: usize,
+    // in the condition of `if`, `while` or `for`, where struct literals
+    // need parentheses
+    no_struct: bool,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut lexer = Lexer::new(Rc::new(source.to_string()), Rc::new(String::new()));
+        while !matches!(lexer.next_token().as_ref(), Token::EOF(_)) {}
+        Self {
+            lines: source.lines().collect(),
+            indent: 0,
+            comments: lexer.comments().to_vec(),
+            next_comment: 0,
+            no_struct: false,
+        }
+    }
+
+    pub fn format(&mut self, program: &Program) -> String {
+        self.statements(&program.statements, None)
+    }
+
+    /// The statements of a block ending at `end`, or of the whole program.
+    fn statements(
+        &mut self,
+        statements: &[Rc<dyn Statement>],
+        end: Option<(usize, usize)>,
+    ) -> String {
+        let mut buffer = String::new();
+        // where the last statement ended, if it ended in a block
+        let mut open_block = None;
+        for (idx, stmt) in statements.iter().enumerate() {
+            let line = stmt.location().map(|location| location.line);
+            if let Some(line) = line {
+                self.own_line_comments(&mut buffer, |comment| {
+                    comment.location.line < line
+                        || (comment.location.line == line && !comment.trailing)
+                });
+            }
+            // comments right after a brace opened on the line stay after it
+            let mut opening = vec![];
+            while let Some(comment) = self.comments.get(self.next_comment) {
+                if Some(comment.location.line) != line || !self.after_brace(comment) {
+                    break;
+                }
+                opening.push(comment.text.clone());
+                self.next_comment += 1;
+            }
+            let last = end.is_some() && idx == statements.len() - 1;
+            let (mut text, block) = self.statement(stmt.as_ref(), last);
+            // comments left after code up to the block end or the next statement
+            // end the lines of this one, so follow its last line
+            let next = statements
+                .get(idx + 1)
+                .and_then(|stmt| stmt.location())
+                .map(|next| (next.line, next.position));
+            let mut trailing = vec![];
+            while let Some(comment) = self.comments.get(self.next_comment) {
+                if (Some(comment.location.line) != line && !comment.trailing)
+                    || !end.is_none_or(|end| Self::before(comment, end))
+                    || !next.is_none_or(|next| Self::before(comment, next))
+                {
+                    break;
+                }
+                trailing.push(comment.text.clone());
+                self.next_comment += 1;
+            }
+            if text.is_empty() {
+                continue;
+            }
+            // a block expression followed by these would parse as an operand
+            if let Some(end) = open_block.filter(|_| text.starts_with(['(', '[', '-'])) {
+                buffer.insert(end, ';');
+            }
+            if !buffer.is_empty() && self.blank_line_before(line) {
+                buffer.push('\n');
+            }
+            buffer.push_str(&INDENT.repeat(self.indent));
+            let statement_end = buffer.len() + text.len();
+            if !opening.is_empty() {
+                let first_line = text.find('\n').unwrap_or(text.len());
+                text.insert_str(first_line, &format!(" {}", opening.join(" ")));
+            }
+            buffer.push_str(&text);
+            if !trailing.is_empty() {
+                buffer.push_str(&format!(" {}", trailing.join(" ")));
+            }
+            buffer.push('\n');
+            open_block = if block {
+                Some(if text.contains('\n') {
+                    buffer.len() - 1
+                } else {
+                    statement_end
+                })
+            } else {
+                None
+            };
+        }
+        match end {
+            Some(end) => self.own_line_comments(&mut buffer, |comment| Self::before(comment, end)),
+            None => self.own_line_comments(&mut buffer, |_| true),
+        }
+        buffer
+    }
+
+    /// Prints the next comments matching `until` on lines of their own.
+    fn own_line_comments(&mut self, buffer: &mut String, until: impl Fn(&Comment) -> bool) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if !until(comment) {
+                break;
+            }
+            if !buffer.is_empty() && self.blank_line_before(Some(comment.location.line)) {
+                buffer.push('\n');
+            }
+            buffer.push_str(&INDENT.repeat(self.indent));
+            buffer.push_str(&comment.text);
+            buffer.push('\n');
+            self.next_comment += 1;
+        }
+    }
+
+    /// The statement without indent, and whether it ends in a block.
+    fn statement(&mut self, stmt: &dyn Statement, last: bool) -> (String, bool) {
+        let stmt = stmt.as_any();
+        let text = if let Some(stmt) = stmt.downcast_ref::<Let>() {
+            format!(
+                "let {} = {};",
+                self.declared(&stmt.name),
+                self.expression(&stmt.value)
+            )
+        } else if let Some(stmt) = stmt.downcast_ref::<Var>() {
+            format!(
+                "var {} = {};",
+                self.declared(&stmt.name),
+                self.expression(&stmt.value)
+            )
+        } else if let Some(stmt) = stmt.downcast_ref::<Return>() {
+            match &stmt.return_value {
+                Some(value) => format!("ret {};", self.expression(value)),
+                None => "ret;".to_string(),
+            }
+        } else if let Some(stmt) = stmt.downcast_ref::<Throw>() {
+            format!("throw {};", self.expression(&stmt.value))
+        } else if stmt.downcast_ref::<Break>().is_some() {
+            "break;".to_string()
+        } else if stmt.downcast_ref::<Continue>().is_some() {
+            "continue;".to_string()
+        } else if let Some(stmt) = stmt.downcast_ref::<Import>() {
+            Self::import(stmt)
+        } else if let Some(stmt) = stmt.downcast_ref::<StructStmt>() {
+            let fields: Vec<(Option<Location>, String)> = stmt
+                .fields
+                .iter()
+                .map(|field| (field.location(), self.declared(field)))
+                .collect();
+            self.declaration("struct", &stmt.name, fields, &stmt.end)
+        } else if let Some(stmt) = stmt.downcast_ref::<EnumStmt>() {
+            let variants: Vec<(Option<Location>, String)> = stmt
+                .variants
+                .iter()
+                .map(|(name, types)| {
+                    let mut text = self.expression(name);
+                    if !types.is_empty() {
+                        let types: Vec<String> = types.iter().filter_map(Self::type_name).collect();
+                        text.push_str(&format!("({})", types.join(", ")));
+                    }
+                    (name.location(), text)
+                })
+                .collect();
+            self.declaration("enum", &stmt.name, variants, &stmt.end)
+        } else if let Some(stmt) = stmt.downcast_ref::<ImplStmt>() {
+            self.impl_block(stmt)
+        } else if let Some(stmt) = stmt.downcast_ref::<ExpressionStatement>() {
+            let Some(expression) = &stmt.expression else {
+                return (String::new(), false);
+            };
+            let text = self.expression(expression);
+            if Self::is_block(expression.as_ref()) {
+                return (text, true);
+            }
+            // the value of a block is its last expression, keep it as written
+            if last && !stmt.semicolon {
+                text
+            } else {
+                format!("{};", text)
+            }
+        } else {
+            String::new()
+        };
+        (text, false)
+    }
+
+    /// A struct or enum declaration with a field or variant per line.
+    fn declaration(
+        &mut self,
+        keyword: &str,
+        name: &Rc<dyn Expression>,
+        entries: Vec<(Option<Location>, String)>,
+        end: &Option<Location>,
+    ) -> String {
+        let end = Self::end_of(end);
+        if entries.is_empty() && !self.commented(end) {
+            return format!("{} {} {{}}", keyword, name);
+        }
+        self.indent += 1;
+        let mut body = String::new();
+        for (location, text) in entries {
+            let line = location.map_or(0, |location| location.line);
+            self.own_line_comments(&mut body, |comment| comment.location.line < line);
+            body.push_str(&INDENT.repeat(self.indent));
+            body.push_str(&format!("{},", text));
+            self.trailing_comments(&mut body, line);
+            body.push('\n');
+        }
+        self.own_line_comments(&mut body, |comment| Self::before(comment, end));
+        self.indent -= 1;
+        format!(
+            "{} {} {{\n{}{}}}",
+            keyword,
+            name,
+            body,
+            INDENT.repeat(self.indent)
+        )
+    }
+
+    /// An impl block with its methods spaced like the statements of a block.
+    fn impl_block(&mut self, stmt: &ImplStmt) -> String {
+        let end = Self::end_of(&stmt.end);
+        if stmt.methods.is_empty() && !self.commented(end) {
+            return format!("impl {} {{}}", stmt.name);
+        }
+        self.indent += 1;
+        let mut body = String::new();
+        for method in stmt.methods.iter() {
+            let line = method.location.as_ref().map_or(0, |location| location.line);
+            self.own_line_comments(&mut body, |comment| comment.location.line < line);
+            if !body.is_empty() && self.blank_line_before(Some(line)) {
+                body.push('\n');
+            }
+            let mut trailing = String::new();
+            self.trailing_comments(&mut trailing, line);
+            let method: Rc<dyn Expression> = Rc::clone(method) as Rc<dyn Expression>;
+            let mut text = self.expression(&method);
+            text.insert_str(text.find('\n').unwrap_or(text.len()), &trailing);
+            body.push_str(&INDENT.repeat(self.indent));
+            body.push_str(&text);
+            body.push('\n');
+        }
+        self.own_line_comments(&mut body, |comment| Self::before(comment, end));
+        self.indent -= 1;
+        format!(
+            "impl {} {{\n{}{}}}",
+            stmt.name,
+            body,
+            INDENT.repeat(self.indent)
+        )
+    }
+
+    /// A match with an arm per line, arms with a block body need no comma.
+    fn match_expr(&mut self, match_expr: &MatchExpr) -> String {
+        let value = self.condition(&match_expr.value);
+        let end = Self::end_of(&match_expr.end);
+        if match_expr.arms.is_empty() && !self.commented(end) {
+            return format!("match {} {{}}", value);
+        }
+        self.indent += 1;
+        let no_struct = std::mem::take(&mut self.no_struct);
+        let mut body = String::new();
+        for arm in match_expr.arms.iter() {
+            let line = arm.pattern.location().map_or(0, |location| location.line);
+            self.own_line_comments(&mut body, |comment| comment.location.line < line);
+            body.push_str(&INDENT.repeat(self.indent));
+            body.push_str(&self.pattern(&arm.pattern));
+            if let Some(guard) = &arm.guard {
+                body.push_str(&format!(" if {}", self.expression(guard)));
+            }
+            body.push_str(" => ");
+            let arm_body = arm.body.as_any();
+            if let Some(block) = arm_body.downcast_ref::<BlockStatement>() {
+                body.push_str(&self.block_stmt(block));
+            } else if let Some(expression) = arm_body
+                .downcast_ref::<ExpressionStatement>()
+                .and_then(|stmt| stmt.expression.as_ref())
+            {
+                body.push_str(&format!("{},", self.expression(expression)));
+                self.trailing_comments(&mut body, line);
+            }
+            body.push('\n');
+        }
+        self.own_line_comments(&mut body, |comment| Self::before(comment, end));
+        self.no_struct = no_struct;
+        self.indent -= 1;
+        format!(
+            "match {} {{\n{}{}}}",
+            value,
+            body,
+            INDENT.repeat(self.indent)
+        )
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> String {
+        match pattern {
+            Pattern::Wildcard(_) => "_".to_string(),
+            Pattern::Binding(expr) | Pattern::Literal(expr) => self.expression(expr),
+            Pattern::Range(start, end) => {
+                format!("{}..{}", self.expression(start), self.expression(end))
+            }
+            Pattern::Variant {
+                name,
+                variant,
+                values,
+            } => {
+                let mut text = format!("{}.{}", name, variant);
+                if !values.is_empty() {
+                    let values: Vec<String> =
+                        values.iter().map(|value| self.pattern(value)).collect();
+                    text.push_str(&format!("({})", values.join(", ")));
+                }
+                text
+            }
+            Pattern::Array { items, rest, .. } => {
+                let mut items: Vec<String> = items.iter().map(|item| self.pattern(item)).collect();
+                match rest.as_deref() {
+                    Some(Pattern::Wildcard(_)) => items.push("..".to_string()),
+                    Some(rest) => items.push(format!("..{}", self.pattern(rest))),
+                    None => {}
+                }
+                format!("[{}]", items.join(", "))
+            }
+        }
+    }
+
+    /// Appends the comments after the code of `line`.
+    fn trailing_comments(&mut self, buffer: &mut String, line: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.location.line != line {
+                break;
+            }
+            buffer.push_str(&format!(" {}", comment.text));
+            self.next_comment += 1;
+        }
+    }
+
+    /// Whether comments are left before `end`.
+    fn commented(&self, end: (usize, usize)) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|comment| Self::before(comment, end))
+    }
+
+    /// The line and column of a closing brace, past everything when unknown.
+    fn end_of(end: &Option<Location>) -> (usize, usize) {
+        end.as_ref()
+            .map_or((usize::MAX, 0), |end| (end.line, end.position))
+    }
+
+    /// Whether `comment` starts before the line and column `end`.
+    fn before(comment: &Comment, end: (usize, usize)) -> bool {
+        (comment.location.line, comment.location.position) < end
+    }
+
+    /// Whether the code before `comment` on its line ends opening a brace.
+    fn after_brace(&self, comment: &Comment) -> bool {
+        let line = self.lines.get(comment.location.line - 1).unwrap_or(&"");
+        let code: String = line.chars().take(comment.location.position - 1).collect();
+        code.trim_end().ends_with('{')
+    }
+
+    fn import(import: &Import) -> String {
+        let name = import.name.to_string();
+        let stem = module_name(&import.path);
+        let path = if import.path == format!("{}.bzr", stem) && Self::is_identifier(&stem) {
+            stem.clone()
+        } else {
+            format!("\"{}\"", import.path)
+        };
+        if name == stem {
+            format!("import {};", path)
+        } else {
+            format!("import {} as {};", path, name)
+        }
+    }
+
+    fn expression(&mut self, expr: &Rc<dyn Expression>) -> String {
+        let any = expr.as_any();
+        if let Some(ident) = any.downcast_ref::<Identifier>() {
+            ident.value.to_string()
+        } else if let Some(int) = any.downcast_ref::<IntExpr>() {
+            int.value.to_string()
+        } else if let Some(big) = any.downcast_ref::<BigIntExpr>() {
+            big.value.to_string()
+        } else if let Some(float) = any.downcast_ref::<FloatExpr>() {
+            format!("{:?}", float.value)
+        } else if let Some(boolean) = any.downcast_ref::<BoolExpr>() {
+            boolean.value.to_string()
+        } else if let Some(string) = any.downcast_ref::<StrExpr>() {
+            format!("\"{}\"", Self::escape(&string.value))
+        } else if let Some(interp) = any.downcast_ref::<InterpExpr>() {
+            let mut text = String::from('"');
+            for part in interp.parts.iter() {
+                match part.as_any().downcast_ref::<StrExpr>() {
+                    Some(string) => text.push_str(&Self::escape(&string.value)),
+                    None => text.push_str(&format!("${{{}}}", self.expression(part))),
+                }
+            }
+            text.push('"');
+            text
+        } else if let Some(array) = any.downcast_ref::<ArrayExpr>() {
+            format!("[{}]", self.list(&array.value))
+        } else if let Some(hash) = any.downcast_ref::<HashExpr>() {
+            let pairs: Vec<String> = hash
+                .pairs
+                .iter()
+                .map(|(key, value)| format!("{}: {}", self.expression(key), self.expression(value)))
+                .collect();
+            format!("{{{}}}", pairs.join(", "))
+        } else if let Some(struct_expr) = any.downcast_ref::<StructExpr>() {
+            let no_struct = std::mem::take(&mut self.no_struct);
+            let fields: Vec<String> = struct_expr
+                .fields
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, self.expression(value)))
+                .collect();
+            self.no_struct = no_struct;
+            let text = if fields.is_empty() {
+                format!("{} {{}}", struct_expr.name)
+            } else {
+                format!("{} {{ {} }}", struct_expr.name, fields.join(", "))
+            };
+            // `if p == Point { .. } {` would read the struct fields as the body
+            if self.no_struct {
+                format!("({})", text)
+            } else {
+                text
+            }
+        } else if let Some(prefix) = any.downcast_ref::<PrefixExpr>() {
+            let right = match &prefix.right {
+                Some(right) => self.operand(right, Precedence::Prefix, false),
+                None => String::new(),
+            };
+            format!("{}{}", prefix.operator, right)
+        } else if let Some(infix) = any.downcast_ref::<InfixExpr>() {
+            let left = match &infix.left {
+                Some(left) => self.infix_operand(left, &infix.operator, false),
+                None => String::new(),
+            };
+            let right = match &infix.right {
+                Some(right) => self.infix_operand(right, &infix.operator, true),
+                None => String::new(),
+            };
+            format!("{} {} {}", left, infix.operator, right)
+        } else if let Some(range) = any.downcast_ref::<RangeExpr>() {
+            let start = self.operand(&range.start, Precedence::Range, false);
+            let end = self.operand(&range.end, Precedence::Range, true);
+            format!("{}..{}", start, end)
+        } else if let Some(call) = any.downcast_ref::<CallExpr>() {
+            let function = self.operand(&call.function, Precedence::Call, false);
+            format!("{}({})", function, self.list(&call.arguments))
+        } else if let Some(index) = any.downcast_ref::<IndexExpr>() {
+            let left = self.operand(&index.left, Precedence::Index, false);
+            format!("{}[{}]", left, self.expression(&index.index))
+        } else if let Some(member) = any.downcast_ref::<MemberExpr>() {
+            let object = self.operand(&member.object, Precedence::Index, false);
+            format!("{}.{}", object, self.expression(&member.member))
+        } else if let Some(if_expr) = any.downcast_ref::<IfExpr>() {
+            let mut text = format!(
+                "if {} {}",
+                self.condition(&if_expr.condition),
+                self.block(&if_expr.consequence)
+            );
+            if let Some(el_if) = &if_expr.el_if {
+                text.push_str(&format!(" else {}", self.expression(el_if)));
+            } else if if_expr.alternative.is_some() {
+                text.push_str(&format!(" else {}", self.block(&if_expr.alternative)));
+            }
+            text
+        } else if let Some(while_expr) = any.downcast_ref::<WhileExpr>() {
+            format!(
+                "while {} {}",
+                self.condition(&while_expr.condition),
+                self.block(&while_expr.consequence)
+            )
+        } else if let Some(for_expr) = any.downcast_ref::<ForExpr>() {
+            format!(
+                "for {} in {} {}",
+                self.expression(&for_expr.variable),
+                self.condition(&for_expr.iterable),
+                self.block(&for_expr.body)
+            )
+        } else if let Some(try_expr) = any.downcast_ref::<TryExpr>() {
+            format!(
+                "try {} catch {} {}",
+                self.block(&try_expr.body),
+                self.expression(&try_expr.name),
+                self.block(&try_expr.handler)
+            )
+        } else if let Some(match_expr) = any.downcast_ref::<MatchExpr>() {
+            self.match_expr(match_expr)
+        } else if let Some(function) = any.downcast_ref::<FunctionExpr>() {
+            let parameters: Vec<String> = function
+                .parameters
+                .iter()
+                .map(|param| self.declared(param))
+                .collect();
+            let mut text = String::from("fn");
+            if let Some(name) = &function.name {
+                text.push(' ');
+                text.push_str(&self.expression(name));
+            }
+            text.push_str(&format!("({}) ", parameters.join(", ")));
+            if let Some(typ) = Self::type_name(&function.ret_typ) {
+                text.push_str(&typ);
+                text.push(' ');
+            }
+            text.push_str(&self.block(&function.body));
+            text
+        } else {
+            expr.to_string()
+        }
+    }
+
+    /// An expression followed by a body in braces.
+    fn condition(&mut self, expr: &Rc<dyn Expression>) -> String {
+        let no_struct = std::mem::replace(&mut self.no_struct, true);
+        let text = self.expression(expr);
+        self.no_struct = no_struct;
+        text
+    }
+
+    /// An operand of an operator binding as tight as `precedence`, in
+    /// parentheses when it would otherwise parse differently.
+    fn operand(
+        &mut self,
+        expr: &Rc<dyn Expression>,
+        precedence: Precedence,
+        right: bool,
+    ) -> String {
+        let own = Self::precedence(expr.as_ref());
+        let text = self.expression(expr);
+        if own < precedence || (right && own == precedence && own != Precedence::Index) {
+            format!("({})", text)
+        } else {
+            text
+        }
+    }
+
+    /// Like `operand`, but bit operators mixed with other operators of the
+    /// same precedence keep their parentheses, `(a << 1) | b` reads better.
+    fn infix_operand(&mut self, expr: &Rc<dyn Expression>, operator: &str, right: bool) -> String {
+        let precedence = operator_precedence(operator);
+        let mixed = expr
+            .as_any()
+            .downcast_ref::<InfixExpr>()
+            .is_some_and(|infix| {
+                infix.operator != operator
+                    && operator_precedence(&infix.operator) == precedence
+                    && [operator, infix.operator.as_str()]
+                        .iter()
+                        .any(|op| ["&", "|", "^", "<<", ">>"].contains(op))
+            });
+        if mixed {
+            format!("({})", self.expression(expr))
+        } else {
+            self.operand(expr, precedence, right)
+        }
+    }
+
+    fn precedence(expr: &dyn Expression) -> Precedence {
+        let any = expr.as_any();
+        if let Some(infix) = any.downcast_ref::<InfixExpr>() {
+            operator_precedence(&infix.operator)
+        } else if any.downcast_ref::<RangeExpr>().is_some() {
+            Precedence::Range
+        } else if any.downcast_ref::<PrefixExpr>().is_some() {
+            Precedence::Prefix
+        } else {
+            Precedence::Index
+        }
+    }
+
+    fn list(&mut self, exprs: &[Rc<dyn Expression>]) -> String {
+        let no_struct = std::mem::take(&mut self.no_struct);
+        let items: Vec<String> = exprs.iter().map(|expr| self.expression(expr)).collect();
+        self.no_struct = no_struct;
+        items.join(", ")
+    }
+
+    fn block(&mut self, block: &Option<Rc<BlockStatement>>) -> String {
+        match block {
+            Some(block) => self.block_stmt(block),
+            None => "{}".to_string(),
+        }
+    }
+
+    fn block_stmt(&mut self, block: &BlockStatement) -> String {
+        let end = Self::end_of(&block.end);
+        if block.statements.is_empty() && !self.commented(end) {
+            return "{}".to_string();
+        }
+        self.indent += 1;
+        let no_struct = std::mem::take(&mut self.no_struct);
+        let body = self.statements(&block.statements, Some(end));
+        self.no_struct = no_struct;
+        self.indent -= 1;
+        format!("{{\n{}{}}}", body, INDENT.repeat(self.indent))
+    }
+
+    /// A `let` name or a parameter with its declared type, if any.
+    fn declared(&mut self, name: &Rc<dyn Expression>) -> String {
+        let typ = name
+            .as_any()
+            .downcast_ref::<Identifier>()
+            .and_then(|ident| ident.typ.as_ref())
+            .and_then(Self::type_name);
+        match typ {
+            Some(typ) => format!("{} {}", self.expression(name), typ),
+            None => self.expression(name),
+        }
+    }
+
+    fn type_name(typ: &Type) -> Option<String> {
+        let name = match typ {
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Bool => "bool",
+            Type::String => "str",
+            Type::Array => "array",
+            Type::Map => "map",
+            Type::Function => "fn",
+            Type::Struct(name) | Type::Enum(name) => name,
+            _ => return None,
+        };
+        Some(name.to_string())
+    }
+
+    /// Expressions ending in a block, which need no `;` as statements.
+    fn is_block(expr: &dyn Expression) -> bool {
+        let any = expr.as_any();
+        any.is::<IfExpr>()
+            || any.is::<WhileExpr>()
+            || any.is::<ForExpr>()
+            || any.is::<TryExpr>()
+            || any.is::<MatchExpr>()
+            || any
+                .downcast_ref::<FunctionExpr>()
+                .is_some_and(|function| function.name.is_some())
+    }
+
+    fn blank_line_before(&self, line: Option<usize>) -> bool {
+        match line {
+            Some(line) if line >= 2 => self
+                .lines
+                .get(line - 2)
+                .is_some_and(|line| line.trim().is_empty()),
+            _ => false,
+        }
+    }
+
+    /// The text of a string literal with the value `value`.
+    fn escape(value: &str) -> String {
+        let mut text = String::with_capacity(value.len());
+        let mut chars = value.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' => text.push_str("\\\""),
+                '\\' => text.push_str("\\\\"),
+                '\n' => text.push_str("\\n"),
+                '\t' => text.push_str("\\t"),
+                '\r' => text.push_str("\\r"),
+                '\0' => text.push_str("\\0"),
+                '$' if chars.peek() == Some(&'{') => text.push_str("\\$"),
+                ch if ch.is_control() => text.push_str(&format!("\\u{{{:x}}}", ch as u32)),
+                ch => text.push(ch),
+            }
+        }
+        text
+    }
+
+    fn is_identifier(name: &str) -> bool {
+        let mut lexer = Lexer::new(Rc::new(name.to_string()), Rc::new(String::new()));
+        match lexer.next_token().as_ref() {
+            Token::Ident(Some(ident), _) => ident.as_str() == name,
+            _ => false,
+        }
+    }
+}
+
+/// Parses `source` and returns it formatted, or the parse errors.
+pub fn format_source(source: &str, filename: &str) -> Result<String, Vec<Diagnostic>> {
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new(filename.to_string()));
+    let program = Parser::new(lexer).parse_program();
+    if !program.errors.is_empty() {
+        return Err(program.errors);
+    }
+    Ok(Formatter::new(source).format(&program))
+}
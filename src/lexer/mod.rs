@@ -1,36 +1,54 @@
 use std::rc::Rc;
 
-use self::token::{Location, Token};
+use self::token::{Comment, Location, Token};
 
 pub mod token;
 
 #[derive(Debug)]
 pub struct Lexer {
-    input: Rc<String>, //Source code
+    input: Vec<char>, //Source code
     position: usize,
     read_position: usize,
     ch: Option<char>,
     line: usize,
     line_position: usize,
     filename: Rc<String>,
+    comments: Vec<Comment>,
 }
 
 impl Lexer {
     pub fn new(input: Rc<String>, filename: Rc<String>) -> Self {
         Self {
-            input,
+            input: input.chars().collect(),
             position: 0,
             read_position: 0,
             ch: None,
             line: 1,
-            line_position: 0,
+            line_position: 1,
             filename,
+            comments: vec![],
         }
     }
 
+    /// A lexer for source embedded in a file, like the code of a `${}` in a
+    /// string, whose locations continue from `location`.
+    pub fn at(input: Rc<String>, location: &Location) -> Self {
+        let mut lexer = Self::new(input, Rc::clone(&location.filename));
+        lexer.line = location.line;
+        lexer.line_position = location.position;
+        lexer
+    }
+
+    /// The comments skipped so far, doc comments included.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
     pub fn next_token(&mut self) -> Rc<Token> {
         self.read_char();
-        self.skip_whitespace();
+        if let Some(token) = self.skip_comments() {
+            return Rc::new(token);
+        }
         let line_position = self.line_position - 1;
         let line = self.line;
         let filename = Rc::clone(&self.filename);
@@ -49,8 +67,15 @@ impl Lexer {
                             line,
                             Rc::clone(&filename),
                         )))
+                    } else if next_char == '>' {
+                        token = Token::FatArrow(Some(Location::new(
+                            line_position,
+                            line,
+                            Rc::clone(&filename),
+                        )))
                     } else if !(Self::is_whitespace(Some(next_char))
                         || (next_char == '\"' || next_char == '(')
+                        || (next_char == '[' || next_char == '{')
                         || Self::is_number(Some(next_char))
                         || Self::is_letter(Some(next_char)))
                     {
@@ -72,7 +97,7 @@ impl Lexer {
                     let next_char = Self::peek_next_char(self, None);
                     if Self::is_number(Some(next_char)) {
                         self.read_char();
-                        let ident: &str = self.read_number();
+                        let ident = self.read_number();
                         if Self::valid_number_suffix(Some(next_char)) {
                             token = Token::Number(
                                 Some(Rc::new(format!("-{}", ident))),
@@ -165,56 +190,53 @@ impl Lexer {
                 '[' => Token::LSqBracket(Some(Location::new(line_position, line, filename))),
                 ']' => Token::RSqBracket(Some(Location::new(line_position, line, filename))),
                 ',' => Token::Comma(Some(Location::new(line_position, line, filename))),
+                ':' => Token::Colon(Some(Location::new(line_position, line, filename))),
                 '{' => Token::LBrace(Some(Location::new(line_position, line, filename))),
                 '}' => Token::Rbrace(Some(Location::new(line_position, line, filename))),
                 '%' => Token::Mod(Some(Location::new(line_position, line, filename))),
                 '^' => Token::Xor(Some(Location::new(line_position, line, filename))),
-                '\"' => {
-                    let string = Self::read_string(self);
-                    let value = Some(Rc::new(String::from(string)));
-                    match string.chars().last() {
-                        Some(ch) => {
-                            if ch != '\"' {
-                                Token::Illegal(
-                                    value,
-                                    Some(Location::new(line_position, line, filename)),
-                                )
-                            } else {
-                                Token::String(
-                                    Some(Rc::new(String::from(&string[0..string.len() - 1]))),
-                                    Some(Location::new(line_position, line, filename)),
-                                )
-                            }
-                        }
-                        None => Token::Illegal(
-                            value,
-                            Some(Location::new(line_position, line, filename)),
-                        ),
+                '.' => {
+                    let next_char = Self::peek_next_char(self, None);
+                    if next_char == '.' {
+                        self.read_char();
+                        Token::DotDot(Some(Location::new(line_position, line, filename)))
+                    } else {
+                        Token::Dot(Some(Location::new(line_position, line, filename)))
                     }
                 }
+                '\"' => match Self::read_string(self) {
+                    Ok(string) => Token::String(
+                        Some(Rc::new(string)),
+                        Some(Location::new(line_position, line, filename)),
+                    ),
+                    Err(string) => Token::Illegal(
+                        Some(Rc::new(format!("\"{}", string))),
+                        Some(Location::new(line_position, line, filename)),
+                    ),
+                },
                 _ => {
                     if Self::is_letter(Some(*ch)) {
-                        let ident: &str = Self::read_identifier(self);
+                        let ident = Self::read_identifier(self);
                         match Token::get_keyword_token(
-                            ident,
+                            &ident,
                             Some(Location::new(line_position, line, Rc::clone(&filename))),
                         ) {
                             Ok(keyword_token) => keyword_token,
                             Err(_) => Token::Ident(
-                                Some(Rc::new(String::from(ident))),
+                                Some(Rc::new(ident)),
                                 Some(Location::new(line_position, line, filename)),
                             ),
                         }
                     } else if Self::is_number(Some(*ch)) {
-                        let next_char = Self::peek_next_char(self, Some(1));
-                        let ident: &str = self.read_number();
+                        let ident = self.read_number();
+                        let next_char = Self::peek_next_char(self, None);
                         let mut token: Token = Token::Illegal(
-                            Some(Rc::new(String::from(ident))),
+                            Some(Rc::new(ident.clone())),
                             Some(Location::new(line_position, line, Rc::clone(&filename))),
                         );
                         if Self::valid_number_suffix(Some(next_char)) {
                             token = Token::Number(
-                                Some(Rc::new(String::from(ident))),
+                                Some(Rc::new(ident)),
                                 Some(Location::new(line_position, line, filename)),
                             )
                         } else {
@@ -245,6 +267,8 @@ impl Lexer {
                 || Self::is_whitespace(Some(ch))
                 || ch == ';'
                 || ch == '{'
+                || ch == '}'
+                || ch == ':'
                 || ch == '|'
                 || ch == '&'
                 || ch == '\0'
@@ -252,11 +276,75 @@ impl Lexer {
                 || ch == ','
                 || ch == ']'
                 || ch == '^'
+                || ch == '.'
                 || Self::is_number(Some(ch))
         } else {
             false
         }
     }
+    /// Skips whitespace, `// line` and nested `/* block */` comments. A `///`
+    /// doc comment is returned as a token, as is an unterminated block comment.
+    fn skip_comments(&mut self) -> Option<Token> {
+        loop {
+            self.skip_whitespace();
+            let next_char = Self::peek_next_char(self, None);
+            if self.ch != Some('/') || (next_char != '/' && next_char != '*') {
+                return None;
+            }
+            let location =
+                Location::new(self.line_position - 1, self.line, Rc::clone(&self.filename));
+            let trailing = self.input[..self.position]
+                .iter()
+                .rev()
+                .take_while(|ch| **ch != '\n')
+                .any(|ch| !Self::is_whitespace(Some(*ch)));
+            let mut text = String::from('/');
+            if next_char == '/' {
+                while self
+                    .input
+                    .get(self.read_position)
+                    .is_some_and(|ch| *ch != '\n')
+                {
+                    self.read_char();
+                    text.push(self.ch.unwrap_or_default());
+                }
+            } else {
+                self.read_char();
+                text.push('*');
+                let mut depth = 1;
+                loop {
+                    self.read_char();
+                    let Some(ch) = self.ch else {
+                        return Some(Token::Illegal(
+                            Some(Rc::new("/*".to_string())),
+                            Some(location),
+                        ));
+                    };
+                    text.push(ch);
+                    let next_char = Self::peek_next_char(self, None);
+                    if (ch == '/' && next_char == '*') || (ch == '*' && next_char == '/') {
+                        self.read_char();
+                        text.push(next_char);
+                        depth += if ch == '/' { 1 } else { -1 };
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+            }
+            let doc = text.starts_with("///") && !text.starts_with("////");
+            self.comments.push(Comment {
+                text: text.clone(),
+                location: location.clone(),
+                trailing,
+            });
+            if doc {
+                return Some(Token::DocComment(Some(Rc::new(text)), Some(location)));
+            }
+            self.read_char();
+        }
+    }
+
     fn skip_whitespace(&mut self) {
         loop {
             if Self::is_whitespace(self.ch) {
@@ -268,7 +356,7 @@ impl Lexer {
     }
 
     fn read_char(&mut self) {
-        self.ch = self.input.chars().nth(self.read_position);
+        self.ch = self.input.get(self.read_position).copied();
 
         if let Some(ch) = self.ch {
             if ch == '\n' {
@@ -281,17 +369,38 @@ impl Lexer {
         self.read_position += 1;
     }
 
-    fn read_number(&mut self) -> &str {
+    fn read_number(&mut self) -> String {
         let position = self.position;
-        while self.position < self.input.len() && Self::is_number(self.ch) {
+        self.read_digits();
+        if self.ch == Some('.') && Self::is_number(Some(Self::peek_next_char(self, None))) {
             self.read_char();
+            self.read_digits();
+        }
+        if self.ch == Some('e') || self.ch == Some('E') {
+            let next_char = Self::peek_next_char(self, None);
+            let sign = next_char == '-' || next_char == '+';
+            if Self::is_number(Some(next_char))
+                || (sign && Self::is_number(Some(Self::peek_next_char(self, Some(1)))))
+            {
+                self.read_char();
+                if sign {
+                    self.read_char();
+                }
+                self.read_digits();
+            }
         }
         let final_pos = self.position;
         self.back_position();
-        &self.input[position..final_pos]
+        self.input[position..final_pos].iter().collect()
     }
 
-    fn read_identifier(&mut self) -> &str {
+    fn read_digits(&mut self) {
+        while self.position < self.input.len() && Self::is_number(self.ch) {
+            self.read_char();
+        }
+    }
+
+    fn read_identifier(&mut self) -> String {
         let position = self.position;
         while self.position < self.input.len() && Self::is_letter(self.ch)
             || Self::is_number(self.ch)
@@ -300,40 +409,81 @@ impl Lexer {
         }
         let final_pos = self.position;
         self.back_position();
-        &self.input[position..final_pos]
+        self.input[position..final_pos].iter().collect()
     }
 
     fn back_position(&mut self) {
+        // the newline is read again by the next read_char
+        if self.ch == Some('\n') {
+            self.line -= 1;
+        }
         self.line_position -= 1;
         self.position -= 1;
         self.read_position -= 1;
     }
 
-    fn read_string(input: &mut Self) -> &str {
-        let position = input.position + 1;
-        input.read_char();
-        while input.position < input.input.len() && (input.ch != Some('\"') || input.ch == None) {
+    /// Reads up to the closing quote and returns the text in between as
+    /// written, escapes included. Quotes inside `${}` don't close the string.
+    /// An unterminated string comes back as the error.
+    fn read_string(input: &mut Self) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
             input.read_char();
+            let Some(ch) = input.ch else {
+                return Err(text);
+            };
+            match ch {
+                '\"' => return Ok(text),
+                '\\' => {
+                    text.push(ch);
+                    input.read_char();
+                    match input.ch {
+                        Some(ch) => text.push(ch),
+                        None => return Err(text),
+                    }
+                }
+                '$' if Self::peek_next_char(input, None) == '{' => {
+                    input.read_char();
+                    text.push_str("${");
+                    let mut depth = 1;
+                    while depth > 0 {
+                        input.read_char();
+                        let Some(ch) = input.ch else {
+                            return Err(text);
+                        };
+                        match ch {
+                            '{' => depth += 1,
+                            '}' => depth -= 1,
+                            '\"' => {
+                                let nested = Self::read_string(input);
+                                text.push('"');
+                                match nested {
+                                    Ok(nested) => text.push_str(&nested),
+                                    Err(nested) => return Err(text + &nested),
+                                }
+                            }
+                            _ => {}
+                        }
+                        text.push(ch);
+                    }
+                }
+                _ => text.push(ch),
+            }
         }
-        let mut last_position = input.position + 1;
-        while last_position > input.input.len() {
-            last_position -= 1;
-        }
-        &input.input[position..last_position]
     }
 
     fn peek_next_char(input: &Self, offset: Option<usize>) -> char {
-        let offset = if let Some(offset) = offset { offset } else { 0 };
-        if let Some(ch) = input.input.chars().nth(input.read_position + offset) {
-            ch
-        } else {
-            '\0'
-        }
+        let offset = offset.unwrap_or_default();
+        input
+            .input
+            .get(input.read_position + offset)
+            .copied()
+            .unwrap_or('\0')
     }
 
     fn is_letter(ch: Option<char>) -> bool {
         if let Some(ch) = ch {
-            ('a'..='z').contains(&ch) || ('A'..='Z').contains(&ch) || ch == '_'
+            ch.is_ascii_alphabetic() || ch == '_'
         } else {
             false
         }
@@ -341,7 +491,7 @@ impl Lexer {
 
     fn is_number(ch: Option<char>) -> bool {
         if let Some(ch) = ch {
-            ('0'..='9').contains(&ch)
+            ch.is_ascii_digit()
         } else {
             false
         }
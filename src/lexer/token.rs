@@ -9,6 +9,7 @@ pub enum Token {
     Ident(Option<Rc<String>>, Option<Location>),
     Number(Option<Rc<String>>, Option<Location>),
     Comma(Option<Location>),
+    Colon(Option<Location>),
     Semicolon(Option<Location>),
     LParen(Option<Location>),
     RParen(Option<Location>),
@@ -24,10 +25,18 @@ pub enum Token {
     True(Option<Location>),
     False(Option<Location>),
     While(Option<Location>),
+    For(Option<Location>),
+    In(Option<Location>),
+    Break(Option<Location>),
+    Continue(Option<Location>),
     If(Option<Location>),
     Else(Option<Location>),
     Return(Option<Location>),
+    Throw(Option<Location>),
+    Try(Option<Location>),
+    Catch(Option<Location>),
     Int(Option<Location>),
+    Float(Option<Location>),
     Str(Option<Location>),
     Bang(Option<Location>),
     Asterisk(Option<Location>),
@@ -49,7 +58,18 @@ pub enum Token {
     BitWiseOr(Option<Location>),
     Xor(Option<Location>),
     Array(Option<Location>),
+    Map(Option<Location>),
     Mod(Option<Location>),
+    DotDot(Option<Location>),
+    Dot(Option<Location>),
+    Import(Option<Location>),
+    As(Option<Location>),
+    Struct(Option<Location>),
+    Enum(Option<Location>),
+    Match(Option<Location>),
+    Impl(Option<Location>),
+    FatArrow(Option<Location>),
+    DocComment(Option<Rc<String>>, Option<Location>),
 }
 
 #[derive(PartialEq, Debug, Clone, Hash)]
@@ -59,6 +79,16 @@ pub struct Location {
     pub filename: Rc<String>,
 }
 
+/// A comment the lexer skipped, kept for tools such as the formatter.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    /// The comment with its markers, `// note` or `/* note */`.
+    pub text: String,
+    pub location: Location,
+    /// Whether code precedes the comment on its line.
+    pub trailing: bool,
+}
+
 impl Location {
     pub fn new(position: usize, line: usize, filename: Rc<String>) -> Self {
         Self {
@@ -67,6 +97,26 @@ impl Location {
             filename,
         }
     }
+
+    /// Formats `message` prefixed by this location, followed by the source line and a caret.
+    pub fn report(&self, message: &str, source: &str) -> String {
+        let mut report = format!("{}: {}", self, message);
+        if let Some(line) = source.lines().nth(self.line.saturating_sub(1)) {
+            let padding: String = line
+                .chars()
+                .take(self.position.saturating_sub(1))
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            report.push_str(&format!("\n{}\n{}^", line, padding));
+        }
+        report
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.filename, self.line, self.position)
+    }
 }
 
 impl Token {
@@ -82,13 +132,28 @@ impl Token {
             "let" => Ok(Token::Let(location)),
             "var" => Ok(Token::Var(location)),
             "ret" => Ok(Token::Return(location)),
+            "throw" => Ok(Token::Throw(location)),
+            "import" => Ok(Token::Import(location)),
+            "as" => Ok(Token::As(location)),
+            "struct" => Ok(Token::Struct(location)),
+            "enum" => Ok(Token::Enum(location)),
+            "match" => Ok(Token::Match(location)),
+            "impl" => Ok(Token::Impl(location)),
+            "try" => Ok(Token::Try(location)),
+            "catch" => Ok(Token::Catch(location)),
             "true" => Ok(Token::True(location)),
             "false" => Ok(Token::False(location)),
             "int" => Ok(Token::Int(location)),
+            "float" => Ok(Token::Float(location)),
             "str" => Ok(Token::Str(location)),
             "bool" => Ok(Token::Bool(location)),
             "while" => Ok(Token::While(location)),
+            "for" => Ok(Token::For(location)),
+            "in" => Ok(Token::In(location)),
+            "break" => Ok(Token::Break(location)),
+            "continue" => Ok(Token::Continue(location)),
             "array" => Ok(Token::Array(location)),
+            "map" => Ok(Token::Map(location)),
             _ => Err(String::from("Not a keyword")),
         }
     }
@@ -110,28 +175,133 @@ impl Token {
             Self::And(_) => "&&".to_string(),
             Self::Assign(_) => "=".to_string(),
             Self::Array(_) => "array".to_string(),
+            Self::Map(_) => "map".to_string(),
             Self::ShiftLeft(_) => "<<".to_string(),
             Self::ShiftRight(_) => ">>".to_string(),
             Self::Xor(_) => "^".to_string(),
             Self::BitWiseAnd(_) => "&".to_string(),
             Self::BitWiseOr(_) => "|".to_string(),
             Self::Mod(_) => "%".to_string(),
+            Self::DotDot(_) => "..".to_string(),
+            Self::Dot(_) => ".".to_string(),
             _ => "unknown".to_string(),
         }
     }
 
+    /// The token as diagnostics name it, quoted and without its location.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::EOF(_) => "end of file".to_string(),
+            Self::Ident(None, _) => "a name".to_string(),
+            Self::Number(None, _) => "a number".to_string(),
+            Self::String(..) => "a string".to_string(),
+            Self::Ident(Some(text), _)
+            | Self::Number(Some(text), _)
+            | Self::Illegal(Some(text), _) => format!("'{}'", text),
+            Self::True(_) => "'true'".to_string(),
+            Self::False(_) => "'false'".to_string(),
+            Self::Plus(_)
+            | Self::Minus(_)
+            | Self::Slash(_)
+            | Self::Lt(_)
+            | Self::Gt(_)
+            | Self::Lte(_)
+            | Self::Gte(_) => format!("'{}'", self.literal()),
+            _ => {
+                let text = self.to_string();
+                match self.location() {
+                    Some(location) => text
+                        .strip_suffix(&format!(" in {}", location))
+                        .unwrap_or(&text)
+                        .to_string(),
+                    None => format!("'{}'", text),
+                }
+            }
+        }
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Self::Illegal(_, location)
+            | Self::Ident(_, location)
+            | Self::Number(_, location)
+            | Self::String(_, location)
+            | Self::DocComment(_, location) => location.clone(),
+            Self::EOF(location)
+            | Self::Comma(location)
+            | Self::Colon(location)
+            | Self::Semicolon(location)
+            | Self::LParen(location)
+            | Self::RParen(location)
+            | Self::LSqBracket(location)
+            | Self::RSqBracket(location)
+            | Self::LBrace(location)
+            | Self::Rbrace(location)
+            | Self::Function(location)
+            | Self::Let(location)
+            | Self::Var(location)
+            | Self::Bool(location)
+            | Self::True(location)
+            | Self::False(location)
+            | Self::While(location)
+            | Self::For(location)
+            | Self::In(location)
+            | Self::Break(location)
+            | Self::Continue(location)
+            | Self::If(location)
+            | Self::Else(location)
+            | Self::Return(location)
+            | Self::Throw(location)
+            | Self::Try(location)
+            | Self::Catch(location)
+            | Self::Int(location)
+            | Self::Float(location)
+            | Self::Str(location)
+            | Self::Bang(location)
+            | Self::Asterisk(location)
+            | Self::Plus(location)
+            | Self::Minus(location)
+            | Self::Slash(location)
+            | Self::Assign(location)
+            | Self::Lt(location)
+            | Self::Gt(location)
+            | Self::Eq(location)
+            | Self::Lte(location)
+            | Self::Gte(location)
+            | Self::Diff(location)
+            | Self::And(location)
+            | Self::Or(location)
+            | Self::ShiftLeft(location)
+            | Self::ShiftRight(location)
+            | Self::BitWiseAnd(location)
+            | Self::BitWiseOr(location)
+            | Self::Xor(location)
+            | Self::Array(location)
+            | Self::Map(location)
+            | Self::Mod(location)
+            | Self::DotDot(location)
+            | Self::Dot(location)
+            | Self::Import(location)
+            | Self::As(location)
+            | Self::Struct(location)
+            | Self::Enum(location)
+            | Self::Match(location)
+            | Self::Impl(location)
+            | Self::FatArrow(location) => location.clone(),
+        }
+    }
+
     pub fn to_type(&self) -> Type {
         match self {
             Self::Int(_)
-            | Self::Mod(_)
-            | Self::Plus(_)
-            | Self::Minus(_)
             | Self::BitWiseOr(_)
             | Self::ShiftLeft(_)
             | Self::BitWiseAnd(_)
             | Self::ShiftRight(_)
             | Self::Xor(_) => Type::Int,
+            Self::Float(_) => Type::Float,
             Self::Array(_) => Type::Array,
+            Self::Map(_) => Type::Map,
             Self::Bool(_)
             | Self::Lt(_)
             | Self::Gt(_)
@@ -193,6 +363,13 @@ impl Display for Token {
                     ",".to_string()
                 }
             }
+            Self::Colon(pos) => {
+                if let Some(pos) = pos {
+                    format!("':' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    ":".to_string()
+                }
+            }
             Self::Diff(pos) => {
                 if let Some(pos) = pos {
                     format!("'!=' in {}:{}:{}", pos.filename, pos.line, pos.position)
@@ -274,7 +451,7 @@ impl Display for Token {
                 if let Some(pos) = pos {
                     format!("'}}' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 } else {
-                    "}}".to_string()
+                    "}".to_string()
                 }
             }
             Self::Return(pos) => {
@@ -284,6 +461,121 @@ impl Display for Token {
                     "ret".to_string()
                 }
             }
+            Self::For(pos) => {
+                if let Some(pos) = pos {
+                    format!("'for' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "for".to_string()
+                }
+            }
+            Self::In(pos) => {
+                if let Some(pos) = pos {
+                    format!("'in' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "in".to_string()
+                }
+            }
+            Self::Break(pos) => {
+                if let Some(pos) = pos {
+                    format!("'break' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "break".to_string()
+                }
+            }
+            Self::Continue(pos) => {
+                if let Some(pos) = pos {
+                    format!(
+                        "'continue' in {}:{}:{}",
+                        pos.filename, pos.line, pos.position
+                    )
+                } else {
+                    "continue".to_string()
+                }
+            }
+            Self::DotDot(pos) => {
+                if let Some(pos) = pos {
+                    format!("'..' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "..".to_string()
+                }
+            }
+            Self::Dot(pos) => {
+                if let Some(pos) = pos {
+                    format!("'.' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    ".".to_string()
+                }
+            }
+            Self::Import(pos) => {
+                if let Some(pos) = pos {
+                    format!("'import' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "import".to_string()
+                }
+            }
+            Self::As(pos) => {
+                if let Some(pos) = pos {
+                    format!("'as' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "as".to_string()
+                }
+            }
+            Self::Struct(pos) => {
+                if let Some(pos) = pos {
+                    format!("'struct' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "struct".to_string()
+                }
+            }
+            Self::Enum(pos) => {
+                if let Some(pos) = pos {
+                    format!("'enum' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "enum".to_string()
+                }
+            }
+            Self::Match(pos) => {
+                if let Some(pos) = pos {
+                    format!("'match' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "match".to_string()
+                }
+            }
+            Self::Impl(pos) => {
+                if let Some(pos) = pos {
+                    format!("'impl' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "impl".to_string()
+                }
+            }
+            Self::FatArrow(pos) => {
+                if let Some(pos) = pos {
+                    format!("'=>' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "=>".to_string()
+                }
+            }
+            Self::Throw(pos) => {
+                if let Some(pos) = pos {
+                    format!("'throw' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "throw".to_string()
+                }
+            }
+            Self::Try(pos) => {
+                if let Some(pos) = pos {
+                    format!("'try' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "try".to_string()
+                }
+            }
+            Self::Catch(pos) => {
+                if let Some(pos) = pos {
+                    format!("'catch' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "catch".to_string()
+                }
+            }
             Self::RParen(pos) => {
                 if let Some(pos) = pos {
                     format!("')' in {}:{}:{}", pos.filename, pos.line, pos.position)
@@ -305,6 +597,13 @@ impl Display for Token {
                     "str".to_string()
                 }
             }
+            Self::Float(pos) => {
+                if let Some(pos) = pos {
+                    format!("'float' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                } else {
+                    "float".to_string()
+                }
+            }
             Self::True(pos) | Self::False(pos) => {
                 if let Some(pos) = pos {
                     format!("'bool' in {}:{}:{}", pos.filename, pos.line, pos.position)
@@ -341,6 +640,12 @@ impl Display for Token {
                 }
                 (_, _) => "illegal".to_string(),
             },
+            Self::DocComment(_, pos) => match pos {
+                Some(pos) => {
+                    format!("'///' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                }
+                _ => "///".to_string(),
+            },
             Self::Ident(val, pos) => match (val, pos) {
                 (Some(val), Some(pos)) => {
                     format!(
@@ -361,57 +666,63 @@ impl Display for Token {
             },
             Self::If(pos) => match pos {
                 Some(pos) => {
-                    format!("'if'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'if' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
                 _ => "if".to_string(),
             },
             Self::Or(pos) => match pos {
                 Some(pos) => {
-                    format!("'||'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'||' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "||".to_string(),
             },
             Self::Array(pos) => match pos {
                 Some(pos) => {
-                    format!("'array'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'array' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "array".to_string(),
+            },
+            Self::Map(pos) => match pos {
+                Some(pos) => {
+                    format!("'map' in {}:{}:{}", pos.filename, pos.line, pos.position)
+                }
+                _ => "map".to_string(),
             },
             Self::ShiftLeft(pos) => match pos {
                 Some(pos) => {
-                    format!("'<<'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'<<' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "<<".to_string(),
             },
             Self::ShiftRight(pos) => match pos {
                 Some(pos) => {
-                    format!("'>>'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'>>' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => ">>".to_string(),
             },
             Self::BitWiseAnd(pos) => match pos {
                 Some(pos) => {
-                    format!("'&'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'&' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "&".to_string(),
             },
             Self::BitWiseOr(pos) => match pos {
                 Some(pos) => {
-                    format!("'|'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'|' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "|".to_string(),
             },
             Self::Xor(pos) => match pos {
                 Some(pos) => {
-                    format!("'^'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'^' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "^".to_string(),
             },
             Self::Mod(pos) => match pos {
                 Some(pos) => {
-                    format!("'%'in {}:{}:{}", pos.filename, pos.line, pos.position)
+                    format!("'%' in {}:{}:{}", pos.filename, pos.line, pos.position)
                 }
-                _ => "if".to_string(),
+                _ => "%".to_string(),
             },
             _ => String::from("another thing, found it"),
         };
@@ -431,6 +742,4 @@ impl PartialEq for Token {
     }
 }
 
-impl std::cmp::Eq for Token {
-    fn assert_receiver_is_total_eq(&self) {}
-}
+impl std::cmp::Eq for Token {}
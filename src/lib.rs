@@ -1,7 +1,12 @@
-#![feature(trait_upcasting)]
-
 pub mod ast;
+pub mod compiler;
+pub mod diagnostic;
 pub mod evaluator;
+pub mod formatter;
 pub mod lexer;
+pub mod loader;
 pub mod object;
 pub mod parser;
+pub mod repl;
+pub mod typeck;
+pub mod vm;
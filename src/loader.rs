@@ -0,0 +1,109 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    ast::program::Program, lexer::token::Location, lexer::Lexer, parser::Parser,
+    typeck::TypeChecker,
+};
+
+/// The files named by `import` statements: each one is read, parsed and type
+/// checked once, then its module is cached under its canonical path.
+pub struct Loader<T> {
+    loaded: HashMap<PathBuf, T>,
+    // the file the first import came from, so importing it back is a cycle too
+    root: Option<(PathBuf, String)>,
+    // files whose import is running, outermost first
+    importing: Vec<(PathBuf, String)>,
+}
+
+pub enum Load<T> {
+    Cached(T),
+    /// A program to run for the module, `Loader::finish` must follow.
+    Parsed(PathBuf, Box<Program>),
+}
+
+impl<T: Clone> Loader<T> {
+    pub fn new() -> Self {
+        Self {
+            loaded: HashMap::new(),
+            root: None,
+            importing: vec![],
+        }
+    }
+
+    /// Loads `path`, relative to the directory of the file importing it at `location`.
+    pub fn load(&mut self, path: &str, location: Option<&Location>) -> Result<Load<T>, String> {
+        let file =
+            match location.and_then(|location| Path::new(location.filename.as_str()).parent()) {
+                Some(dir) => dir.join(path),
+                None => PathBuf::from(path),
+            };
+        let key =
+            fs::canonicalize(&file).map_err(|e| format!("cannot import '{}': {}", path, e))?;
+        if let Some(module) = self.loaded.get(&key) {
+            return Ok(Load::Cached(module.clone()));
+        }
+        if self.importing.is_empty() {
+            self.root = location.and_then(|location| {
+                let path = fs::canonicalize(location.filename.as_str()).ok()?;
+                Some((path, location.filename.to_string()))
+            });
+        }
+        let filename = file.to_string_lossy().to_string();
+        let chain: Vec<&(PathBuf, String)> = self.root.iter().chain(&self.importing).collect();
+        if let Some(start) = chain.iter().position(|(importing, _)| *importing == key) {
+            let mut cycle: Vec<&str> = chain[start..]
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect();
+            cycle.push(&filename);
+            return Err(format!("import cycle: {}", cycle.join(" -> ")));
+        }
+        let source =
+            fs::read_to_string(&file).map_err(|e| format!("cannot import '{}': {}", path, e))?;
+        let lexer = Lexer::new(Rc::new(source), Rc::new(filename.clone()));
+        let program = Parser::new(lexer).parse_program();
+        if !program.errors.is_empty() {
+            let errors: Vec<String> = program.errors.iter().map(|e| e.to_string()).collect();
+            return Err(errors.join("\n"));
+        }
+        let type_errors = TypeChecker::default().check(&program);
+        if !type_errors.is_empty() {
+            let errors: Vec<String> = type_errors.iter().map(|e| e.to_string()).collect();
+            return Err(errors.join("\n"));
+        }
+        self.importing.push((key.clone(), filename));
+        Ok(Load::Parsed(key, program))
+    }
+
+    /// Ends the import of `key`, caching its module when it loaded without errors.
+    pub fn finish(&mut self, key: PathBuf, module: Option<T>) {
+        self.importing.retain(|(importing, _)| *importing != key);
+        if let Some(module) = module {
+            self.loaded.insert(key, module);
+        }
+    }
+}
+
+impl<T: Clone> Default for Loader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The name a module is bound to when its import has no alias: the file stem.
+pub fn module_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// The name stack traces give the top level code of an imported file.
+pub fn module_trace_name(path: &str) -> String {
+    format!("<module {}>", path)
+}
@@ -1,13 +1,102 @@
-use std::{cell::RefCell, env, fs, process, rc::Rc};
+use std::{
+    cell::RefCell,
+    env, fs,
+    io::{self, Read},
+    process,
+    rc::Rc,
+    thread,
+};
 
-use bzr::{evaluator::Evaluator, lexer::Lexer, object::environment::Environment, parser::Parser};
+use bzr::{
+    ast::program::Program,
+    compiler::Compiler,
+    evaluator::{self, Evaluator, MAX_DEPTH},
+    formatter::format_source,
+    lexer::Lexer,
+    object::{environment::Environment, error::Error, Object},
+    parser::Parser,
+    repl::Repl,
+    typeck::TypeChecker,
+    vm::Vm,
+};
+
+// parse, type and compile errors
+const EXIT_COMPILE_ERROR: i32 = 1;
+// an error nothing handled while running
+const EXIT_RUNTIME_ERROR: i32 = 2;
+// a file `fmt --check` would change
+const EXIT_UNFORMATTED: i32 = 1;
+// native stack a level of calls can take in the evaluator, unoptimized builds
+// take several times more
+const CALL_STACK_SIZE: usize = if cfg!(debug_assertions) {
+    256 * 1024
+} else {
+    64 * 1024
+};
 
 fn main() {
-    let filename = if let Some(filename) = env::args().nth(1) {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "fmt") {
+        run_fmt(&args[1..]);
+        return;
+    }
+    let mut use_vm = false;
+    let mut max_depth = MAX_DEPTH;
+    let mut filename = None;
+    let mut args = args.into_iter();
+    // flags go before the script, everything after it is for the script
+    while let Some(arg) = args.next() {
+        if arg == "--vm" {
+            use_vm = true;
+        } else if arg == "--max-depth" {
+            match args.next().and_then(|depth| depth.parse().ok()) {
+                Some(depth) => max_depth = depth,
+                None => {
+                    eprintln!("--max-depth needs a number of calls");
+                    process::exit(1);
+                }
+            }
+        } else {
+            filename = Some(arg);
+            break;
+        }
+    }
+    let script_args = args.collect();
+    // the evaluator nests a native call for each call of the program
+    let stack_size = max_depth.saturating_add(1).saturating_mul(CALL_STACK_SIZE);
+    let running = thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            evaluator::set_args(script_args);
+            run(filename, use_vm, max_depth)
+        });
+    match running {
+        Ok(running) => {
+            if running.join().is_err() {
+                // the panic message is out already
+                process::exit(101);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error on start: {}, try a lower --max-depth", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(filename: Option<String>, use_vm: bool, max_depth: usize) {
+    let filename = if let Some(filename) = filename {
         filename
     } else {
-        eprintln!("please use bzr filename.bzr");
-        process::exit(1);
+        let stdin = io::stdin();
+        let mut repl = Repl::default();
+        repl.evaluator.max_depth = max_depth;
+        repl.evaluator.stack_size = max_depth.saturating_mul(CALL_STACK_SIZE);
+        if let Err(e) = repl.start(stdin.lock(), &mut io::stdout()) {
+            eprintln!("Error on repl: {:?}", e.kind());
+            process::exit(1);
+        }
+        return;
     };
     let input = match fs::read_to_string(&filename) {
         Ok(input) => input,
@@ -17,20 +106,128 @@ fn main() {
         }
     };
 
-    let lexer = Lexer::new(Rc::new(input), Rc::new(filename));
+    let source = Rc::new(input);
+    let lexer = Lexer::new(Rc::clone(&source), Rc::new(filename));
 
     let parse = Parser::new(lexer);
 
     let program = parse.parse_program();
-    let eval = Evaluator::default();
-    if program.errors.is_empty() {
-        eval.eval(
+    if !program.errors.is_empty() {
+        for error in program.errors {
+            eprintln!("{}", error.report(&source));
+        }
+        process::exit(EXIT_COMPILE_ERROR);
+    }
+    let type_errors = TypeChecker::default().check(&program);
+    if !type_errors.is_empty() {
+        for error in type_errors {
+            eprintln!("{}", error.report(&source));
+        }
+        process::exit(EXIT_COMPILE_ERROR);
+    }
+    if use_vm {
+        run_vm(&program, &source, max_depth);
+    } else {
+        let mut eval = Evaluator::default();
+        eval.max_depth = max_depth;
+        eval.stack_size = max_depth.saturating_mul(CALL_STACK_SIZE);
+        let evaluated = eval.eval(
             Some(program.as_ref()),
             Rc::new(RefCell::new(Environment::default())),
         );
-    } else {
-        for error in program.errors {
-            eprintln!("{}", error);
+        if let Some(obj) = evaluated {
+            exit_on_error(obj, &source);
+        }
+    }
+}
+
+fn exit_on_error(obj: Rc<dyn Object>, source: &str) {
+    if let Some(error) = obj.as_any().downcast_ref::<Error>() {
+        // an error raised in an imported file shows a line of that file
+        let imported = error
+            .location
+            .as_ref()
+            .and_then(|location| fs::read_to_string(location.filename.as_str()).ok());
+        eprintln!("{}", error.report(imported.as_deref().unwrap_or(source)));
+        process::exit(EXIT_RUNTIME_ERROR);
+    }
+}
+
+fn run_vm(program: &Program, source: &str, max_depth: usize) {
+    let bytecode = match Compiler::default().compile(program) {
+        Ok(bytecode) => bytecode,
+        Err(e) => {
+            eprintln!("Error on compile: {}", e);
+            process::exit(EXIT_COMPILE_ERROR);
+        }
+    };
+    let mut vm = Vm::new(bytecode);
+    vm.max_depth = max_depth;
+    match vm.run() {
+        Ok(obj) => exit_on_error(obj, source),
+        Err(e) => {
+            eprintln!("Error on vm: {}", e);
+            process::exit(EXIT_RUNTIME_ERROR);
+        }
+    }
+}
+
+/// `bzr fmt [--check] [files]` rewrites each file formatted, or with `--check`
+/// only lists the files it would change. Without files it formats stdin.
+fn run_fmt(args: &[String]) {
+    let check = args.iter().any(|arg| arg == "--check");
+    let files: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+    if files.is_empty() {
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut input) {
+            eprintln!("Error on read stdin: {:?}", e.kind());
+            process::exit(1);
+        }
+        let formatted = format_or_exit(&input, "<stdin>");
+        if check {
+            if formatted != input {
+                eprintln!("<stdin> is not formatted");
+                process::exit(EXIT_UNFORMATTED);
+            }
+        } else {
+            print!("{}", formatted);
+        }
+        return;
+    }
+    let mut unformatted = false;
+    for file in files {
+        let input = match fs::read_to_string(file) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Error on openfile {}: {:?}", file, e.kind());
+                process::exit(1);
+            }
+        };
+        let formatted = format_or_exit(&input, file);
+        if formatted == input {
+            continue;
+        }
+        if check {
+            eprintln!("{} is not formatted", file);
+            unformatted = true;
+        } else if let Err(e) = fs::write(file, formatted) {
+            eprintln!("Error on write {}: {:?}", file, e.kind());
+            process::exit(1);
+        }
+    }
+    if unformatted {
+        process::exit(EXIT_UNFORMATTED);
+    }
+}
+
+fn format_or_exit(input: &str, filename: &str) -> String {
+    match format_source(input, filename) {
+        Ok(formatted) => formatted,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}", error.report(input));
+            }
+            process::exit(EXIT_COMPILE_ERROR);
         }
     }
 }
@@ -2,7 +2,7 @@ use std::{any::Any, cell::RefCell, fmt::Display, rc::Rc};
 
 use crate::ast::types::Type;
 
-use super::Object;
+use super::{print_once, Object};
 
 pub struct Array {
     pub elements: RefCell<Vec<Rc<dyn Object>>>,
@@ -22,17 +22,18 @@ impl Object for Array {
     }
 
     fn inspect(&self) -> String {
-        let mut buffer = String::new();
-        buffer.push('[');
-        for (idx, elem) in self.elements.borrow_mut().iter().enumerate() {
-            if idx == 0 {
-                buffer.push_str(&elem.to_string())
-            } else {
-                buffer.push_str(&format!(", {}", elem))
+        let elements = print_once(self, || {
+            let mut buffer = String::new();
+            for (idx, elem) in self.elements.borrow().iter().enumerate() {
+                if idx == 0 {
+                    buffer.push_str(&elem.to_string())
+                } else {
+                    buffer.push_str(&format!(", {}", elem))
+                }
             }
-        }
-        buffer.push(']');
-        buffer
+            buffer
+        });
+        format!("[{}]", elements.as_deref().unwrap_or("..."))
     }
 
     fn as_any(&self) -> &dyn Any {
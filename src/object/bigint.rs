@@ -0,0 +1,353 @@
+use std::{any::Any, cmp::Ordering, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{hash::HashKey, integer::Integer, Object};
+
+/// An int outside the range of `i64`, what int arithmetic gives instead of
+/// overflowing. Results that fit again are plain `Integer`s, so values of
+/// this type are always big.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    // the magnitude in base 2^32, least significant first, no leading zeros
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        Self { negative, digits }
+    }
+
+    /// Reads a decimal int with an optional sign, like `i64` parsing does.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let ten = Self::from(10);
+        digits
+            .chars()
+            .try_fold(Self::new(false, vec![]), |acc, ch| {
+                let digit = ch.to_digit(10)?;
+                Some(acc.mul(&ten).add(&Self::from(digit as i64)))
+            })
+            .map(|big| if negative { big.neg() } else { big })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, digit| acc << 32 | *digit as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0.0, |acc, digit| acc * 4294967296.0 + *digit as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(!self.negative, self.digits.clone())
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(false, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return Self::new(self.negative, add_digits(&self.digits, &other.digits));
+        }
+        match cmp_digits(&self.digits, &other.digits) {
+            Ordering::Less => Self::new(other.negative, sub_digits(&other.digits, &self.digits)),
+            _ => Self::new(self.negative, sub_digits(&self.digits, &other.digits)),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.digits.iter().enumerate() {
+                let total = digits[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                digits[i + j] = total as u32;
+                carry = total >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        Self::new(self.negative != other.negative, digits)
+    }
+
+    /// The quotient rounded toward zero and the remainder with the sign of
+    /// `self`, like `/` and `%` on `i64`. `None` when `other` is zero.
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+        let mut quotient = vec![0u32; self.digits.len()];
+        let mut remainder: Vec<u32> = vec![];
+        for idx in (0..self.digits.len() * 32).rev() {
+            remainder = shift_left_one(&remainder);
+            if self.digits[idx / 32] >> (idx % 32) & 1 == 1 {
+                match remainder.first_mut() {
+                    Some(first) => *first |= 1,
+                    None => remainder.push(1),
+                }
+            }
+            if cmp_digits(&remainder, &other.digits) != Ordering::Less {
+                remainder = sub_digits(&remainder, &other.digits);
+                quotient[idx / 32] |= 1 << (idx % 32);
+            }
+        }
+        Some((
+            Self::new(self.negative != other.negative, quotient),
+            Self::new(self.negative, remainder),
+        ))
+    }
+
+    /// `self` times 2 to the power of `bits`.
+    pub fn shl(&self, bits: u32) -> Self {
+        let mut digits = vec![0u32; (bits / 32) as usize];
+        let shift = bits % 32;
+        let mut carry = 0u32;
+        for digit in self.digits.iter() {
+            digits.push(if shift == 0 {
+                *digit
+            } else {
+                digit << shift | carry
+            });
+            carry = if shift == 0 { 0 } else { digit >> (32 - shift) };
+        }
+        digits.push(carry);
+        Self::new(self.negative, digits)
+    }
+
+    /// `self` divided by 2 to the power of `bits`, rounded down like `>>` on
+    /// `i64`.
+    pub fn shr(&self, bits: u32) -> Self {
+        if self.negative {
+            // -x >> n is -((x - 1) >> n) - 1
+            let one = Self::from(1);
+            return self.abs().sub(&one).shr(bits).add(&one).neg();
+        }
+        let skip = (bits / 32) as usize;
+        let shift = bits % 32;
+        let digits = (skip..self.digits.len())
+            .map(|idx| {
+                let high = self.digits.get(idx + 1).copied().unwrap_or(0) as u64;
+                ((high << 32 | self.digits[idx] as u64) >> shift) as u32
+            })
+            .collect();
+        Self::new(false, digits)
+    }
+
+    pub fn bitand(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    /// Applies `op` to the two's complement digits of both values, like the
+    /// bit operators on `i64`.
+    fn bitwise(&self, other: &Self, op: fn(u32, u32) -> u32) -> Self {
+        // one more digit than needed so the top bit is the sign
+        let len = self.digits.len().max(other.digits.len()) + 1;
+        let (a, b) = (self.twos_complement(len), other.twos_complement(len));
+        let digits: Vec<u32> = a.iter().zip(b.iter()).map(|(a, b)| op(*a, *b)).collect();
+        if digits[len - 1] >> 31 == 0 {
+            return Self::new(false, digits);
+        }
+        let inverted: Vec<u32> = digits.iter().map(|digit| !digit).collect();
+        Self::new(true, add_digits(&inverted, &[1]))
+    }
+
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut digits = self.digits.clone();
+        digits.resize(len, 0);
+        if !self.negative {
+            return digits;
+        }
+        let inverted: Vec<u32> = digits.iter().map(|digit| !digit).collect();
+        let mut digits = add_digits(&inverted, &[1]);
+        digits.truncate(len);
+        digits
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(val: i64) -> Self {
+        let magnitude = val.unsigned_abs();
+        Self::new(val < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_digits(&self.digits, &other.digits),
+            (true, true) => cmp_digits(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Object for BigInt {
+    fn get_type(&self) -> Type {
+        Type::Int
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn hash_key(&self) -> Option<HashKey> {
+        Some(HashKey::BigInt(self.clone()))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // peel off nine decimal digits at a time
+        let mut chunks = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let mut remainder = 0u64;
+            for digit in digits.iter_mut().rev() {
+                let current = remainder << 32 | *digit as u64;
+                *digit = (current / 1_000_000_000) as u32;
+                remainder = current % 1_000_000_000;
+            }
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+            chunks.push(remainder);
+        }
+        let mut text = String::from(if self.negative { "-" } else { "" });
+        match chunks.pop() {
+            Some(first) => text.push_str(&first.to_string()),
+            None => text.push('0'),
+        }
+        for chunk in chunks.iter().rev() {
+            text.push_str(&format!("{:09}", chunk));
+        }
+        write!(f, "{}", text)
+    }
+}
+
+/// Reads an `Integer` or a `BigInt` as a `BigInt`.
+pub fn as_big(obj: &dyn Object) -> Option<BigInt> {
+    if let Some(big) = obj.as_any().downcast_ref::<BigInt>() {
+        Some(big.clone())
+    } else {
+        obj.as_any()
+            .downcast_ref::<Integer>()
+            .map(|integer| BigInt::from(integer.val))
+    }
+}
+
+/// The int object for `big`, an `Integer` when it fits in one.
+pub fn int_object(big: BigInt) -> Rc<dyn Object> {
+    match big.to_i64() {
+        Some(val) => Rc::new(Integer::new(val)),
+        None => Rc::new(big),
+    }
+}
+
+fn cmp_digits(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for idx in 0..a.len().max(b.len()) {
+        let total = *a.get(idx).unwrap_or(&0) as u64 + *b.get(idx).unwrap_or(&0) as u64 + carry;
+        digits.push(total as u32);
+        carry = total >> 32;
+    }
+    digits.push(carry as u32);
+    digits
+}
+
+/// `a - b` for `a` at least `b`.
+fn sub_digits(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut digits = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (idx, digit) in a.iter().enumerate() {
+        let mut diff = *digit as i64 - *b.get(idx).unwrap_or(&0) as i64 - borrow;
+        borrow = if diff < 0 { 1 } else { 0 };
+        if diff < 0 {
+            diff += 1 << 32;
+        }
+        digits.push(diff as u32);
+    }
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn shift_left_one(digits: &[u32]) -> Vec<u32> {
+    let mut shifted = Vec::with_capacity(digits.len() + 1);
+    let mut carry = 0u32;
+    for digit in digits.iter() {
+        shifted.push(digit << 1 | carry);
+        carry = digit >> 31;
+    }
+    if carry != 0 {
+        shifted.push(carry);
+    }
+    shifted
+}
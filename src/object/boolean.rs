@@ -2,7 +2,7 @@ use std::{any::Any, fmt::Display};
 
 use crate::ast::types::Type;
 
-use super::Object;
+use super::{hash::HashKey, Object};
 
 pub struct Boolean {
     pub val: bool,
@@ -26,6 +26,10 @@ impl Object for Boolean {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn hash_key(&self) -> Option<HashKey> {
+        Some(HashKey::Bool(self.val))
+    }
 }
 
 impl Display for Boolean {
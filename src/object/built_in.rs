@@ -5,14 +5,48 @@ use crate::ast::types::Type;
 use super::Object;
 
 pub type BuildInFn = fn(&[Rc<dyn Object>]) -> Rc<dyn Object>;
+pub type CallingFn = fn(&[Rc<dyn Object>], &mut dyn Caller) -> Rc<dyn Object>;
+
+/// Runs bzr functions for the builtins that take callbacks, each backend has its own.
+pub trait Caller {
+    fn call_function(
+        &mut self,
+        function: Rc<dyn Object>,
+        args: Vec<Rc<dyn Object>>,
+    ) -> Rc<dyn Object>;
+}
+
+pub enum Native {
+    Plain(BuildInFn),
+    Calling(CallingFn),
+}
 
 pub struct BuiltIn {
-    pub function: BuildInFn,
+    pub function: Native,
+    // receives error arguments instead of propagating them
+    pub accepts_errors: bool,
 }
 
 impl BuiltIn {
     pub fn new(function: BuildInFn) -> Self {
-        Self { function }
+        Self {
+            function: Native::Plain(function),
+            accepts_errors: false,
+        }
+    }
+
+    pub fn calling(function: CallingFn) -> Self {
+        Self {
+            function: Native::Calling(function),
+            accepts_errors: false,
+        }
+    }
+
+    pub fn call(&self, args: &[Rc<dyn Object>], caller: &mut dyn Caller) -> Rc<dyn Object> {
+        match self.function {
+            Native::Plain(function) => function(args),
+            Native::Calling(function) => function(args, caller),
+        }
     }
 }
 
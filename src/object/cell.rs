@@ -0,0 +1,63 @@
+use std::{any::Any, cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::Object;
+
+/// A variable slot shared between the vm frame that declared it and the
+/// closures that captured it, so writes on either side are seen by both.
+pub struct Cell {
+    pub val: RefCell<Rc<dyn Object>>,
+}
+
+impl Cell {
+    pub fn new(val: Rc<dyn Object>) -> Self {
+        Self {
+            val: RefCell::new(val),
+        }
+    }
+
+    /// The value held by `slot`, looking through a cell.
+    pub fn get(slot: &Rc<dyn Object>) -> Rc<dyn Object> {
+        match slot.as_any().downcast_ref::<Cell>() {
+            Some(cell) => Rc::clone(&cell.val.borrow()),
+            None => Rc::clone(slot),
+        }
+    }
+
+    /// Writes `val` into the cell held by `slot`, or replaces a plain value.
+    pub fn set(slot: &mut Rc<dyn Object>, val: Rc<dyn Object>) {
+        match slot.as_any().downcast_ref::<Cell>() {
+            Some(cell) => *cell.val.borrow_mut() = val,
+            None => *slot = val,
+        }
+    }
+
+    /// Moves the value of `slot` into a cell, once, and returns that cell.
+    pub fn capture(slot: &mut Rc<dyn Object>) -> Rc<dyn Object> {
+        if !slot.as_any().is::<Cell>() {
+            *slot = Rc::new(Cell::new(Rc::clone(slot)));
+        }
+        Rc::clone(slot)
+    }
+}
+
+impl Object for Cell {
+    fn get_type(&self) -> Type {
+        self.val.borrow().get_type()
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.val.borrow())
+    }
+}
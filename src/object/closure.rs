@@ -0,0 +1,39 @@
+use std::{any::Any, cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{compiled_function::CompiledFunction, Object};
+
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub free: RefCell<Vec<Rc<dyn Object>>>,
+}
+
+impl Closure {
+    pub fn new(function: Rc<CompiledFunction>, free: Vec<Rc<dyn Object>>) -> Self {
+        Self {
+            function,
+            free: RefCell::new(free),
+        }
+    }
+}
+
+impl Object for Closure {
+    fn get_type(&self) -> Type {
+        Type::Function
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.function.source)
+    }
+}
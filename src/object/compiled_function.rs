@@ -0,0 +1,62 @@
+use std::{any::Any, fmt::Display, rc::Rc};
+
+use crate::{ast::types::Type, lexer::token::Location};
+
+use super::Object;
+
+#[derive(Clone)]
+pub struct CompiledFunction {
+    pub instructions: Rc<[u8]>,
+    pub num_locals: usize,
+    pub num_parameters: usize,
+    pub name: String,
+    // (instruction position, location) pairs sorted by position
+    pub locations: Rc<[(usize, Location)]>,
+    // what closures of it print, like the tree walker's functions
+    pub source: Rc<str>,
+}
+
+impl CompiledFunction {
+    pub fn new(
+        instructions: Vec<u8>,
+        num_locals: usize,
+        num_parameters: usize,
+        name: String,
+    ) -> Self {
+        Self {
+            instructions: instructions.into(),
+            num_locals,
+            num_parameters,
+            name,
+            locations: Rc::new([]),
+            source: Rc::from(""),
+        }
+    }
+
+    pub fn location_at(&self, ip: usize) -> Option<Location> {
+        let idx = self
+            .locations
+            .partition_point(|(position, _)| *position <= ip);
+        idx.checked_sub(1).map(|idx| self.locations[idx].1.clone())
+    }
+}
+
+impl Object for CompiledFunction {
+    fn get_type(&self) -> Type {
+        Type::Function
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for CompiledFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "compiled function {}", self.name)
+    }
+}
@@ -0,0 +1,188 @@
+use std::{any::Any, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{method::Methods, Object};
+
+/// What an `enum` declaration binds its name to: the variants and the types
+/// of the values each holds.
+pub struct EnumDef {
+    pub name: Rc<str>,
+    pub variants: Vec<(String, Vec<Type>)>,
+    pub methods: Methods,
+}
+
+impl EnumDef {
+    pub fn new(name: Rc<str>, variants: Vec<(String, Vec<Type>)>) -> Self {
+        Self {
+            name,
+            variants,
+            methods: Methods::default(),
+        }
+    }
+
+    pub fn variant(&self, name: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|(variant, _)| variant == name)
+    }
+}
+
+impl Object for EnumDef {
+    fn get_type(&self) -> Type {
+        Type::EnumDef
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for EnumDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+/// A value of an enum type: which variant it is and the values it holds.
+pub struct Variant {
+    // always an `EnumDef`, kept as the object so values share it
+    def: Rc<dyn Object>,
+    pub index: usize,
+    pub values: Vec<Rc<dyn Object>>,
+}
+
+impl Variant {
+    pub fn def(&self) -> &EnumDef {
+        self.def
+            .as_any()
+            .downcast_ref::<EnumDef>()
+            .expect("enum values are built from an EnumDef")
+    }
+
+    pub fn name(&self) -> &str {
+        &self.def().variants[self.index].0
+    }
+}
+
+impl Object for Variant {
+    fn get_type(&self) -> Type {
+        Type::Enum(Rc::clone(&self.def().name))
+    }
+
+    fn inspect(&self) -> String {
+        let name = format!("{}.{}", self.def().name, self.name());
+        if self.values.is_empty() {
+            return name;
+        }
+        let values: Vec<String> = self.values.iter().map(|value| value.to_string()).collect();
+        format!("{}({})", name, values.join(", "))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+/// `Shape.Circle` for a variant that holds values, called with them to build
+/// the enum value.
+pub struct Constructor {
+    def: Rc<dyn Object>,
+    index: usize,
+}
+
+impl Constructor {
+    pub fn call(&self, args: Vec<Rc<dyn Object>>) -> Result<Variant, String> {
+        let def = self
+            .def
+            .as_any()
+            .downcast_ref::<EnumDef>()
+            .expect("constructors are built from an EnumDef");
+        let (name, types) = &def.variants[self.index];
+        if args.len() != types.len() {
+            return Err(format!(
+                "wrong number of arguments to {}.{}: want={}, got={}",
+                def.name,
+                name,
+                types.len(),
+                args.len()
+            ));
+        }
+        for (idx, (arg, typ)) in args.iter().zip(types.iter()).enumerate() {
+            if arg.get_type() != *typ {
+                return Err(format!(
+                    "argument {} of {}.{} expected {}, got {}",
+                    idx + 1,
+                    def.name,
+                    name,
+                    typ,
+                    arg.get_type()
+                ));
+            }
+        }
+        Ok(Variant {
+            def: Rc::clone(&self.def),
+            index: self.index,
+            values: args,
+        })
+    }
+}
+
+impl Object for Constructor {
+    fn get_type(&self) -> Type {
+        Type::Function
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Constructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let def = self
+            .def
+            .as_any()
+            .downcast_ref::<EnumDef>()
+            .expect("constructors are built from an EnumDef");
+        write!(f, "<variant {}.{}>", def.name, def.variants[self.index].0)
+    }
+}
+
+/// `Shape.Circle` on the enum `object` names: the value itself for a variant
+/// without values, otherwise the constructor taking them.
+pub fn variant(object: &Rc<dyn Object>, name: &str) -> Result<Rc<dyn Object>, String> {
+    let def = match object.as_any().downcast_ref::<EnumDef>() {
+        Some(def) => def,
+        None => return Err(format!("{} is not an enum", object.get_type())),
+    };
+    let index = match def.variant(name) {
+        Some(index) => index,
+        None => return Err(format!("{} has no variant '{}'", def.name, name)),
+    };
+    if def.variants[index].1.is_empty() {
+        return Ok(Rc::new(Variant {
+            def: Rc::clone(object),
+            index,
+            values: vec![],
+        }));
+    }
+    Ok(Rc::new(Constructor {
+        def: Rc::clone(object),
+        index,
+    }))
+}
@@ -1,10 +1,44 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::lexer::token::Location;
 
 use super::Object;
 
 pub struct Environment {
     pub store: HashMap<String, Rc<dyn Object>>,
     pub outer: Option<Rc<RefCell<Environment>>>,
+    /// The immutable bindings of this scope with how and where they were declared.
+    immutable: HashMap<String, (Declaration, Option<Location>)>,
+}
+
+/// The statement that declared a name. Only `var` bindings can be assigned or
+/// declared again in the same scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Declaration {
+    Let,
+    Var,
+    Import,
+    Struct,
+    Enum,
+}
+
+impl Declaration {
+    pub fn is_mutable(&self) -> bool {
+        *self == Self::Var
+    }
+}
+
+impl Display for Declaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keyword = match self {
+            Self::Let => "let",
+            Self::Var => "var",
+            Self::Import => "import",
+            Self::Struct => "struct",
+            Self::Enum => "enum",
+        };
+        write!(f, "{}", keyword)
+    }
 }
 
 impl Environment {
@@ -12,6 +46,7 @@ impl Environment {
         Self {
             store: HashMap::new(),
             outer,
+            immutable: HashMap::new(),
         }
     }
 
@@ -25,6 +60,54 @@ impl Environment {
         }
     }
 
+    /// Binds `name` in this scope, shadowing outer bindings. Fails when this
+    /// scope already has an immutable binding with that name.
+    pub fn declare(
+        &mut self,
+        name: String,
+        obj: Rc<dyn Object>,
+        declaration: Declaration,
+        location: Option<Location>,
+    ) -> Result<(), String> {
+        if let Some((declared, at)) = self.immutable.get(&name) {
+            return Err(format!(
+                "'{}' is already declared with {}{}",
+                name,
+                declared,
+                declared_at(at)
+            ));
+        }
+        if !declaration.is_mutable() {
+            self.immutable.insert(name.clone(), (declaration, location));
+        }
+        self.store.insert(name, obj);
+        Ok(())
+    }
+
+    /// Updates the closest binding of `name`, defining it here when there is none.
+    pub fn assign(&mut self, name: String, obj: Rc<dyn Object>) -> Result<(), String> {
+        if self.store.contains_key(&name) {
+            if let Some((declared, at)) = self.immutable.get(&name) {
+                return Err(format!(
+                    "cannot assign to '{}', it was declared with {}{}",
+                    name,
+                    declared,
+                    declared_at(at)
+                ));
+            }
+            self.store.insert(name, obj);
+            Ok(())
+        } else if self.exists_in_outer(&name) {
+            match self.outer {
+                Some(ref outer) => outer.borrow_mut().assign(name, obj),
+                None => Ok(()),
+            }
+        } else {
+            self.store.insert(name, obj);
+            Ok(())
+        }
+    }
+
     pub fn get(&mut self, name: String) -> Option<Rc<dyn Object>> {
         if let Some(obj) = self.store.get(&name) {
             Some(Rc::clone(obj))
@@ -53,3 +136,11 @@ impl Default for Environment {
         Self::new(None)
     }
 }
+
+/// " at <location>" for messages pointing at a declaration, empty when it is unknown.
+pub fn declared_at(location: &Option<Location>) -> String {
+    match location {
+        Some(location) => format!(" at {}", location),
+        None => String::new(),
+    }
+}
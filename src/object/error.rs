@@ -1,22 +1,124 @@
-use std::{any::Any, fmt::Display};
+use std::{any::Any, fmt::Display, iter, rc::Rc};
 
-use crate::ast::types::Type;
+use crate::{ast::types::Type, lexer::token::Location};
 
 use super::Object;
 
+// how many times in a row a stack trace shows the same line
+const TRACE_REPEATS: usize = 3;
+
+#[derive(Clone)]
 pub struct Error {
     pub val: String,
+    pub location: Option<Location>,
+    pub trace: Vec<TraceEntry>,
+    // the map thrown to raise it, whose other keys a `catch` still sees
+    pub value: Option<Rc<dyn Object>>,
+}
+
+/// A call the error went through on its way out, innermost first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEntry {
+    pub function: String,
+    pub location: Option<Location>,
 }
 
 impl Error {
     pub fn new(val: String) -> Self {
-        Self { val }
+        Self {
+            val,
+            location: None,
+            trace: vec![],
+            value: None,
+        }
+    }
+
+    /// Gives `obj` the location returned by `location` when it is an error without one.
+    pub fn locate<F>(obj: Rc<dyn Object>, location: F) -> Rc<dyn Object>
+    where
+        F: FnOnce() -> Option<Location>,
+    {
+        match obj.as_any().downcast_ref::<Error>() {
+            Some(error) if error.location.is_none() => match location() {
+                Some(location) => {
+                    let mut located = error.clone();
+                    located.location = Some(location);
+                    Rc::new(located)
+                }
+                None => obj,
+            },
+            _ => obj,
+        }
+    }
+
+    /// Records that `obj`, when it is an error, left `function` called at `location`.
+    pub fn trace(
+        obj: Rc<dyn Object>,
+        function: String,
+        location: Option<Location>,
+    ) -> Rc<dyn Object> {
+        if !obj.as_any().is::<Error>() {
+            return obj;
+        }
+        let any: Rc<dyn Any> = obj;
+        let mut error = any.downcast::<Error>().expect("checked to be an error");
+        // an error on its way out is usually only here, so deep traces grow in place
+        Rc::make_mut(&mut error)
+            .trace
+            .push(TraceEntry { function, location });
+        error
+    }
+
+    pub fn report(&self, source: &str) -> String {
+        let mut report = match &self.location {
+            Some(location) => location.report(&self.val, source),
+            None => self.val.clone(),
+        };
+        if !self.trace.is_empty() {
+            report.push_str("\nstack trace:");
+            let functions = self.trace.iter().map(|entry| entry.function.as_str());
+            let locations = self.trace.iter().map(|entry| entry.location.as_ref());
+            let frames = functions
+                .chain(iter::once("main"))
+                .zip(iter::once(self.location.as_ref()).chain(locations));
+            let mut last = String::new();
+            let mut repeated = 0;
+            for (function, location) in frames {
+                let mut line = format!("\n    {}", function);
+                if let Some(location) = location {
+                    line.push_str(&format!(" at {}", location));
+                }
+                // deep recursion shows each line a few times
+                if line == last {
+                    repeated += 1;
+                    if repeated >= TRACE_REPEATS {
+                        continue;
+                    }
+                } else {
+                    Self::report_repeats(&mut report, repeated);
+                    repeated = 0;
+                }
+                report.push_str(&line);
+                last = line;
+            }
+            Self::report_repeats(&mut report, repeated);
+        }
+        report
+    }
+
+    fn report_repeats(report: &mut String, repeated: usize) {
+        if repeated >= TRACE_REPEATS {
+            report.push_str(&format!(
+                "\n    ... repeated {} more times",
+                repeated + 1 - TRACE_REPEATS
+            ));
+        }
     }
 }
 
 impl Object for Error {
     fn get_type(&self) -> Type {
-        Type::String
+        Type::Error
     }
 
     fn inspect(&self) -> String {
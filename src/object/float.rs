@@ -0,0 +1,49 @@
+use std::{any::Any, fmt::Display};
+
+use crate::ast::types::Type;
+
+use super::{bigint::BigInt, integer::Integer, Object};
+
+pub struct Float {
+    pub val: f64,
+}
+
+impl Float {
+    pub fn new(val: f64) -> Self {
+        Self { val }
+    }
+}
+
+impl Object for Float {
+    fn get_type(&self) -> Type {
+        Type::Float
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// debug formatting keeps the decimal point, so 1.0 does not print as an int
+impl Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.val)
+    }
+}
+
+/// Reads an int or a float as an f64, for mixed arithmetic and the math builtins.
+pub fn as_f64(obj: &dyn Object) -> Option<f64> {
+    if let Some(float) = obj.as_any().downcast_ref::<Float>() {
+        Some(float.val)
+    } else if let Some(big) = obj.as_any().downcast_ref::<BigInt>() {
+        Some(big.to_f64())
+    } else {
+        obj.as_any()
+            .downcast_ref::<Integer>()
+            .map(|integer| integer.val as f64)
+    }
+}
@@ -4,9 +4,12 @@ use crate::ast::{expression::Expression, stmt::block_stmt::BlockStatement, types
 
 use super::{environment::Environment, Object};
 
+/// Stack trace name of functions declared without one.
+pub const ANONYMOUS: &str = "<anonymous>";
+
 pub struct Function {
     pub parameters: Vec<Rc<dyn Expression>>,
-    pub name: Rc<dyn Expression>,
+    pub name: Option<Rc<dyn Expression>>,
     pub body: Option<Rc<BlockStatement>>,
     pub env: Rc<RefCell<Environment>>,
 }
@@ -14,7 +17,7 @@ pub struct Function {
 impl Function {
     pub fn new(
         parameters: Vec<Rc<dyn Expression>>,
-        name: Rc<dyn Expression>,
+        name: Option<Rc<dyn Expression>>,
         body: Option<Rc<BlockStatement>>,
         env: Rc<RefCell<Environment>>,
     ) -> Self {
@@ -27,13 +30,23 @@ impl Function {
     }
 }
 
+impl Function {
+    /// The name shown in stack traces.
+    pub fn trace_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.to_string(),
+            None => ANONYMOUS.to_string(),
+        }
+    }
+}
+
 impl Object for Function {
     fn get_type(&self) -> crate::ast::types::Type {
         Type::Function
     }
 
     fn inspect(&self) -> String {
-        todo!()
+        self.to_string()
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -43,20 +56,25 @@ impl Object for Function {
 
 impl Display for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buffer = String::new();
-        let mut args = Vec::with_capacity(5);
-        for param in self.parameters.iter() {
-            args.push(param.to_string())
-        }
-        buffer.push_str("fn (");
-        buffer.push_str(&args.join(","));
-        buffer.push_str(") {\n");
-        buffer.push_str(&if let Some(ref body) = self.body {
-            body.to_string()
-        } else {
-            String::new()
-        });
-        buffer.push_str("\n}");
-        write!(f, "{}", buffer)
+        write!(f, "{}", source(&self.parameters, self.body.as_deref()))
+    }
+}
+
+/// How a function value prints, the same for the tree walker and the vm.
+pub fn source(parameters: &[Rc<dyn Expression>], body: Option<&BlockStatement>) -> String {
+    let mut buffer = String::new();
+    let mut args = Vec::with_capacity(5);
+    for param in parameters.iter() {
+        args.push(param.to_string())
     }
+    buffer.push_str("fn (");
+    buffer.push_str(&args.join(","));
+    buffer.push_str(") {\n");
+    buffer.push_str(&if let Some(body) = body {
+        body.to_string()
+    } else {
+        String::new()
+    });
+    buffer.push_str("\n}");
+    buffer
 }
@@ -0,0 +1,61 @@
+use std::{any::Any, cell::RefCell, collections::BTreeMap, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{bigint::BigInt, print_once, Object};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HashKey {
+    Int(i64),
+    BigInt(BigInt),
+    Bool(bool),
+    Str(String),
+}
+
+pub struct HashPair {
+    pub key: Rc<dyn Object>,
+    pub value: Rc<dyn Object>,
+}
+
+pub struct Hash {
+    pub pairs: RefCell<BTreeMap<HashKey, HashPair>>,
+}
+
+impl Hash {
+    pub fn new(pairs: BTreeMap<HashKey, HashPair>) -> Self {
+        Self {
+            pairs: RefCell::new(pairs),
+        }
+    }
+}
+
+impl Object for Hash {
+    fn get_type(&self) -> Type {
+        Type::Map
+    }
+
+    fn inspect(&self) -> String {
+        let pairs = print_once(self, || {
+            let mut buffer = String::new();
+            for (idx, pair) in self.pairs.borrow().values().enumerate() {
+                if idx == 0 {
+                    buffer.push_str(&format!("{}: {}", pair.key, pair.value))
+                } else {
+                    buffer.push_str(&format!(", {}: {}", pair.key, pair.value))
+                }
+            }
+            buffer
+        });
+        format!("{{{}}}", pairs.as_deref().unwrap_or("..."))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Hash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
@@ -0,0 +1,35 @@
+use std::{any::Any, fmt::Display};
+
+use crate::ast::types::Type;
+
+use super::Object;
+
+/// Raised by `break` and `continue`, unwound through blocks up to the enclosing loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopControl {
+    Break,
+    Continue,
+}
+
+impl Object for LoopControl {
+    fn get_type(&self) -> Type {
+        Type::Unknown
+    }
+
+    fn inspect(&self) -> String {
+        match self {
+            Self::Break => "break".to_string(),
+            Self::Continue => "continue".to_string(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for LoopControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
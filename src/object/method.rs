@@ -0,0 +1,116 @@
+use std::{any::Any, cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{
+    enumeration::{EnumDef, Variant},
+    structure::{Struct, StructDef},
+    Object,
+};
+
+/// The functions `impl` blocks add to a struct or enum, by name.
+pub type Methods = RefCell<HashMap<String, Rc<dyn Object>>>;
+
+/// `value.name` for a method of the value: calling it passes `receiver`
+/// before the arguments.
+pub struct BoundMethod {
+    pub name: String,
+    pub receiver: Rc<dyn Object>,
+    pub function: Rc<dyn Object>,
+}
+
+impl BoundMethod {
+    pub fn new(name: String, receiver: Rc<dyn Object>, function: Rc<dyn Object>) -> Self {
+        Self {
+            name,
+            receiver,
+            function,
+        }
+    }
+
+    /// The arguments of a call with the receiver in front.
+    pub fn arguments<T: From<Rc<dyn Object>>>(&self, args: Vec<T>) -> Vec<T> {
+        let mut arguments = Vec::with_capacity(args.len() + 1);
+        arguments.push(T::from(Rc::clone(&self.receiver)));
+        arguments.extend(args);
+        arguments
+    }
+}
+
+impl Object for BoundMethod {
+    fn get_type(&self) -> Type {
+        Type::Function
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for BoundMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<method {}.{}>", self.receiver.get_type(), self.name)
+    }
+}
+
+/// The name and the methods of the struct or enum `def` declares.
+fn methods(def: &dyn Object) -> Option<(&str, &Methods)> {
+    if let Some(def) = def.as_any().downcast_ref::<StructDef>() {
+        Some((&def.name, &def.methods))
+    } else if let Some(def) = def.as_any().downcast_ref::<EnumDef>() {
+        Some((&def.name, &def.methods))
+    } else {
+        None
+    }
+}
+
+/// `object.name` for a method: bound to `object` for a value of a struct or
+/// enum, the plain function when `object` is the type itself.
+pub fn method(object: &Rc<dyn Object>, name: &str) -> Option<Rc<dyn Object>> {
+    let def: &dyn Object = if let Some(value) = object.as_any().downcast_ref::<Struct>() {
+        value.def()
+    } else if let Some(value) = object.as_any().downcast_ref::<Variant>() {
+        value.def()
+    } else {
+        let function = methods(object.as_ref())?.1.borrow().get(name).cloned();
+        return function;
+    };
+    let function = Rc::clone(methods(def)?.1.borrow().get(name)?);
+    Some(Rc::new(BoundMethod::new(
+        name.to_string(),
+        Rc::clone(object),
+        function,
+    )))
+}
+
+/// Adds the methods of an `impl` block to the struct or enum `object` is,
+/// none of them when one is already declared.
+pub fn implement(
+    object: &Rc<dyn Object>,
+    functions: Vec<(String, Rc<dyn Object>)>,
+) -> Result<(), String> {
+    let (type_name, methods) = match methods(object.as_ref()) {
+        Some(methods) => methods,
+        None => {
+            return Err(format!(
+                "impl needs a struct or enum, got {}",
+                object.get_type()
+            ))
+        }
+    };
+    let mut methods = methods.borrow_mut();
+    for (idx, (name, _)) in functions.iter().enumerate() {
+        if methods.contains_key(name) || functions[..idx].iter().any(|(other, _)| other == name) {
+            return Err(format!(
+                "method '{}' of {} is declared twice",
+                name, type_name
+            ));
+        }
+    }
+    methods.extend(functions);
+    Ok(())
+}
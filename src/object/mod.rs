@@ -1,20 +1,55 @@
-use std::{any::Any, fmt::Display};
+use std::{any::Any, cell::RefCell, fmt::Display};
 
 use crate::ast::types::Type;
 
+use self::hash::HashKey;
+
 pub mod array;
+pub mod bigint;
 pub mod boolean;
 pub mod built_in;
+pub mod cell;
+pub mod closure;
+pub mod compiled_function;
+pub mod enumeration;
 pub mod environment;
 pub mod error;
+pub mod float;
 pub mod function;
+pub mod hash;
 pub mod integer;
+pub mod loop_control;
+pub mod method;
+pub mod module;
 pub mod null;
+pub mod pattern;
 pub mod ret;
 pub mod string;
+pub mod structure;
 
-pub trait Object: Display {
+pub trait Object: Display + Any {
     fn get_type(&self) -> Type;
     fn inspect(&self) -> String;
     fn as_any(&self) -> &dyn Any;
+    fn hash_key(&self) -> Option<HashKey> {
+        None
+    }
+}
+
+thread_local! {
+    // the arrays, maps and structs being printed, innermost last
+    static PRINTING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+}
+
+/// Prints the contents of a container with `print`, or gives `None` when the
+/// container is already being printed because it holds itself.
+pub(crate) fn print_once<T>(container: &T, print: impl FnOnce() -> String) -> Option<String> {
+    let addr = container as *const T as usize;
+    if PRINTING.with(|printing| printing.borrow().contains(&addr)) {
+        return None;
+    }
+    PRINTING.with(|printing| printing.borrow_mut().push(addr));
+    let printed = print();
+    PRINTING.with(|printing| printing.borrow_mut().pop());
+    Some(printed)
 }
@@ -0,0 +1,43 @@
+use std::{any::Any, cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{cell::Cell, environment::Environment, Object};
+
+/// The namespace an `import` binds: the top level bindings of the imported file.
+pub struct Module {
+    pub name: String,
+    pub env: Rc<RefCell<Environment>>,
+}
+
+impl Module {
+    pub fn new(name: String, env: Rc<RefCell<Environment>>) -> Self {
+        Self { name, env }
+    }
+
+    /// The current value of the top level binding `member`, if the module has one.
+    pub fn member(&self, member: &str) -> Option<Rc<dyn Object>> {
+        let slot = self.env.borrow().store.get(member).cloned()?;
+        Some(Cell::get(&slot))
+    }
+}
+
+impl Object for Module {
+    fn get_type(&self) -> Type {
+        Type::Module
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
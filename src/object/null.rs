@@ -12,7 +12,7 @@ impl Object for Null {
     }
 
     fn inspect(&self) -> String {
-        todo!()
+        self.to_string()
     }
 
     fn as_any(&self) -> &dyn Any {
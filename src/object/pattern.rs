@@ -0,0 +1,37 @@
+use std::{any::Any, fmt::Display, rc::Rc};
+
+use crate::ast::{pattern::Pattern, types::Type};
+
+use super::Object;
+
+/// The pattern of a `match` arm, kept as a constant for the VM to test
+/// values against.
+pub struct MatchPattern {
+    pub pattern: Rc<Pattern>,
+}
+
+impl MatchPattern {
+    pub fn new(pattern: Rc<Pattern>) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Object for MatchPattern {
+    fn get_type(&self) -> Type {
+        Type::Unknown
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for MatchPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<pattern {}>", self.pattern)
+    }
+}
@@ -1,6 +1,6 @@
 use std::{any::Any, fmt::Display, rc::Rc};
 
-use crate::ast::types::Type;
+use crate::{ast::types::Type, lexer::token::Location};
 
 use super::Object;
 
@@ -33,3 +33,45 @@ impl Display for Ret {
         write!(f, "{}", self.val)
     }
 }
+
+/// `ret f(...)` in a function: the call the function ends on, made by whoever
+/// unwraps the `ret` so calls in tail position don't nest.
+pub struct TailCall {
+    pub function: Rc<dyn Object>,
+    pub args: Vec<Option<Rc<dyn Object>>>,
+    pub location: Option<Location>,
+}
+
+impl TailCall {
+    pub fn new(
+        function: Rc<dyn Object>,
+        args: Vec<Option<Rc<dyn Object>>>,
+        location: Option<Location>,
+    ) -> Self {
+        Self {
+            function,
+            args,
+            location,
+        }
+    }
+}
+
+impl Object for TailCall {
+    fn get_type(&self) -> Type {
+        Type::Function
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for TailCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<tail call {}>", self.function)
+    }
+}
@@ -0,0 +1,172 @@
+use std::{any::Any, cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::ast::types::Type;
+
+use super::{method::Methods, print_once, Object};
+
+/// What a `struct` declaration binds its name to: the fields and their types.
+pub struct StructDef {
+    pub name: Rc<str>,
+    pub fields: Vec<(String, Type)>,
+    pub methods: Methods,
+}
+
+impl StructDef {
+    pub fn new(name: Rc<str>, fields: Vec<(String, Type)>) -> Self {
+        Self {
+            name,
+            fields,
+            methods: Methods::default(),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|(field, _)| field == name)
+    }
+
+    /// Whether `value` has the declared type of the field at `idx`.
+    fn check(&self, idx: usize, value: &Rc<dyn Object>) -> Result<(), String> {
+        let (name, typ) = &self.fields[idx];
+        if value.get_type() != *typ {
+            return Err(format!(
+                "field '{}' of {} expected {}, got {}",
+                name,
+                self.name,
+                typ,
+                value.get_type()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Object for StructDef {
+    fn get_type(&self) -> Type {
+        Type::StructDef
+    }
+
+    fn inspect(&self) -> String {
+        self.to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for StructDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<struct {}>", self.name)
+    }
+}
+
+/// A value of a struct type, with its fields in declaration order. Like
+/// arrays and maps it is shared, not copied, when assigned or passed.
+pub struct Struct {
+    // always a `StructDef`, kept as the object so values share it
+    def: Rc<dyn Object>,
+    pub values: RefCell<Vec<Rc<dyn Object>>>,
+}
+
+impl Struct {
+    pub fn def(&self) -> &StructDef {
+        self.def
+            .as_any()
+            .downcast_ref::<StructDef>()
+            .expect("struct values are built from a StructDef")
+    }
+
+    pub fn get(&self, field: &str) -> Option<Rc<dyn Object>> {
+        let idx = self.def().field(field)?;
+        Some(Rc::clone(&self.values.borrow()[idx]))
+    }
+
+    /// Sets a field, or returns why the value doesn't fit it.
+    pub fn set(&self, field: &str, value: Rc<dyn Object>) -> Result<(), String> {
+        let def = self.def();
+        let idx = match def.field(field) {
+            Some(idx) => idx,
+            None => return Err(format!("{} has no field '{}'", def.name, field)),
+        };
+        def.check(idx, &value)?;
+        self.values.borrow_mut()[idx] = value;
+        Ok(())
+    }
+}
+
+impl Object for Struct {
+    fn get_type(&self) -> Type {
+        Type::Struct(Rc::clone(&self.def().name))
+    }
+
+    fn inspect(&self) -> String {
+        let def = self.def();
+        let fields = print_once(self, || {
+            let fields: Vec<String> = def
+                .fields
+                .iter()
+                .zip(self.values.borrow().iter())
+                .map(|((name, _), value)| format!("{}: {}", name, value))
+                .collect();
+            fields.join(", ")
+        });
+        format!("{} {{ {} }}", def.name, fields.as_deref().unwrap_or("..."))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Display for Struct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+/// Builds a value of the struct `object` names from the fields of a literal,
+/// checking that each field is given once with a value of its declared type.
+pub fn build_struct(
+    object: &Rc<dyn Object>,
+    fields: Vec<(String, Rc<dyn Object>)>,
+) -> Result<Struct, String> {
+    let def = match object.as_any().downcast_ref::<StructDef>() {
+        Some(def) => def,
+        None => return Err(format!("{} is not a struct", object.get_type())),
+    };
+    let mut values: Vec<Option<Rc<dyn Object>>> = vec![None; def.fields.len()];
+    for (name, value) in fields {
+        let idx = match def.field(&name) {
+            Some(idx) => idx,
+            None => return Err(format!("{} has no field '{}'", def.name, name)),
+        };
+        if values[idx].is_some() {
+            return Err(format!("field '{}' of {} is given twice", name, def.name));
+        }
+        def.check(idx, &value)?;
+        values[idx] = Some(value);
+    }
+    let missing: Vec<&str> = def
+        .fields
+        .iter()
+        .zip(values.iter())
+        .filter(|(_, value)| value.is_none())
+        .map(|((name, _), _)| name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "missing field{} {} in {}",
+            if missing.len() == 1 { "" } else { "s" },
+            missing
+                .iter()
+                .map(|name| format!("'{}'", name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            def.name
+        ));
+    }
+    Ok(Struct {
+        def: Rc::clone(object),
+        values: RefCell::new(values.into_iter().flatten().collect()),
+    })
+}
@@ -1,22 +1,23 @@
-use std::{fmt::Display, num::ParseIntError};
+use std::fmt::Display;
+
+use crate::diagnostic::Diagnostic;
 
 pub(super) enum ParseError {
     Eof,
-    Message(String),
+    Error(Diagnostic),
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let message = match self {
-            Self::Eof => "EOF",
-            Self::Message(msg) => msg,
-        };
-        write!(f, "{}", message)
+        match self {
+            Self::Eof => write!(f, "EOF"),
+            Self::Error(diagnostic) => write!(f, "{}", diagnostic),
+        }
     }
 }
 
-impl From<ParseIntError> for ParseError {
-    fn from(e: ParseIntError) -> Self {
-        Self::Message(e.to_string())
+impl From<Diagnostic> for ParseError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self::Error(diagnostic)
     }
 }
@@ -1,30 +1,61 @@
 mod errors;
-mod precedence;
+pub(crate) mod precedence;
+mod string;
 
 use std::{collections::HashMap, mem, rc::Rc};
 
 use crate::{
     ast::{
         expr::{
-            arr_expr::ArrayExpr, bool_expr::BoolExpr, call_expr::CallExpr,
-            function_expr::FunctionExpr, if_expr::IfExpr, index_expr::IndexExpr,
-            infix_expr::InfixExpr, int_expr::IntExpr, prefix_expr::PrefixExpr, str_expr::StrExpr,
+            arr_expr::ArrayExpr,
+            big_int_expr::BigIntExpr,
+            bool_expr::BoolExpr,
+            call_expr::CallExpr,
+            float_expr::FloatExpr,
+            for_expr::ForExpr,
+            function_expr::FunctionExpr,
+            hash_expr::HashExpr,
+            if_expr::IfExpr,
+            index_expr::IndexExpr,
+            infix_expr::InfixExpr,
+            int_expr::IntExpr,
+            interp_expr::InterpExpr,
+            match_expr::{MatchArm, MatchExpr},
+            member_expr::MemberExpr,
+            prefix_expr::PrefixExpr,
+            range_expr::RangeExpr,
+            str_expr::StrExpr,
+            struct_expr::StructExpr,
+            try_expr::TryExpr,
             while_expr::WhileExpr,
         },
         expression::Expression,
         identifier::Identifier,
+        pattern::Pattern,
         program::Program,
         statement::Statement,
         stmt::{
-            block_stmt::BlockStatement, expression_stmt::ExpressionStatement, let_stmt::Let,
-            return_stmt::Return, var_stmt::Var,
+            block_stmt::BlockStatement, break_stmt::Break, continue_stmt::Continue,
+            enum_stmt::EnumStmt, expression_stmt::ExpressionStatement, impl_stmt::ImplStmt,
+            import_stmt::Import, let_stmt::Let, return_stmt::Return, struct_stmt::StructStmt,
+            throw_stmt::Throw, var_stmt::Var,
         },
         types::Type,
     },
-    lexer::{token::Token, Lexer},
+    diagnostic::Diagnostic,
+    lexer::{
+        token::{Location, Token},
+        Lexer,
+    },
+    loader::module_name,
+    object::bigint::BigInt,
 };
 
-use self::{errors::ParseError, precedence::Precedence};
+use self::{
+    errors::ParseError,
+    precedence::Precedence,
+    string::{split, Part},
+};
 
 type PrefixParseFn = fn(&mut Parser) -> Result<Rc<dyn Expression>, ParseError>;
 type InfixParseFn = fn(&mut Parser, Rc<dyn Expression>) -> Result<Rc<dyn Expression>, ParseError>;
@@ -33,15 +64,22 @@ pub struct Parser {
     lexer: Lexer,
     current_token: Rc<Token>,
     peek_token: Rc<Token>,
-    errors: Vec<String>,
+    // `///` comments before the current and the peek token
+    doc: Option<String>,
+    peek_doc: Option<String>,
+    // unclosed `{` before the current token
+    depth: usize,
+    // in the condition of `if`, `while` or `for`, where `{` starts the body
+    no_struct: bool,
+    errors: Vec<Diagnostic>,
     prefix_parse_fns: HashMap<Token, PrefixParseFn>,
     infix_parse_fns: HashMap<Token, InfixParseFn>,
 }
 
 impl Parser {
     pub fn new(mut lexer: Lexer) -> Self {
-        let current_token = lexer.next_token();
-        let peek_token = lexer.next_token();
+        let (current_token, doc) = Self::lex(&mut lexer);
+        let (peek_token, peek_doc) = Self::lex(&mut lexer);
         let mut prefix_parse_fns: HashMap<Token, PrefixParseFn> = HashMap::new();
         let mut infix_parse_fns: HashMap<Token, InfixParseFn> = HashMap::new();
         prefix_parse_fns.insert(Token::Ident(None, None), Self::parse_identifier);
@@ -53,9 +91,14 @@ impl Parser {
         prefix_parse_fns.insert(Token::String(None, None), Self::parse_string_literal);
         prefix_parse_fns.insert(Token::LParen(None), Self::parse_grouped_expression);
         prefix_parse_fns.insert(Token::LSqBracket(None), Self::parse_array);
+        prefix_parse_fns.insert(Token::LBrace(None), Self::parse_hash);
         prefix_parse_fns.insert(Token::If(None), Self::parse_if_expression);
         prefix_parse_fns.insert(Token::While(None), Self::parse_while_expression);
         prefix_parse_fns.insert(Token::Function(None), Self::parse_function_literal);
+        prefix_parse_fns.insert(Token::Try(None), Self::parse_try_expression);
+        prefix_parse_fns.insert(Token::For(None), Self::parse_for_expression);
+        prefix_parse_fns.insert(Token::Match(None), Self::parse_match_expression);
+        prefix_parse_fns.insert(Token::Map(None), Self::parse_map_builtin);
 
         infix_parse_fns.insert(Token::Plus(None), Self::parse_infix_expression);
         infix_parse_fns.insert(Token::Minus(None), Self::parse_infix_expression);
@@ -76,12 +119,18 @@ impl Parser {
         infix_parse_fns.insert(Token::Lte(None), Self::parse_infix_expression);
         infix_parse_fns.insert(Token::And(None), Self::parse_infix_expression);
         infix_parse_fns.insert(Token::Assign(None), Self::parse_infix_expression);
+        infix_parse_fns.insert(Token::DotDot(None), Self::parse_range_expression);
         infix_parse_fns.insert(Token::LParen(None), Self::parse_call_expression);
         infix_parse_fns.insert(Token::LSqBracket(None), Self::parse_index_expression);
+        infix_parse_fns.insert(Token::Dot(None), Self::parse_member_expression);
         Self {
             lexer,
             current_token,
             peek_token,
+            doc,
+            peek_doc,
+            depth: 0,
+            no_struct: false,
             errors: vec![],
             prefix_parse_fns,
             infix_parse_fns,
@@ -91,6 +140,7 @@ impl Parser {
     pub fn parse_program(mut self) -> Box<Program> {
         let mut statements = vec![];
         loop {
+            let depth = self.depth;
             match self.parse_statement() {
                 Ok(sts) => {
                     self.next_token();
@@ -98,9 +148,13 @@ impl Parser {
                 }
                 Err(e) => match e {
                     ParseError::Eof => break,
-                    ParseError::Message(msg) => {
-                        self.next_token();
-                        self.errors.push(msg)
+                    ParseError::Error(diagnostic) => {
+                        self.errors.push(diagnostic);
+                        self.synchronize(depth);
+                        // a stray `}` closes nothing at the top level
+                        if self.current_token_is(Token::Rbrace(None)) {
+                            self.next_token();
+                        }
                     }
                 },
             }
@@ -108,93 +162,280 @@ impl Parser {
         Program::new(statements, self.errors)
     }
 
+    /// Skips the rest of a statement that failed to parse, past its `;` or
+    /// the `}` closing a block it opened, and stops before the `}` closing
+    /// the block around it.
+    fn synchronize(&mut self, depth: usize) {
+        loop {
+            match self.current_token.as_ref() {
+                Token::EOF(_) => return,
+                Token::Rbrace(_) if self.depth <= depth => return,
+                Token::Semicolon(_) if self.depth == depth => {
+                    self.next_token();
+                    return;
+                }
+                Token::Rbrace(_) if self.depth == depth + 1 => {
+                    self.next_token();
+                    if self.current_token_is(Token::Semicolon(None)) {
+                        self.next_token();
+                    }
+                    return;
+                }
+                _ => self.next_token(),
+            }
+        }
+    }
+
     fn parse_statement(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
         match self.current_token.as_ref() {
             Token::Let(_) => self.parse_let_var(true),
             Token::Var(_) => self.parse_let_var(false),
             Token::Return(_) => self.parse_return(),
+            Token::Throw(_) => self.parse_throw(),
+            Token::Import(_) => self.parse_import(),
+            Token::Struct(_) => self.parse_struct(),
+            Token::Enum(_) => self.parse_enum(),
+            Token::Impl(_) => self.parse_impl(),
+            Token::Break(_) => {
+                let mut stmt = Break::new();
+                stmt.location = self.current_token.location();
+                self.skip_semicolon();
+                Ok(Rc::new(stmt))
+            }
+            Token::Continue(_) => {
+                let mut stmt = Continue::new();
+                stmt.location = self.current_token.location();
+                self.skip_semicolon();
+                Ok(Rc::new(stmt))
+            }
             Token::EOF(_) => Err(ParseError::Eof),
             _ => self.parse_expression_statement(),
         }
     }
 
     fn next_token(&mut self) {
+        match self.current_token.as_ref() {
+            Token::LBrace(_) => self.depth += 1,
+            Token::Rbrace(_) => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
         mem::swap(&mut self.current_token, &mut self.peek_token);
-        self.peek_token = self.lexer.next_token();
+        self.doc = self.peek_doc.take();
+        (self.peek_token, self.peek_doc) = Self::lex(&mut self.lexer);
+    }
+
+    /// The next token that isn't a doc comment, with the text of the doc
+    /// comments before it.
+    fn lex(lexer: &mut Lexer) -> (Rc<Token>, Option<String>) {
+        let mut doc: Vec<String> = vec![];
+        loop {
+            let token = lexer.next_token();
+            if let Token::DocComment(Some(text), _) = token.as_ref() {
+                let line = text.trim_start_matches('/');
+                doc.push(line.strip_prefix(' ').unwrap_or(line).to_string());
+                continue;
+            }
+            return (token, (!doc.is_empty()).then(|| doc.join("\n")));
+        }
     }
 
     fn parse_let_var(&mut self, is_let: bool) -> Result<Rc<dyn Statement>, ParseError> {
-        let current_token = Rc::clone(&self.current_token);
+        let doc = self.doc.take();
         self.expected_peek(Token::Ident(None, None))?;
-        let identifier: Rc<dyn Expression> = Self::parse_identifier(self)?;
-        let expression: Rc<dyn Expression>;
-        if self.has_type() {
-            self.next_token();
-            let declared_type = Some(self.current_token.to_type());
-            self.expected_peek(Token::Assign(None))?;
-            self.next_token();
-            expression = if let Ok(expr) = self.parse_expression(Precedence::Lowest) {
-                expr
-            } else {
-                let msg = format!("error on parse expression {}", current_token);
-                return Err(ParseError::Message(msg));
-            };
-            if declared_type.unwrap() != expression.get_type() {
-                let msg = format!(
-                    "expected {}, got {}",
-                    declared_type.unwrap(),
-                    expression.get_type()
-                );
-                return Err(ParseError::Message(msg));
+        let identifier = self.create_identifier(true)?;
+        let declared_type = identifier
+            .as_any()
+            .downcast_ref::<Identifier>()
+            .and_then(|identifier| identifier.typ.clone());
+        if let Err(mut e) = self.expected_peek(Token::Assign(None)) {
+            if let ParseError::Error(diagnostic) = &mut e {
+                diagnostic.hint = Some(format!("give '{}' a value with '='", identifier));
             }
-        } else {
-            self.expected_peek(Token::Assign(None))?;
-            self.next_token();
-            expression = match self.parse_expression(Precedence::Lowest) {
-                Ok(expr) => expr,
-                Err(e) => {
-                    let error_msg = if let ParseError::Message(msg) = e {
-                        msg
-                    } else {
-                        "".to_string()
-                    };
-                    let msg = format!(
-                        "error on parse expression {}... {}",
-                        current_token, error_msg
-                    );
-                    return Err(ParseError::Message(msg));
-                }
+            return Err(e);
+        }
+        self.next_token();
+        let expression = self.parse_expression(Precedence::Lowest)?;
+        if let Some(declared_type) = declared_type {
+            if Self::is_concrete(expression.get_type()) && declared_type != expression.get_type() {
+                let msg = format!("expected {}, got {}", declared_type, expression.get_type());
+                return Err(Diagnostic::error(msg, expression.location()).into());
             }
         }
         if self.peek_token_is(&Token::Semicolon(None)) {
             self.next_token();
         }
+        let typ = expression.get_type();
         if is_let {
-            Ok(Let::new(expression.get_type(), identifier, expression))
+            Ok(Let::documented(typ, identifier, expression, doc))
         } else {
-            Ok(Var::new(expression.get_type(), identifier, expression))
+            Ok(Var::documented(typ, identifier, expression, doc))
         }
     }
 
     fn parse_return(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
         let mut ret = Return::new(None);
+        ret.location = self.current_token.location();
         self.next_token();
         ret.return_value = Some(self.parse_expression(Precedence::Lowest)?);
-        self.next_token();
+        self.skip_semicolon();
         Ok(Rc::new(ret))
     }
 
+    fn parse_throw(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
+        let location = self.current_token.location();
+        self.next_token();
+        let mut throw = Throw::new(self.parse_expression(Precedence::Lowest)?);
+        throw.location = location;
+        self.skip_semicolon();
+        Ok(Rc::new(throw))
+    }
+
+    fn parse_import(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
+        let location = self.current_token.location();
+        self.next_token();
+        let path = match self.current_token.as_ref() {
+            Token::String(Some(path), _) => path.to_string(),
+            Token::Ident(Some(name), _) => format!("{}.bzr", name),
+            tok => return Err(Self::unexpected("a module path", tok).into()),
+        };
+        let mut name_token = Rc::clone(&self.current_token);
+        if self.peek_token_is(&Token::As(None)) {
+            self.next_token();
+            self.expected_peek(Token::Ident(None, None))?;
+            name_token = Rc::clone(&self.current_token);
+        }
+        let name = match name_token.as_ref() {
+            Token::Ident(Some(name), _) => Rc::clone(name),
+            _ => Rc::new(module_name(&path)),
+        };
+        let mut identifier = Identifier::new(name);
+        identifier.location = name_token.location();
+        let mut import = Import::new(path, Rc::new(identifier));
+        import.location = location;
+        self.skip_semicolon();
+        Ok(Rc::new(import))
+    }
+
+    fn parse_struct(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
+        let location = self.current_token.location();
+        let doc = self.doc.take();
+        self.expected_peek(Token::Ident(None, None))?;
+        let name = self.create_identifier(false)?;
+        self.expected_peek(Token::LBrace(None))?;
+        let mut fields = vec![];
+        while !self.peek_token_is(&Token::Rbrace(None)) {
+            self.expected_peek(Token::Ident(None, None))?;
+            if !self.has_declared_type() {
+                let mut diagnostic = Self::unexpected("a type", &self.peek_token);
+                diagnostic.hint = Some("write struct fields as 'name type'".to_string());
+                return Err(diagnostic.into());
+            }
+            fields.push(self.create_identifier(true)?);
+            if !self.peek_token_is(&Token::Rbrace(None)) {
+                self.expected_peek(Token::Comma(None))?;
+            }
+        }
+        self.next_token();
+        let mut stmt = StructStmt::new(name, fields);
+        stmt.location = location;
+        stmt.end = self.current_token.location();
+        stmt.doc = doc;
+        self.skip_semicolon();
+        Ok(Rc::new(stmt))
+    }
+
+    fn parse_enum(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
+        let location = self.current_token.location();
+        let doc = self.doc.take();
+        self.expected_peek(Token::Ident(None, None))?;
+        let name = self.create_identifier(false)?;
+        self.expected_peek(Token::LBrace(None))?;
+        let mut variants = vec![];
+        while !self.peek_token_is(&Token::Rbrace(None)) {
+            self.expected_peek(Token::Ident(None, None))?;
+            let variant = self.create_identifier(false)?;
+            let mut types = vec![];
+            if self.peek_token_is(&Token::LParen(None)) {
+                self.next_token();
+                while !self.peek_token_is(&Token::RParen(None)) {
+                    if !self.has_declared_type() {
+                        let mut diagnostic = Self::unexpected("a type", &self.peek_token);
+                        diagnostic.hint = Some(
+                            "write the values of a variant as types, 'Circle(int)'".to_string(),
+                        );
+                        return Err(diagnostic.into());
+                    }
+                    self.next_token();
+                    types.push(Self::declared_type(&self.current_token));
+                    if !self.peek_token_is(&Token::RParen(None)) {
+                        self.expected_peek(Token::Comma(None))?;
+                    }
+                }
+                self.next_token();
+            }
+            variants.push((variant, types));
+            if !self.peek_token_is(&Token::Rbrace(None)) {
+                self.expected_peek(Token::Comma(None))?;
+            }
+        }
+        self.next_token();
+        let mut stmt = EnumStmt::new(name, variants);
+        stmt.location = location;
+        stmt.end = self.current_token.location();
+        stmt.doc = doc;
+        self.skip_semicolon();
+        Ok(Rc::new(stmt))
+    }
+
+    fn parse_impl(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
+        let location = self.current_token.location();
+        let doc = self.doc.take();
+        self.expected_peek(Token::Ident(None, None))?;
+        let name = self.create_identifier(false)?;
+        self.expected_peek(Token::LBrace(None))?;
+        let mut methods = vec![];
+        while !self.peek_token_is(&Token::Rbrace(None)) {
+            if !self.peek_token_is(&Token::Function(None)) {
+                let mut diagnostic = Self::unexpected("a method", &self.peek_token);
+                diagnostic.hint = Some("write methods as 'fn name(self) { ... }'".to_string());
+                return Err(diagnostic.into());
+            }
+            self.next_token();
+            let method = self.parse_function()?;
+            if method.name.is_none() {
+                let msg = format!("a method of {} needs a name", name);
+                return Err(Diagnostic::error(msg, method.location.clone()).into());
+            }
+            methods.push(Rc::new(method));
+            self.skip_semicolon();
+        }
+        self.next_token();
+        let mut stmt = ImplStmt::new(name, methods);
+        stmt.location = location;
+        stmt.end = self.current_token.location();
+        stmt.doc = doc;
+        self.skip_semicolon();
+        Ok(Rc::new(stmt))
+    }
+
+    fn skip_semicolon(&mut self) {
+        if self.peek_token_is(&Token::Semicolon(None)) {
+            self.next_token();
+        }
+    }
+
     fn parse_expression_statement(&mut self) -> Result<Rc<dyn Statement>, ParseError> {
         let mut stmt = ExpressionStatement::new(Type::Unknown);
         stmt.expression = match self.parse_expression(Precedence::Lowest) {
             Ok(expr) => Some(expr),
             Err(e) => match e {
-                ParseError::Message(_) => return Err(e),
+                ParseError::Error(_) => return Err(e),
                 _ => None,
             },
         };
         if self.peek_token_is(&Token::Semicolon(None)) {
             self.next_token();
+            stmt.semicolon = true;
         }
 
         Ok(Rc::new(stmt))
@@ -208,10 +449,7 @@ impl Parser {
         let prefix = self.prefix_parse_fns.get(token);
         let mut left_expr = match prefix {
             Some(prefix_fn) => prefix_fn(self)?,
-            None => {
-                let msg = format!("error expected a prefix, got {}", self.current_token);
-                return Err(ParseError::Message(msg));
-            }
+            None => return Err(Self::no_expression(&self.current_token).into()),
         };
 
         while !self.current_token_is(Token::Semicolon(None))
@@ -229,37 +467,95 @@ impl Parser {
 
     fn parse_identifier(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
         let identifier_expr = parser.create_identifier(false)?;
+        if !parser.no_struct && parser.peek_token_is(&Token::LBrace(None)) {
+            return parser.parse_struct_literal(identifier_expr);
+        }
         Ok(Rc::clone(&identifier_expr))
     }
 
+    /// `Name { field: value, ... }`, the current token is the name.
+    fn parse_struct_literal(
+        &mut self,
+        name: Rc<dyn Expression>,
+    ) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = name.location();
+        self.next_token();
+        let mut fields = vec![];
+        while !self.peek_token_is(&Token::Rbrace(None)) {
+            self.expected_peek(Token::Ident(None, None))?;
+            let field = self.create_identifier(false)?;
+            if !self.peek_token_is(&Token::Colon(None)) {
+                let mut diagnostic = Self::unexpected("':'", &self.peek_token);
+                diagnostic.hint = Some("write struct fields as 'name: value'".to_string());
+                return Err(diagnostic.into());
+            }
+            self.next_token();
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+            fields.push((field, value));
+            if !self.peek_token_is(&Token::Rbrace(None)) {
+                self.expected_peek(Token::Comma(None))?;
+            }
+        }
+        self.next_token();
+        let mut struct_expr = StructExpr::new(name, fields);
+        struct_expr.location = location;
+        Ok(Rc::new(struct_expr))
+    }
+
+    /// `map` names the map type, but called it's the `map` builtin.
+    fn parse_map_builtin(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        if !parser.peek_token_is(&Token::LParen(None)) {
+            return Err(Self::no_expression(&parser.current_token).into());
+        }
+        let mut identifier_expr = Identifier::new(Rc::new("map".to_string()));
+        identifier_expr.location = parser.current_token.location();
+        Ok(Rc::new(identifier_expr))
+    }
+
     fn create_identifier(&mut self, skip_type: bool) -> Result<Rc<dyn Expression>, ParseError> {
         let identifier_value = match self.current_token.as_ref() {
             Token::Ident(Some(ident), _) => Rc::clone(ident),
-            tok => {
-                let msg = format!("expected identifier, got {}", tok);
-                return Err(ParseError::Message(msg));
-            }
+            tok => return Err(Self::unexpected("a name", tok).into()),
         };
         let mut identifier_expr = Identifier::new(identifier_value);
-        if self.has_type() {
-            let typ = self.peek_token.to_type();
-            if skip_type {
-                self.next_token();
-            }
-            identifier_expr.set_type(typ);
+        identifier_expr.location = self.current_token.location();
+        if skip_type && self.has_declared_type() {
+            self.next_token();
+            identifier_expr.set_type(Self::declared_type(&self.current_token));
+        } else if self.has_type() {
+            identifier_expr.set_type(self.peek_token.to_type());
         }
         Ok(Rc::new(identifier_expr))
     }
 
     fn parse_number_literal(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
         let number = match parser.current_token.as_ref() {
-            Token::Number(Some(val), _) => val.trim().parse()?,
-            _ => {
-                let msg = format!("fail on parse value: {}", parser.current_token);
-                return Err(ParseError::Message(msg));
+            Token::Number(Some(val), _) => val.trim(),
+            tok => return Err(Self::unexpected("a number", tok).into()),
+        };
+        let location = parser.current_token.location();
+        let invalid = |e: String| {
+            let msg = format!("invalid number '{}': {}", number, e);
+            ParseError::from(Diagnostic::error(msg, location.clone()))
+        };
+        if number.contains(['.', 'e', 'E']) {
+            let value = number.parse::<f64>().map_err(|e| invalid(e.to_string()))?;
+            let mut float_expr = FloatExpr::new(value);
+            float_expr.location = location;
+            return Ok(Rc::new(float_expr));
+        }
+        let value = match number.parse::<i64>() {
+            Ok(value) => value,
+            Err(e) => {
+                let value = BigInt::parse(number).ok_or_else(|| invalid(e.to_string()))?;
+                let mut big_int_expr = BigIntExpr::new(value);
+                big_int_expr.location = location;
+                return Ok(Rc::new(big_int_expr));
             }
         };
-        let int_expr = IntExpr::new(number);
+        let mut int_expr = IntExpr::new(value);
+        int_expr.location = location;
         Ok(Rc::new(int_expr))
     }
 
@@ -267,33 +563,40 @@ impl Parser {
         let boolean = match parser.current_token.as_ref() {
             Token::True(_) => true,
             Token::False(_) => false,
-            tok => {
-                let msg = format!("expected boolean value got: {}", tok);
-                return Err(ParseError::Message(msg));
-            }
+            tok => return Err(Self::unexpected("a boolean", tok).into()),
         };
-        let bool_expr = BoolExpr::new(boolean);
+        let mut bool_expr = BoolExpr::new(boolean);
+        bool_expr.location = parser.current_token.location();
         Ok(Rc::new(bool_expr))
     }
 
     fn parse_function_literal(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
-        parser.next_token();
-        let identifier = Self::parse_identifier(parser)?;
+        Ok(Rc::new(parser.parse_function()?))
+    }
+
+    fn parse_function(&mut self) -> Result<FunctionExpr, ParseError> {
+        let location = self.current_token.location();
+        let doc = self.doc.take();
+        let identifier = if self.peek_token_is(&Token::LParen(None)) {
+            None
+        } else {
+            self.next_token();
+            Some(Self::parse_identifier(self)?)
+        };
         let mut function_expr = FunctionExpr::new(identifier);
-        parser.expected_peek(Token::LParen(None))?;
-        function_expr.parameters = parser.parse_function_parameters()?;
-        if parser.has_type() {
-            parser.next_token();
-            function_expr.ret_typ = match parser.current_token.as_ref() {
-                Token::Bool(_) => Type::Bool,
-                Token::Int(_) => Type::Int,
-                Token::Str(_) => Type::String,
-                _ => Type::Unknown,
-            };
+        function_expr.location = location;
+        if function_expr.name.is_some() {
+            function_expr.doc = doc;
         }
-        parser.expected_peek(Token::LBrace(None))?;
-        function_expr.body = parser.parse_block_statement();
-        Ok(Rc::new(function_expr))
+        self.expected_peek(Token::LParen(None))?;
+        function_expr.parameters = self.parse_function_parameters()?;
+        if self.has_declared_type() {
+            self.next_token();
+            function_expr.ret_typ = Self::declared_type(&self.current_token);
+        }
+        self.expected_peek(Token::LBrace(None))?;
+        function_expr.body = self.parse_block_statement();
+        Ok(function_expr)
     }
 
     fn parse_function_parameters(&mut self) -> Result<Vec<Rc<dyn Expression>>, ParseError> {
@@ -322,12 +625,13 @@ impl Parser {
     fn parse_prefix_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
         let mut prefix_expr = PrefixExpr::new(parser.current_token.literal());
         prefix_expr.set_type(parser.current_token.to_type());
+        prefix_expr.location = parser.current_token.location();
         parser.next_token();
 
         prefix_expr.right = match parser.parse_expression(Precedence::Prefix) {
             Ok(expr) => Some(expr),
             Err(e) => match e {
-                ParseError::Message(_) => return Err(e),
+                ParseError::Error(_) => return Err(e),
                 _ => None,
             },
         };
@@ -336,27 +640,73 @@ impl Parser {
     }
 
     fn parse_string_literal(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
-        let string = match parser.current_token.as_ref() {
-            Token::String(val, _) => val.as_ref().unwrap().as_ref(),
-            tok => {
-                let msg = format!("expected boolean value got: {}", tok);
-                return Err(ParseError::Message(msg));
+        let (literal, location) = match parser.current_token.as_ref() {
+            Token::String(Some(val), location) => (Rc::clone(val), location.clone()),
+            tok => return Err(Self::unexpected("a string", tok).into()),
+        };
+        let in_string = |msg: String| ParseError::from(Diagnostic::error(msg, location.clone()));
+        let mut parts: Vec<Rc<dyn Expression>> = vec![];
+        for part in split(&literal).map_err(in_string)? {
+            match part {
+                Part::Text(text) => {
+                    let mut string_expr = StrExpr::new(text);
+                    string_expr.location = location.clone();
+                    parts.push(Rc::new(string_expr));
+                }
+                Part::Code(code, start) => {
+                    let start = location
+                        .as_ref()
+                        .map(|location| code_location(location, &literal, start));
+                    parts.push(Self::parse_embedded(code, start)?);
+                }
             }
+        }
+        if parts.len() == 1 && parts[0].as_any().is::<StrExpr>() {
+            return Ok(parts.remove(0));
+        }
+        let mut interp_expr = InterpExpr::new(parts);
+        interp_expr.location = location;
+        Ok(Rc::new(interp_expr))
+    }
+
+    /// Parses the code of a `${}` in a string, a single expression.
+    fn parse_embedded(
+        code: String,
+        location: Option<Location>,
+    ) -> Result<Rc<dyn Expression>, ParseError> {
+        let lexer = match &location {
+            Some(location) => Lexer::at(Rc::new(code), location),
+            None => Lexer::new(Rc::new(code), Rc::new(String::new())),
         };
-        let string_expr = StrExpr::new(string.to_string());
-        Ok(Rc::new(string_expr))
+        let mut program = Parser::new(lexer).parse_program();
+        if !program.errors.is_empty() {
+            return Err(program.errors.remove(0).into());
+        }
+        let expression = match program.statements.as_slice() {
+            [stmt] => stmt
+                .as_any()
+                .downcast_ref::<ExpressionStatement>()
+                .and_then(|stmt| stmt.expression.clone()),
+            _ => None,
+        };
+        expression.ok_or_else(|| {
+            let msg = "expected one expression in '${}'".to_string();
+            Diagnostic::error(msg, location).into()
+        })
     }
 
     fn parse_if_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
         parser.next_token();
 
-        let expr = parser.parse_expression(Precedence::Lowest)?;
+        let expr = parser.parse_condition()?;
 
-        parser.expected_peek(Token::LBrace(None))?;
+        parser.expected_body("if")?;
 
         let consequence_block = parser.parse_block_statement();
 
         let mut if_expr = IfExpr::new(expr);
+        if_expr.location = location;
         if_expr.consequence = consequence_block;
 
         if parser.peek_token_is(&Token::Else(None)) {
@@ -368,7 +718,10 @@ impl Parser {
                 }
                 Err(_) => match parser.expected_peek(Token::If(None)) {
                     Ok(_) => if_expr.el_if = Some(Self::parse_if_expression(parser)?),
-                    Err(e) => return Err(e),
+                    Err(_) => {
+                        let tok = &parser.peek_token;
+                        return Err(Self::unexpected("'{' or 'if' after 'else'", tok).into());
+                    }
                 },
             }
         }
@@ -377,27 +730,310 @@ impl Parser {
     }
 
     fn parse_while_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
         parser.next_token();
-        let expr = parser.parse_expression(Precedence::Lowest)?;
-        parser.expected_peek(Token::LBrace(None))?;
+        let expr = parser.parse_condition()?;
+        parser.expected_body("while")?;
         let consequence_block = parser.parse_block_statement();
         let mut while_expr = WhileExpr::new(expr);
+        while_expr.location = location;
         while_expr.consequence = consequence_block;
         Ok(Rc::new(while_expr))
     }
 
+    fn parse_for_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
+        parser.expected_peek(Token::Ident(None, None))?;
+        let variable = Self::parse_identifier(parser)?;
+        parser.expected_peek(Token::In(None))?;
+        parser.next_token();
+        let iterable = parser.parse_condition()?;
+        parser.expected_body("for")?;
+        let mut for_expr = ForExpr::new(variable, iterable);
+        for_expr.location = location;
+        for_expr.body = parser.parse_block_statement();
+        Ok(Rc::new(for_expr))
+    }
+
+    fn parse_match_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
+        parser.next_token();
+        let value = parser.parse_condition()?;
+        parser.expected_body("match")?;
+        let no_struct = mem::take(&mut parser.no_struct);
+        let arms = parser.parse_match_arms();
+        parser.no_struct = no_struct;
+        let mut match_expr = MatchExpr::new(value, arms?);
+        match_expr.location = location;
+        match_expr.end = parser.current_token.location();
+        Ok(Rc::new(match_expr))
+    }
+
+    /// The arms of a `match` up to its closing brace, the current token is
+    /// the opening one.
+    fn parse_match_arms(&mut self) -> Result<Vec<MatchArm>, ParseError> {
+        let mut arms = vec![];
+        while !self.peek_token_is(&Token::Rbrace(None)) {
+            self.next_token();
+            let pattern = self.parse_pattern()?;
+            let mut guard = None;
+            if self.peek_token_is(&Token::If(None)) {
+                self.next_token();
+                self.next_token();
+                guard = Some(self.parse_expression(Precedence::Lowest)?);
+            }
+            if let Err(mut e) = self.expected_peek(Token::FatArrow(None)) {
+                if let ParseError::Error(diagnostic) = &mut e {
+                    diagnostic.hint = Some("write match arms as 'pattern => value'".to_string());
+                }
+                return Err(e);
+            }
+            self.next_token();
+            let block = self.current_token_is(Token::LBrace(None));
+            let body: Rc<dyn Statement> = if block {
+                self.parse_block_statement()
+                    .unwrap_or_else(|| Rc::new(BlockStatement::new()))
+            } else {
+                let mut stmt = ExpressionStatement::new(Type::Unknown);
+                stmt.expression = Some(self.parse_expression(Precedence::Lowest)?);
+                Rc::new(stmt)
+            };
+            let mut arm = MatchArm::new(pattern, body);
+            arm.guard = guard;
+            arms.push(arm);
+            if self.peek_token_is(&Token::Comma(None)) {
+                self.next_token();
+            } else if !block && !self.peek_token_is(&Token::Rbrace(None)) {
+                let mut diagnostic = Self::unexpected("',' or '}'", &self.peek_token);
+                diagnostic.hint = Some("separate the arms with ','".to_string());
+                return Err(diagnostic.into());
+            }
+        }
+        self.next_token();
+        Ok(arms)
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        match self.current_token.as_ref() {
+            Token::Ident(Some(name), location) if name.as_str() == "_" => {
+                Ok(Pattern::Wildcard(location.clone()))
+            }
+            Token::Ident(..) => {
+                let name = self.create_identifier(false)?;
+                if !self.peek_token_is(&Token::Dot(None)) {
+                    return Ok(Pattern::Binding(name));
+                }
+                self.next_token();
+                self.expected_peek(Token::Ident(None, None))?;
+                let variant = self.create_identifier(false)?;
+                let mut values = vec![];
+                if self.peek_token_is(&Token::LParen(None)) {
+                    self.next_token();
+                    values = self.parse_patterns(Token::RParen(None))?;
+                }
+                Ok(Pattern::Variant {
+                    name,
+                    variant,
+                    values,
+                })
+            }
+            Token::LSqBracket(_) => self.parse_array_pattern(),
+            Token::Number(..)
+            | Token::Minus(_)
+            | Token::String(..)
+            | Token::True(_)
+            | Token::False(_) => {
+                let literal = self.parse_literal_pattern()?;
+                if !self.peek_token_is(&Token::DotDot(None)) {
+                    return Ok(Pattern::Literal(literal));
+                }
+                self.next_token();
+                self.next_token();
+                let end = self.parse_literal_pattern()?;
+                for bound in [&literal, &end] {
+                    if bound.as_any().is::<BigIntExpr>() {
+                        let msg = format!("range pattern bound {} is out of range", bound);
+                        return Err(Diagnostic::error(msg, bound.location()).into());
+                    }
+                    if !bound.as_any().is::<IntExpr>() {
+                        let msg = "range patterns take int bounds".to_string();
+                        return Err(Diagnostic::error(msg, bound.location()).into());
+                    }
+                }
+                Ok(Pattern::Range(literal, end))
+            }
+            tok => Err(Self::unexpected("a pattern", tok).into()),
+        }
+    }
+
+    fn parse_literal_pattern(&mut self) -> Result<Rc<dyn Expression>, ParseError> {
+        let literal = match self.current_token.as_ref() {
+            Token::Number(..) => Self::parse_number_literal(self)?,
+            Token::Minus(location) if matches!(self.peek_token.as_ref(), Token::Number(..)) => {
+                let location = location.clone();
+                self.next_token();
+                let number = Self::parse_number_literal(self)?;
+                Self::negated(number, location)
+            }
+            Token::String(..) => Self::parse_string_literal(self)?,
+            Token::True(_) | Token::False(_) => Self::parse_bool_literal(self)?,
+            tok => return Err(Self::unexpected("a literal", tok).into()),
+        };
+        if literal.as_any().is::<InterpExpr>() {
+            let msg = "string patterns can't embed code with '${}'".to_string();
+            return Err(Diagnostic::error(msg, literal.location()).into());
+        }
+        Ok(literal)
+    }
+
+    /// A number literal with the sign of a `-` before it.
+    fn negated(number: Rc<dyn Expression>, location: Option<Location>) -> Rc<dyn Expression> {
+        if let Some(int_expr) = number.as_any().downcast_ref::<IntExpr>() {
+            let mut negated = IntExpr::new(-int_expr.value);
+            negated.location = location;
+            return Rc::new(negated);
+        }
+        if let Some(big_int_expr) = number.as_any().downcast_ref::<BigIntExpr>() {
+            let value = big_int_expr.value.neg();
+            if let Some(value) = value.to_i64() {
+                let mut negated = IntExpr::new(value);
+                negated.location = location;
+                return Rc::new(negated);
+            }
+            let mut negated = BigIntExpr::new(value);
+            negated.location = location;
+            return Rc::new(negated);
+        }
+        let float_expr = number
+            .as_any()
+            .downcast_ref::<FloatExpr>()
+            .expect("number literals are ints or floats");
+        let mut negated = FloatExpr::new(-float_expr.value);
+        negated.location = location;
+        Rc::new(negated)
+    }
+
+    /// `[a, b]`, `[a, ..]` or `[a, ..rest]`, the current token is the `[`.
+    fn parse_array_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let location = self.current_token.location();
+        let open = Rc::clone(&self.current_token);
+        let mut items = vec![];
+        let mut rest = None;
+        while !self.peek_token_is(&Token::RSqBracket(None)) {
+            self.next_token();
+            if let Token::DotDot(location) = self.current_token.as_ref() {
+                let location = location.clone();
+                rest = Some(Box::new(if self.peek_token_is(&Token::Ident(None, None)) {
+                    self.next_token();
+                    self.parse_pattern()?
+                } else {
+                    Pattern::Wildcard(location)
+                }));
+                if !self.peek_token_is(&Token::RSqBracket(None)) {
+                    let mut diagnostic = Self::unexpected("']'", &self.peek_token);
+                    diagnostic.hint = Some("'..' goes last in an array pattern".to_string());
+                    return Err(diagnostic.into());
+                }
+                break;
+            }
+            items.push(self.parse_pattern()?);
+            if !self.peek_token_is(&Token::RSqBracket(None)) {
+                self.expected_peek(Token::Comma(None))?;
+            }
+        }
+        self.expected_close(Token::RSqBracket(None), open)?;
+        Ok(Pattern::Array {
+            items,
+            rest,
+            location,
+        })
+    }
+
+    /// Patterns separated by commas up to `end`, the current token opens them.
+    fn parse_patterns(&mut self, end: Token) -> Result<Vec<Pattern>, ParseError> {
+        let open = Rc::clone(&self.current_token);
+        let mut patterns = vec![];
+        while !self.peek_token_is(&end) {
+            self.next_token();
+            patterns.push(self.parse_pattern()?);
+            if !self.peek_token_is(&end) {
+                self.expected_peek(Token::Comma(None))?;
+            }
+        }
+        self.expected_close(end, open)?;
+        Ok(patterns)
+    }
+
+    /// An expression followed by a body in braces, so `x {` isn't read as a
+    /// struct literal.
+    fn parse_condition(&mut self) -> Result<Rc<dyn Expression>, ParseError> {
+        let no_struct = mem::replace(&mut self.no_struct, true);
+        let expr = self.parse_expression(Precedence::Lowest);
+        self.no_struct = no_struct;
+        expr
+    }
+
+    fn parse_range_expression(
+        parser: &mut Self,
+        start: Rc<dyn Expression>,
+    ) -> Result<Rc<dyn Expression>, ParseError> {
+        parser.next_token();
+        let location = parser.current_token.location();
+        let precedence = precedence::get_precedence(parser.current_token.as_ref());
+        parser.next_token();
+        let end = parser.parse_expression(precedence)?;
+        let mut range_expr = RangeExpr::new(start, end);
+        range_expr.location = location;
+        Ok(Rc::new(range_expr))
+    }
+
+    fn parse_try_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
+        parser.expected_peek(Token::LBrace(None))?;
+        let body = parser.parse_block_statement();
+        parser.expected_peek(Token::Catch(None))?;
+        parser.expected_peek(Token::Ident(None, None))?;
+        let name = parser.create_identifier(false)?;
+        parser.expected_peek(Token::LBrace(None))?;
+        let mut try_expr = TryExpr::new(name);
+        try_expr.location = location;
+        try_expr.body = body;
+        try_expr.handler = parser.parse_block_statement();
+        Ok(Rc::new(try_expr))
+    }
+
     fn parse_block_statement(&mut self) -> Option<Rc<BlockStatement>> {
         let mut block_stmt = BlockStatement::new();
+        block_stmt.location = self.current_token.location();
+        let errors = self.errors.len();
+        let no_struct = mem::take(&mut self.no_struct);
+        self.next_token();
 
         while !self.current_token_is(Token::Rbrace(None))
             && !self.current_token_is(Token::EOF(None))
         {
-            let statement = self.parse_statement();
-            if let Ok(statement) = statement {
-                block_stmt.push_stmt(statement);
+            let depth = self.depth;
+            match self.parse_statement() {
+                Ok(statement) => {
+                    block_stmt.push_stmt(statement);
+                    self.next_token();
+                }
+                Err(ParseError::Error(diagnostic)) => {
+                    self.errors.push(diagnostic);
+                    self.synchronize(depth);
+                }
+                Err(ParseError::Eof) => break,
             }
-            self.next_token();
         }
+        // an error inside the block has likely been reported already
+        if self.current_token_is(Token::EOF(None)) && self.errors.len() == errors {
+            let msg = "unclosed '{'".to_string();
+            let mut diagnostic = Diagnostic::error(msg, block_stmt.location.clone());
+            diagnostic.hint = Some("add a '}' to close the block".to_string());
+            self.errors.push(diagnostic);
+        }
+        block_stmt.end = self.current_token.location();
+        self.no_struct = no_struct;
 
         Some(Rc::new(block_stmt))
     }
@@ -410,6 +1046,7 @@ impl Parser {
         let precedence = precedence::get_precedence(parser.current_token.as_ref());
         let mut infix_expr = InfixExpr::new(parser.current_token.literal());
         infix_expr.left = Some(left);
+        infix_expr.location = parser.current_token.location();
         let typ = parser.current_token.to_type();
         infix_expr.set_type(typ);
         parser.next_token();
@@ -426,45 +1063,113 @@ impl Parser {
         left: Rc<dyn Expression>,
     ) -> Result<Rc<dyn Expression>, ParseError> {
         parser.next_token();
+        let location = parser.current_token.location();
         parser.next_token();
         let idx_expr = parser.parse_expression(Precedence::Lowest)?;
         parser.expected_peek(Token::RSqBracket(None))?;
-        Ok(Rc::new(IndexExpr::new(left, idx_expr)))
+        let mut index_expr = IndexExpr::new(left, idx_expr);
+        index_expr.location = location;
+        Ok(Rc::new(index_expr))
+    }
+
+    fn parse_member_expression(
+        parser: &mut Self,
+        object: Rc<dyn Expression>,
+    ) -> Result<Rc<dyn Expression>, ParseError> {
+        parser.next_token();
+        let location = parser.current_token.location();
+        let member = if parser.peek_token_is(&Token::Map(None)) {
+            // the `map` type keyword names the built-in function after a `.`
+            parser.next_token();
+            let mut identifier = Identifier::new(Rc::new("map".to_string()));
+            identifier.location = parser.current_token.location();
+            Rc::new(identifier)
+        } else {
+            parser.expected_peek(Token::Ident(None, None))?;
+            parser.create_identifier(false)?
+        };
+        let mut member_expr = MemberExpr::new(object, member);
+        member_expr.location = location;
+        Ok(Rc::new(member_expr))
     }
 
     fn parse_call_expression(
         parser: &mut Self,
         function: Rc<dyn Expression>,
     ) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = function.location().or_else(|| parser.peek_token.location());
         let mut call_expr = CallExpr::new(function);
+        call_expr.location = location;
         parser.next_token();
         call_expr.arguments = parser.parse_expr_list(Token::RParen(None))?;
         Ok(Rc::new(call_expr))
     }
 
     fn parse_grouped_expression(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let open = Rc::clone(&parser.current_token);
         parser.next_token();
-
+        let no_struct = mem::take(&mut parser.no_struct);
         let expr = parser.parse_expression(Precedence::Lowest);
+        parser.no_struct = no_struct;
+        let expr = expr?;
 
-        match parser.expected_peek(Token::RParen(None)) {
-            Ok(_) => {}
-            Err(_) => {
-                let msg = format!("expected LParen got, {}", parser.peek_token);
-                return Err(ParseError::Message(msg));
-            }
-        }
+        parser.expected_close(Token::RParen(None), open)?;
 
-        expr
+        Ok(expr)
     }
 
     fn parse_array(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
         let exprs = parser.parse_expr_list(Token::RSqBracket(None))?;
-        let array_expr = ArrayExpr::new(exprs);
+        let mut array_expr = ArrayExpr::new(exprs);
+        array_expr.location = location;
         Ok(Rc::new(array_expr))
     }
 
+    fn parse_hash(parser: &mut Self) -> Result<Rc<dyn Expression>, ParseError> {
+        let location = parser.current_token.location();
+        let mut pairs = Vec::new();
+        while !parser.peek_token_is(&Token::Rbrace(None)) {
+            parser.next_token();
+            let key = parser.parse_expression(Precedence::Lowest)?;
+            if !parser.peek_token_is(&Token::Colon(None)) {
+                let mut diagnostic = Self::unexpected("':'", &parser.peek_token);
+                diagnostic.hint = Some("write map entries as 'key: value'".to_string());
+                return Err(diagnostic.into());
+            }
+            parser.next_token();
+            parser.next_token();
+            let value = parser.parse_expression(Precedence::Lowest)?;
+            pairs.push((key, value));
+            if !parser.peek_token_is(&Token::Rbrace(None)) {
+                if !parser.peek_token_is(&Token::Comma(None)) {
+                    let mut diagnostic = Self::unexpected("',' or '}'", &parser.peek_token);
+                    if parser
+                        .prefix_parse_fns
+                        .contains_key(parser.peek_token.as_ref())
+                    {
+                        diagnostic.hint = Some("separate the entries with ','".to_string());
+                    }
+                    return Err(diagnostic.into());
+                }
+                parser.next_token();
+            }
+        }
+        parser.expected_peek(Token::Rbrace(None))?;
+        let mut hash_expr = HashExpr::new(pairs);
+        hash_expr.location = location;
+        Ok(Rc::new(hash_expr))
+    }
+
     fn parse_expr_list(&mut self, end: Token) -> Result<Vec<Rc<dyn Expression>>, ParseError> {
+        let no_struct = mem::take(&mut self.no_struct);
+        let exprs = self.parse_items(end);
+        self.no_struct = no_struct;
+        exprs
+    }
+
+    fn parse_items(&mut self, end: Token) -> Result<Vec<Rc<dyn Expression>>, ParseError> {
+        let open = Rc::clone(&self.current_token);
         let mut exprs = Vec::new();
         if self.peek_token_is(&end) {
             self.next_token();
@@ -478,7 +1183,7 @@ impl Parser {
             self.next_token();
             exprs.push(self.parse_expression(Precedence::Lowest)?);
         }
-        self.expected_peek(end)?;
+        self.expected_close(end, open)?;
         Ok(exprs)
     }
 
@@ -487,8 +1192,59 @@ impl Parser {
             self.next_token();
             Ok(())
         } else {
-            let msg = format!("expected {}, got {}", token, self.peek_token);
-            Err(ParseError::Message(msg))
+            Err(Self::unexpected(&token.describe(), &self.peek_token).into())
+        }
+    }
+
+    /// Like `expected_peek` for the token closing a list or group `open`
+    /// starts, hinting at a missing `,` when another item follows instead.
+    fn expected_close(&mut self, token: Token, open: Rc<Token>) -> Result<(), ParseError> {
+        if self.peek_token_is(&token) {
+            self.next_token();
+            return Ok(());
+        }
+        let mut diagnostic = Self::unexpected(&token.describe(), &self.peek_token);
+        diagnostic.hint = if self.prefix_parse_fns.contains_key(self.peek_token.as_ref()) {
+            Some("separate the items with ','".to_string())
+        } else {
+            let opened = open.describe();
+            open.location()
+                .map(|location| format!("to close the {} opened at {}", opened, location))
+        };
+        Err(diagnostic.into())
+    }
+
+    /// Expects the `{` starting the body of `keyword`.
+    fn expected_body(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if let Err(mut e) = self.expected_peek(Token::LBrace(None)) {
+            if let ParseError::Error(diagnostic) = &mut e {
+                diagnostic.hint = Some(format!("the body of '{}' goes in braces", keyword));
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// An `expected ..., found ...` error at `found`.
+    fn unexpected(expected: &str, found: &Token) -> Diagnostic {
+        let msg = format!("expected {}, found {}", expected, found.describe());
+        Diagnostic::error(msg, found.location())
+    }
+
+    /// The error for a token that can't start an expression.
+    fn no_expression(token: &Token) -> Diagnostic {
+        match token {
+            Token::Illegal(Some(text), location) => {
+                let msg = if text.starts_with('"') {
+                    "unterminated string".to_string()
+                } else if text.as_str() == "/*" {
+                    "unterminated block comment".to_string()
+                } else {
+                    format!("unexpected {}", token.describe())
+                };
+                Diagnostic::error(msg, location.clone())
+            }
+            _ => Self::unexpected("an expression", token),
         }
     }
 
@@ -502,8 +1258,47 @@ impl Parser {
 
     fn has_type(&mut self) -> bool {
         self.peek_token_is(&Token::Int(None))
+            || self.peek_token_is(&Token::Float(None))
             || self.peek_token_is(&Token::Str(None))
             || self.peek_token_is(&Token::Bool(None))
             || self.peek_token_is(&Token::Array(None))
+            || self.peek_token_is(&Token::Map(None))
+            || self.peek_token_is(&Token::Function(None))
+    }
+
+    /// A type keyword, or the name of a struct or enum type.
+    fn has_declared_type(&mut self) -> bool {
+        self.has_type() || self.peek_token_is(&Token::Ident(None, None))
+    }
+
+    fn declared_type(token: &Token) -> Type {
+        match token {
+            Token::Ident(Some(name), _) => Type::Struct(Rc::from(name.as_str())),
+            tok => tok.to_type(),
+        }
+    }
+
+    fn is_concrete(typ: Type) -> bool {
+        matches!(
+            typ,
+            Type::Int | Type::Float | Type::Bool | Type::String | Type::Array | Type::Map
+        )
+    }
+}
+
+/// Where the code starting at char `start` of a string literal at `location` is.
+fn code_location(location: &Location, literal: &str, start: usize) -> Location {
+    let before: Vec<char> = literal.chars().take(start).collect();
+    match before.iter().rposition(|ch| *ch == '\n') {
+        Some(newline) => {
+            let line = location.line + before.iter().filter(|ch| **ch == '\n').count();
+            Location::new(start - newline, line, Rc::clone(&location.filename))
+        }
+        // past the opening quote
+        None => Location::new(
+            location.position + 1 + start,
+            location.line,
+            Rc::clone(&location.filename),
+        ),
     }
 }
@@ -3,16 +3,17 @@ use std::cmp::Ordering;
 use crate::lexer::token::Token;
 
 #[derive(Clone, Copy, Debug)]
-pub(super) enum Precedence {
+pub(crate) enum Precedence {
     Lowest = 1,
     AndOr = 2,
     Equals = 3,
     LessGreater = 4,
-    Sum = 5,
-    Product = 6,
-    Prefix = 7,
-    Call = 8,
-    Index = 9,
+    Range = 5,
+    Sum = 6,
+    Product = 7,
+    Prefix = 8,
+    Call = 9,
+    Index = 10,
 }
 
 impl PartialEq for Precedence {
@@ -39,6 +40,7 @@ pub(super) fn get_precedence(token: &Token) -> Precedence {
         Token::Lt(_) | Token::Gt(_) | Token::Lte(_) | Token::Gte(_) | Token::Assign(_) => {
             Precedence::LessGreater
         }
+        Token::DotDot(_) => Precedence::Range,
         Token::Plus(_) | Token::Minus(_) => Precedence::Sum,
         Token::Slash(_)
         | Token::Mod(_)
@@ -49,7 +51,20 @@ pub(super) fn get_precedence(token: &Token) -> Precedence {
         | Token::ShiftRight(_)
         | Token::Xor(_) => Precedence::Product,
         Token::LParen(_) => Precedence::Call,
-        Token::LSqBracket(_) => Precedence::Index,
+        Token::LSqBracket(_) | Token::Dot(_) => Precedence::Index,
+        _ => Precedence::Lowest,
+    }
+}
+
+/// The precedence of an infix operator as written in the source, e.g. `"+"`.
+pub(crate) fn operator_precedence(operator: &str) -> Precedence {
+    match operator {
+        "==" | "!=" => Precedence::Equals,
+        "||" | "&&" => Precedence::AndOr,
+        "<" | ">" | "<=" | ">=" | "=" => Precedence::LessGreater,
+        ".." => Precedence::Range,
+        "+" | "-" => Precedence::Sum,
+        "/" | "%" | "*" | "&" | "|" | "<<" | ">>" | "^" => Precedence::Product,
         _ => Precedence::Lowest,
     }
 }
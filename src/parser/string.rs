@@ -0,0 +1,114 @@
+/// A piece of a string literal: text with its escapes resolved, or the source
+/// of a `${}` expression and where it starts in the literal, in chars.
+pub(super) enum Part {
+    Text(String),
+    Code(String, usize),
+}
+
+/// Splits the text of a string literal, as the lexer read it, into its parts.
+pub(super) fn split(literal: &str) -> Result<Vec<Part>, String> {
+    let chars: Vec<char> = literal.chars().collect();
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '\\' => {
+                let (ch, next) = unescape(&chars, idx)?;
+                text.push(ch);
+                idx = next;
+                continue;
+            }
+            '$' if chars.get(idx + 1) == Some(&'{') => {
+                let start = idx + 2;
+                let end = code_end(&chars, start).ok_or("unterminated '${' in string")?;
+                let code: String = chars[start..end].iter().collect();
+                if code.trim().is_empty() {
+                    return Err("empty '${}' in string".to_string());
+                }
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Part::Code(code, start));
+                idx = end;
+            }
+            ch => text.push(ch),
+        }
+        idx += 1;
+    }
+    if !text.is_empty() || parts.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+/// The char the escape at `idx` stands for and the index after the escape.
+fn unescape(chars: &[char], idx: usize) -> Result<(char, usize), String> {
+    let ch = match chars.get(idx + 1) {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('\'') => '\'',
+        Some('$') => '$',
+        Some('u') => {
+            let close = chars[idx..].iter().position(|ch| *ch == '}');
+            let digits: Option<String> = match (chars.get(idx + 2), close) {
+                (Some('{'), Some(close)) => Some(chars[idx + 3..idx + close].iter().collect()),
+                _ => None,
+            };
+            let ch = digits
+                .filter(|digits| (1..=6).contains(&digits.len()))
+                .and_then(|digits| u32::from_str_radix(&digits, 16).ok())
+                .and_then(char::from_u32);
+            return match (ch, close) {
+                (Some(ch), Some(close)) => Ok((ch, idx + close + 1)),
+                _ => Err(
+                    "invalid unicode escape, expected '\\u{...}' with 1 to 6 hex digits"
+                        .to_string(),
+                ),
+            };
+        }
+        Some(ch) => return Err(format!("invalid escape '\\{}'", ch)),
+        None => return Err("invalid escape '\\'".to_string()),
+    };
+    Ok((ch, idx + 2))
+}
+
+/// The index of the `}` closing the `${` whose code starts at `start`.
+fn code_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 1;
+    let mut idx = start;
+    while idx < chars.len() {
+        match chars[idx] {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            '"' => idx = string_end(chars, idx + 1)?,
+            _ => {}
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// The index of the quote closing a string nested in `${}` code.
+fn string_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut idx = start;
+    while idx < chars.len() {
+        match chars[idx] {
+            '\\' => idx += 1,
+            '"' => return Some(idx),
+            '$' if chars.get(idx + 1) == Some(&'{') => idx = code_end(chars, idx + 2)?,
+            _ => {}
+        }
+        idx += 1;
+    }
+    None
+}
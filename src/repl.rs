@@ -0,0 +1,154 @@
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+use crate::{
+    ast::program::Program,
+    evaluator::Evaluator,
+    lexer::Lexer,
+    object::{environment::Environment, error::Error, null::Null},
+    parser::Parser,
+    typeck::TypeChecker,
+};
+
+const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = ".. ";
+const FILENAME: &str = "repl";
+
+pub struct Repl {
+    pub evaluator: Evaluator,
+    env: Rc<RefCell<Environment>>,
+    checker: RefCell<TypeChecker>,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self {
+            evaluator: Evaluator::default(),
+            env: Rc::new(RefCell::new(Environment::default())),
+            checker: RefCell::new(TypeChecker::default()),
+        }
+    }
+
+    pub fn start<R: BufRead, W: Write>(&self, mut input: R, output: &mut W) -> io::Result<()> {
+        let mut buffer = String::new();
+        loop {
+            let prompt = if buffer.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            };
+            write!(output, "{}", prompt)?;
+            output.flush()?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(output)?;
+                break;
+            }
+            buffer.push_str(&line);
+            if !Self::is_balanced(&buffer) {
+                continue;
+            }
+            let source = std::mem::take(&mut buffer);
+            if source.trim().is_empty() {
+                continue;
+            }
+            self.eval_source(source, output)?;
+        }
+        Ok(())
+    }
+
+    fn eval_source<W: Write>(&self, source: String, output: &mut W) -> io::Result<()> {
+        let source = Rc::new(source);
+        let lexer = Lexer::new(Rc::clone(&source), Rc::new(FILENAME.to_string()));
+        let program = Parser::new(lexer).parse_program();
+        if !program.errors.is_empty() {
+            for error in program.errors.iter() {
+                writeln!(output, "{}", error.report(&source))?;
+            }
+            return Ok(());
+        }
+        // an input that fails declares nothing, the checker goes back to this
+        let snapshot = self.checker.borrow().clone();
+        let type_errors = self.checker.borrow_mut().check(&program);
+        if !type_errors.is_empty() {
+            *self.checker.borrow_mut() = snapshot;
+            for error in type_errors.iter() {
+                writeln!(output, "{}", error.report(&source))?;
+            }
+            return Ok(());
+        }
+        for (idx, stmt) in program.statements.iter().enumerate() {
+            let evaluated = self
+                .evaluator
+                .eval(Some(stmt.as_ref()), Rc::clone(&self.env));
+            // a top-level `ret` has no call around it to run its tail call
+            let evaluated = self.evaluator.finish_tail_call(evaluated);
+            if let Some(obj) = evaluated {
+                if let Some(error) = obj.as_any().downcast_ref::<Error>() {
+                    writeln!(output, "{}", error.report(&source))?;
+                    // like a script, the input stops at the error, only the
+                    // statements that ran keep what they declared
+                    let ran = Program::new(program.statements[..idx].to_vec(), vec![]);
+                    let mut checker = snapshot;
+                    checker.check(&ran);
+                    *self.checker.borrow_mut() = checker;
+                    return Ok(());
+                } else if obj.as_any().downcast_ref::<Null>().is_none() {
+                    writeln!(output, "{}", obj.inspect())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_balanced(source: &str) -> bool {
+        let mut depth = 0;
+        let mut in_string = false;
+        // how many `/* */` comments are open, they nest
+        let mut comments = 0;
+        let mut chars = source.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if comments > 0 {
+                match (ch, chars.peek()) {
+                    ('/', Some('*')) => {
+                        chars.next();
+                        comments += 1;
+                    }
+                    ('*', Some('/')) => {
+                        chars.next();
+                        comments -= 1;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            match ch {
+                '\\' if in_string => {
+                    chars.next();
+                }
+                '\"' => in_string = !in_string,
+                '/' if !in_string && chars.peek() == Some(&'/') => {
+                    while chars.next_if(|ch| *ch != '\n').is_some() {}
+                }
+                '/' if !in_string && chars.peek() == Some(&'*') => {
+                    chars.next();
+                    comments += 1;
+                }
+                '{' | '(' | '[' if !in_string => depth += 1,
+                '}' | ')' | ']' if !in_string => depth -= 1,
+                _ => {}
+            }
+        }
+        depth <= 0 && !in_string && comments == 0
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,1365 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    mem,
+    rc::Rc,
+};
+
+use crate::{
+    ast::{
+        expr::{
+            arr_expr::ArrayExpr, call_expr::CallExpr, for_expr::ForExpr,
+            function_expr::FunctionExpr, hash_expr::HashExpr, if_expr::IfExpr,
+            index_expr::IndexExpr, infix_expr::InfixExpr, interp_expr::InterpExpr,
+            match_expr::MatchExpr, member_expr::MemberExpr, prefix_expr::PrefixExpr,
+            range_expr::RangeExpr, struct_expr::StructExpr, try_expr::TryExpr,
+            while_expr::WhileExpr,
+        },
+        expression::{Expression, Node},
+        identifier::Identifier,
+        pattern::Pattern,
+        program::Program,
+        statement::Statement,
+        stmt::{
+            block_stmt::BlockStatement, break_stmt::Break, continue_stmt::Continue,
+            enum_stmt::EnumStmt, expression_stmt::ExpressionStatement, impl_stmt::ImplStmt,
+            import_stmt::Import, let_stmt::Let, return_stmt::Return, struct_stmt::StructStmt,
+            throw_stmt::Throw, var_stmt::Var,
+        },
+        types::Type,
+    },
+    evaluator::Evaluator,
+    lexer::token::Location,
+    object::environment::{declared_at, Declaration},
+};
+
+#[derive(Debug, Clone)]
+pub struct TypeError {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl TypeError {
+    pub fn new(message: String, location: Option<Location>) -> Self {
+        Self { message, location }
+    }
+
+    pub fn report(&self, source: &str) -> String {
+        match &self.location {
+            Some(location) => location.report(&self.message, source),
+            None => self.message.clone(),
+        }
+    }
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The variants of an enum with the types of the values each holds.
+type Variants = Vec<(String, Vec<Type>)>;
+
+struct Signature {
+    parameters: Vec<Type>,
+    ret: Type,
+}
+
+#[derive(Clone)]
+struct Binding {
+    typ: Type,
+    declared: bool,
+    signature: Option<Rc<Signature>>,
+    declaration: Declaration,
+    location: Option<Location>,
+}
+
+/// Walks a parsed program and reports type mismatches before it runs.
+/// Bindings survive between calls to `check`, so a REPL can reuse one checker
+/// and go back to a clone of it when an input fails.
+#[derive(Clone)]
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, Binding>>,
+    // the fields of every struct declared, wherever it is
+    structs: HashMap<String, Rc<Vec<(String, Type)>>>,
+    // the variants of every enum declared, with the types of their values
+    enums: HashMap<String, Rc<Variants>>,
+    // the methods `impl` blocks add to each struct and enum
+    methods: HashMap<String, HashMap<String, Rc<Signature>>>,
+    // names of the built-in functions, callable as methods of built-in types
+    built_ins: HashSet<String>,
+    returns: Vec<Type>,
+    loops: usize,
+    errors: Vec<TypeError>,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            built_ins: Evaluator::new().build_in_fns.into_keys().collect(),
+            returns: vec![],
+            loops: 0,
+            errors: vec![],
+        }
+    }
+
+    pub fn check(&mut self, program: &Program) -> Vec<TypeError> {
+        self.declare_types(&program.statements);
+        for stmt in program.statements.iter() {
+            if let Some(function) = Self::as_function(stmt.as_ref()) {
+                self.declare_function(function);
+            }
+        }
+        for stmt in program.statements.iter() {
+            self.check_statement(stmt.as_ref());
+        }
+        mem::take(&mut self.errors)
+    }
+
+    fn check_statement(&mut self, stmt: &dyn Statement) {
+        let node = stmt.as_any();
+        if let Some(let_stmt) = node.downcast_ref::<Let>() {
+            self.check_declaration(&let_stmt.name, &let_stmt.value, Declaration::Let);
+        } else if let Some(var) = node.downcast_ref::<Var>() {
+            self.check_declaration(&var.name, &var.value, Declaration::Var);
+        } else if let Some(import) = node.downcast_ref::<Import>() {
+            self.check_import(import);
+        } else if let Some(stmt) = node.downcast_ref::<StructStmt>() {
+            self.check_struct(stmt);
+        } else if let Some(stmt) = node.downcast_ref::<EnumStmt>() {
+            self.check_enum(stmt);
+        } else if let Some(stmt) = node.downcast_ref::<ImplStmt>() {
+            self.check_impl(stmt);
+        } else if let Some(ret) = node.downcast_ref::<Return>() {
+            self.check_return(ret);
+        } else if let Some(throw) = node.downcast_ref::<Throw>() {
+            self.infer(throw.value.as_ref());
+        } else if let Some(stmt) = node.downcast_ref::<Break>() {
+            self.check_loop_control("break", stmt.location());
+        } else if let Some(stmt) = node.downcast_ref::<Continue>() {
+            self.check_loop_control("continue", stmt.location());
+        } else if let Some(expr_stmt) = node.downcast_ref::<ExpressionStatement>() {
+            if let Some(expr) = &expr_stmt.expression {
+                self.infer(expr.as_ref());
+            }
+        } else if let Some(block) = node.downcast_ref::<BlockStatement>() {
+            self.check_block(block);
+        }
+    }
+
+    fn check_block(&mut self, block: &BlockStatement) {
+        self.declare_types(&block.statements);
+        for stmt in block.statements.iter() {
+            self.check_statement(stmt.as_ref());
+        }
+    }
+
+    fn check_declaration(
+        &mut self,
+        name: &Rc<dyn Expression>,
+        value: &Rc<dyn Expression>,
+        declaration: Declaration,
+    ) {
+        let current = self
+            .scopes
+            .last()
+            .and_then(|scope| scope.get(&name.to_string()));
+        let immutable = current
+            .filter(|binding| !binding.declaration.is_mutable())
+            .cloned();
+        if let Some(function) = value.as_any().downcast_ref::<FunctionExpr>() {
+            // a function literal can call itself through the name it is bound to
+            if immutable.is_none() {
+                let binding = Binding {
+                    typ: Type::Function,
+                    declared: false,
+                    signature: Some(Self::signature(function)),
+                    declaration: Declaration::Var,
+                    location: name.location(),
+                };
+                self.define(name.to_string(), binding);
+            }
+        }
+        let typ = self.infer(value.as_ref());
+        let identifier = match name.as_any().downcast_ref::<Identifier>() {
+            Some(identifier) => identifier,
+            None => return,
+        };
+        if let Some(binding) = immutable {
+            let msg = format!(
+                "'{}' is already declared with {}{}",
+                identifier,
+                binding.declaration,
+                declared_at(&binding.location)
+            );
+            self.error(msg, identifier.location());
+            return;
+        }
+        let binding = match &identifier.typ {
+            Some(declared) => {
+                self.check_type(declared, identifier.location());
+                if Self::is_known(&typ) && *declared != typ {
+                    self.error(
+                        format!("'{}' declared as {}, got {}", identifier, declared, typ),
+                        identifier.location(),
+                    );
+                }
+                Binding {
+                    typ: declared.clone(),
+                    declared: true,
+                    signature: self.signature_of(value.as_ref()),
+                    declaration,
+                    location: identifier.location(),
+                }
+            }
+            None => Binding {
+                typ,
+                declared: false,
+                signature: self.signature_of(value.as_ref()),
+                declaration,
+                location: identifier.location(),
+            },
+        };
+        self.define(identifier.to_string(), binding);
+    }
+
+    fn check_import(&mut self, import: &Import) {
+        let name = import.name.to_string();
+        let current = self.scopes.last().and_then(|scope| scope.get(&name));
+        if let Some(binding) = current.filter(|binding| !binding.declaration.is_mutable()) {
+            let msg = format!(
+                "'{}' is already declared with {}{}",
+                name,
+                binding.declaration,
+                declared_at(&binding.location)
+            );
+            self.error(msg, import.name.location());
+            return;
+        }
+        let binding = Binding {
+            typ: Type::Module,
+            declared: false,
+            signature: None,
+            declaration: Declaration::Import,
+            location: import.name.location(),
+        };
+        self.define(name, binding);
+    }
+
+    /// Struct and enum types can be used before their declaration, so their
+    /// fields, variants and methods are known before the statements of a
+    /// block are checked.
+    fn declare_types(&mut self, stmts: &[Rc<dyn Statement>]) {
+        for stmt in stmts.iter() {
+            if let Some(stmt) = stmt.as_any().downcast_ref::<StructStmt>() {
+                let fields = stmt
+                    .fields
+                    .iter()
+                    .map(|field| (field.to_string(), Self::declared_type(field.as_ref())))
+                    .collect();
+                self.structs.insert(stmt.name.to_string(), Rc::new(fields));
+            } else if let Some(stmt) = stmt.as_any().downcast_ref::<EnumStmt>() {
+                let variants = stmt
+                    .variants
+                    .iter()
+                    .map(|(name, types)| (name.to_string(), types.clone()))
+                    .collect();
+                self.enums.insert(stmt.name.to_string(), Rc::new(variants));
+            } else if let Some(stmt) = stmt.as_any().downcast_ref::<ImplStmt>() {
+                let type_name = stmt.name.to_string();
+                let mut seen = Vec::new();
+                for method in stmt.methods.iter() {
+                    let name = method.name.as_ref().map(|name| name.to_string());
+                    let name = name.unwrap_or_default();
+                    let methods = self.methods.entry(type_name.clone()).or_default();
+                    // a name repeated within the block is reported by `check_impl`
+                    if methods.contains_key(&name) && !seen.contains(&name) {
+                        let msg = format!("method '{}' of {} is declared twice", name, type_name);
+                        let location = method.name.as_ref().and_then(|name| name.location());
+                        self.error(msg, location);
+                        continue;
+                    }
+                    seen.push(name.clone());
+                    methods.insert(name, Self::signature(method));
+                }
+            }
+        }
+    }
+
+    fn check_struct(&mut self, stmt: &StructStmt) {
+        let name = stmt.name.to_string();
+        let current = self.scopes.last().and_then(|scope| scope.get(&name));
+        if let Some(binding) = current.filter(|binding| !binding.declaration.is_mutable()) {
+            let msg = format!(
+                "'{}' is already declared with {}{}",
+                name,
+                binding.declaration,
+                declared_at(&binding.location)
+            );
+            self.error(msg, stmt.name.location());
+            return;
+        }
+        for (idx, field) in stmt.fields.iter().enumerate() {
+            let field_name = field.to_string();
+            if stmt.fields[..idx]
+                .iter()
+                .any(|other| other.to_string() == field_name)
+            {
+                let msg = format!("field '{}' of {} is declared twice", field_name, name);
+                self.error(msg, field.location());
+            }
+            self.check_type(&Self::declared_type(field.as_ref()), field.location());
+        }
+        let binding = Binding {
+            typ: Type::StructDef,
+            declared: false,
+            signature: None,
+            declaration: Declaration::Struct,
+            location: stmt.name.location(),
+        };
+        self.define(name, binding);
+    }
+
+    fn check_enum(&mut self, stmt: &EnumStmt) {
+        let name = stmt.name.to_string();
+        let current = self.scopes.last().and_then(|scope| scope.get(&name));
+        if let Some(binding) = current.filter(|binding| !binding.declaration.is_mutable()) {
+            let msg = format!(
+                "'{}' is already declared with {}{}",
+                name,
+                binding.declaration,
+                declared_at(&binding.location)
+            );
+            self.error(msg, stmt.name.location());
+            return;
+        }
+        for (idx, (variant, types)) in stmt.variants.iter().enumerate() {
+            let variant_name = variant.to_string();
+            if stmt.variants[..idx]
+                .iter()
+                .any(|(other, _)| other.to_string() == variant_name)
+            {
+                let msg = format!("variant '{}' of {} is declared twice", variant_name, name);
+                self.error(msg, variant.location());
+            }
+            for typ in types.iter() {
+                self.check_type(typ, variant.location());
+            }
+        }
+        let binding = Binding {
+            typ: Type::EnumDef,
+            declared: false,
+            signature: None,
+            declaration: Declaration::Enum,
+            location: stmt.name.location(),
+        };
+        self.define(name, binding);
+    }
+
+    fn check_impl(&mut self, stmt: &ImplStmt) {
+        let name = stmt.name.to_string();
+        let receiver = if self.structs.contains_key(&name) {
+            Type::Struct(Rc::from(name.as_str()))
+        } else if self.enums.contains_key(&name) {
+            Type::Enum(Rc::from(name.as_str()))
+        } else {
+            let msg = match self.resolve(&name) {
+                Some(binding) if Self::is_known(&binding.typ) => {
+                    format!("impl needs a struct or enum, got {}", binding.typ)
+                }
+                _ => format!("unknown type '{}'", name),
+            };
+            self.error(msg, stmt.name.location());
+            Type::Unknown
+        };
+        let names: Vec<String> = stmt
+            .methods
+            .iter()
+            .map(|method| method.name.as_ref().map(|name| name.to_string()))
+            .map(Option::unwrap_or_default)
+            .collect();
+        for (idx, method) in stmt.methods.iter().enumerate() {
+            let method_name = &names[idx];
+            let is_field = self
+                .structs
+                .get(&name)
+                .is_some_and(|fields| fields.iter().any(|(field, _)| field == method_name));
+            let is_variant = self
+                .enums
+                .get(&name)
+                .is_some_and(|variants| variants.iter().any(|(variant, _)| variant == method_name));
+            let taken = if names[..idx].contains(method_name) {
+                Some("is declared twice")
+            } else if is_field {
+                Some("has the name of a field")
+            } else if is_variant {
+                Some("has the name of a variant")
+            } else {
+                None
+            };
+            if let Some(taken) = taken {
+                let msg = format!("method '{}' of {} {}", method_name, name, taken);
+                let location = method.name.as_ref().and_then(|name| name.location());
+                self.error(msg, location);
+            }
+            self.check_function_body(method, Some(&receiver));
+        }
+    }
+
+    /// The value types of `Shape.Circle` when `member` names a variant of a
+    /// declared enum.
+    fn variant_types(&self, member: &MemberExpr) -> Option<(String, Vec<Type>)> {
+        let name = member.object.to_string();
+        let variants = self.enums.get(&name)?;
+        let variant = member.member.to_string();
+        variants
+            .iter()
+            .find(|(declared, _)| *declared == variant)
+            .map(|(_, types)| (name, types.clone()))
+    }
+
+    /// Calls to `Shape.Circle` are checked like calls to a function taking
+    /// the values of the variant.
+    fn variant_signature(&self, typ: &Type, member: &MemberExpr) -> Option<Rc<Signature>> {
+        if *typ != Type::EnumDef {
+            return None;
+        }
+        let (name, parameters) = self.variant_types(member)?;
+        Some(Rc::new(Signature {
+            parameters,
+            ret: Type::Enum(Rc::from(name)),
+        }))
+    }
+
+    fn check_match(&mut self, match_expr: &MatchExpr) {
+        let typ = self.infer(match_expr.value.as_ref());
+        for arm in match_expr.arms.iter() {
+            let mut scope = HashMap::new();
+            self.check_pattern(&arm.pattern, &typ, &mut scope);
+            self.scopes.push(scope);
+            if let Some(guard) = &arm.guard {
+                self.check_condition(guard.as_ref(), guard.location());
+            }
+            self.check_statement(arm.body.as_ref());
+            self.scopes.pop();
+        }
+        self.check_exhaustive(match_expr, &typ);
+    }
+
+    /// Checks `pattern` against a value of type `typ`, adding the names it
+    /// binds to `scope`.
+    fn check_pattern(
+        &mut self,
+        pattern: &Pattern,
+        typ: &Type,
+        scope: &mut HashMap<String, Binding>,
+    ) {
+        let mismatch = |found: &Type| Self::is_known(typ) && Self::is_known(found) && typ != found;
+        match pattern {
+            Pattern::Wildcard(_) => {}
+            Pattern::Binding(name) => {
+                if scope.contains_key(&name.to_string()) {
+                    let msg = format!("'{}' is bound twice in the pattern", name);
+                    self.error(msg, name.location());
+                }
+                let binding = Binding {
+                    typ: typ.clone(),
+                    declared: false,
+                    signature: None,
+                    declaration: Declaration::Var,
+                    location: name.location(),
+                };
+                scope.insert(name.to_string(), binding);
+            }
+            Pattern::Literal(literal) => {
+                let found = literal.get_type();
+                if mismatch(&found) {
+                    let msg = format!("mismatched pattern: expected {}, got {}", typ, found);
+                    self.error(msg, literal.location());
+                }
+            }
+            Pattern::Range(start, _) => {
+                if mismatch(&Type::Int) {
+                    let msg = format!("mismatched pattern: expected {}, got int", typ);
+                    self.error(msg, start.location());
+                }
+            }
+            Pattern::Variant {
+                name,
+                variant,
+                values,
+            } => {
+                let enum_name = name.to_string();
+                let variants = match self.enums.get(&enum_name) {
+                    Some(variants) => Rc::clone(variants),
+                    None => {
+                        self.error(format!("unknown enum '{}'", enum_name), name.location());
+                        return;
+                    }
+                };
+                if mismatch(&Type::Enum(Rc::from(enum_name.as_str()))) {
+                    let msg = format!("mismatched pattern: expected {}, got {}", typ, enum_name);
+                    self.error(msg, name.location());
+                }
+                let variant_name = variant.to_string();
+                let types = match variants
+                    .iter()
+                    .find(|(declared, _)| *declared == variant_name)
+                {
+                    Some((_, types)) => types,
+                    None => {
+                        let msg = format!("{} has no variant '{}'", enum_name, variant_name);
+                        self.error(msg, variant.location());
+                        return;
+                    }
+                };
+                if types.len() != values.len() {
+                    let msg = format!(
+                        "{}.{} holds {} value{}, got {} in the pattern",
+                        enum_name,
+                        variant_name,
+                        types.len(),
+                        if types.len() == 1 { "" } else { "s" },
+                        values.len()
+                    );
+                    self.error(msg, variant.location());
+                }
+                for (value, typ) in values.iter().zip(types.iter()) {
+                    self.check_pattern(value, typ, scope);
+                }
+            }
+            Pattern::Array {
+                items,
+                rest,
+                location,
+            } => {
+                if mismatch(&Type::Array) {
+                    let msg = format!("mismatched pattern: expected {}, got array", typ);
+                    self.error(msg, location.clone());
+                }
+                for item in items.iter() {
+                    self.check_pattern(item, &Type::Unknown, scope);
+                }
+                if let Some(rest) = rest {
+                    self.check_pattern(rest, &Type::Array, scope);
+                }
+            }
+        }
+    }
+
+    /// Reports the variants no arm covers in a match on an enum value. Arms
+    /// with a guard cover nothing, as the guard may fail.
+    fn check_exhaustive(&mut self, match_expr: &MatchExpr, typ: &Type) {
+        let arms: Vec<&Pattern> = match_expr
+            .arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| arm.pattern.as_ref())
+            .collect();
+        if arms.iter().any(|pattern| pattern.is_catch_all()) {
+            return;
+        }
+        let name = match typ {
+            Type::Struct(name) | Type::Enum(name) => Some(name.to_string()),
+            _ => match_expr
+                .arms
+                .iter()
+                .find_map(|arm| match arm.pattern.as_ref() {
+                    Pattern::Variant { name, .. } => Some(name.to_string()),
+                    _ => None,
+                }),
+        };
+        let variants = match name.as_ref().and_then(|name| self.enums.get(name)) {
+            Some(variants) => Rc::clone(variants),
+            None => return,
+        };
+        let name = name.unwrap_or_default();
+        let missing: Vec<String> = variants
+            .iter()
+            .filter(|(variant, _)| {
+                !arms.iter().any(|pattern| match pattern {
+                    Pattern::Variant {
+                        name: enum_name,
+                        variant: covered,
+                        values,
+                    } => {
+                        enum_name.to_string() == name
+                            && covered.to_string() == *variant
+                            && values.iter().all(Pattern::is_catch_all)
+                    }
+                    _ => false,
+                })
+            })
+            .map(|(variant, _)| format!("{}.{}", name, variant))
+            .collect();
+        if !missing.is_empty() {
+            let msg = format!("non-exhaustive match, missing {}", missing.join(", "));
+            self.error(msg, match_expr.location());
+        }
+    }
+
+    fn infer_struct(&mut self, struct_expr: &StructExpr) -> Type {
+        let values: Vec<Type> = struct_expr
+            .fields
+            .iter()
+            .map(|(_, value)| self.infer(value.as_ref()))
+            .collect();
+        let name = struct_expr.name.to_string();
+        let fields = match self.structs.get(&name) {
+            Some(fields) => Rc::clone(fields),
+            None => {
+                if self.resolve(&name).is_none() {
+                    let msg = format!("unknown struct '{}'", name);
+                    self.error(msg, struct_expr.location());
+                }
+                return Type::Unknown;
+            }
+        };
+        let mut given: Vec<String> = vec![];
+        for ((field, _), value) in struct_expr.fields.iter().zip(values) {
+            let field_name = field.to_string();
+            let msg = match fields.iter().find(|(declared, _)| *declared == field_name) {
+                None => Some(format!("{} has no field '{}'", name, field_name)),
+                Some(_) if given.contains(&field_name) => {
+                    Some(format!("field '{}' of {} is given twice", field_name, name))
+                }
+                Some((_, typ)) if Self::is_known(&value) && *typ != value => Some(format!(
+                    "field '{}' of {} expected {}, got {}",
+                    field_name, name, typ, value
+                )),
+                Some(_) => None,
+            };
+            if let Some(msg) = msg {
+                self.error(msg, field.location());
+            }
+            given.push(field_name);
+        }
+        let missing: Vec<String> = fields
+            .iter()
+            .filter(|(field, _)| !given.contains(field))
+            .map(|(field, _)| format!("'{}'", field))
+            .collect();
+        if !missing.is_empty() {
+            let plural = if missing.len() == 1 { "" } else { "s" };
+            let msg = format!("missing field{} {} in {}", plural, missing.join(", "), name);
+            self.error(msg, struct_expr.location());
+        }
+        Type::Struct(Rc::from(name))
+    }
+
+    /// The type of a field of an object of type `typ`, or an error for a
+    /// member the type doesn't have, a method when it is `called`.
+    fn member_type(&mut self, typ: &Type, member: &MemberExpr, called: bool) -> Type {
+        let name = member.member.to_string();
+        let missing = if called { "method" } else { "field" };
+        match typ {
+            Type::Struct(struct_name) => {
+                let field = self.structs.get(struct_name.as_ref()).and_then(|fields| {
+                    fields
+                        .iter()
+                        .find(|(field, _)| *field == name)
+                        .map(|(_, typ)| typ.clone())
+                });
+                match field {
+                    Some(field) => return field,
+                    None if self.method(typ, member).is_some() => return Type::Function,
+                    None if self.structs.contains_key(struct_name.as_ref()) => {
+                        let msg = format!("{} has no {} '{}'", struct_name, missing, name);
+                        self.error(msg, member.location());
+                    }
+                    None => {}
+                }
+            }
+            Type::Module => {}
+            Type::EnumDef => {
+                let enum_name = member.object.to_string();
+                match self.variant_types(member) {
+                    Some((enum_name, types)) if types.is_empty() => {
+                        return Type::Enum(Rc::from(enum_name))
+                    }
+                    Some(_) => return Type::Function,
+                    None if self.method(typ, member).is_some() => return Type::Function,
+                    None if self.enums.contains_key(&enum_name) => {
+                        let msg = format!("{} has no variant '{}'", enum_name, name);
+                        self.error(msg, member.location());
+                    }
+                    None => {}
+                }
+            }
+            _ if self.method(typ, member).is_some() => return Type::Function,
+            typ if Self::has_built_in_methods(typ) && self.built_ins.contains(&name) => {
+                return Type::Function;
+            }
+            typ if Self::is_known(typ) => {
+                let missing = if called { "method" } else { "member" };
+                let msg = format!("{} has no {} '{}'", typ, missing, name);
+                self.error(msg, member.location());
+            }
+            _ => {}
+        }
+        Type::Unknown
+    }
+
+    /// The signature of the method `member` names on a value of type `typ`,
+    /// without the parameter for the value, or of the function `member`
+    /// names on the type itself.
+    fn method(&self, typ: &Type, member: &MemberExpr) -> Option<Rc<Signature>> {
+        let (type_name, bound) = match typ {
+            Type::Struct(name) | Type::Enum(name) => (name.to_string(), true),
+            Type::StructDef | Type::EnumDef => (member.object.to_string(), false),
+            _ => return None,
+        };
+        if typ == &Type::EnumDef && self.variant_types(member).is_some() {
+            return None;
+        }
+        let signature = self
+            .methods
+            .get(&type_name)?
+            .get(&member.member.to_string())?;
+        if !bound {
+            return Some(Rc::clone(signature));
+        }
+        Some(Rc::new(Signature {
+            parameters: signature.parameters.iter().skip(1).cloned().collect(),
+            ret: signature.ret.clone(),
+        }))
+    }
+
+    /// Reports struct and enum type names nothing declares.
+    fn check_type(&mut self, typ: &Type, location: Option<Location>) {
+        if let Type::Struct(name) = typ {
+            let declared =
+                self.structs.contains_key(name.as_ref()) || self.enums.contains_key(name.as_ref());
+            if !declared {
+                self.error(format!("unknown type '{}'", name), location);
+            }
+        }
+    }
+
+    fn check_return(&mut self, ret: &Return) {
+        let typ = match &ret.return_value {
+            Some(value) => self.infer(value.as_ref()),
+            None => return,
+        };
+        if let Some(expected) = self.returns.last().cloned() {
+            if Self::is_known(&expected) && Self::is_known(&typ) && expected != typ {
+                self.error(
+                    format!("expected return type {}, got {}", expected, typ),
+                    ret.location(),
+                );
+            }
+        }
+    }
+
+    fn infer(&mut self, expr: &dyn Expression) -> Type {
+        let node = expr.as_any();
+        if let Some(identifier) = node.downcast_ref::<Identifier>() {
+            match self.resolve(&identifier.to_string()) {
+                Some(binding) => binding.typ.clone(),
+                None => Type::Unknown,
+            }
+        } else if let Some(infix) = node.downcast_ref::<InfixExpr>() {
+            self.infer_infix(infix)
+        } else if let Some(prefix) = node.downcast_ref::<PrefixExpr>() {
+            self.infer_prefix(prefix)
+        } else if let Some(call) = node.downcast_ref::<CallExpr>() {
+            self.infer_call(call)
+        } else if let Some(index) = node.downcast_ref::<IndexExpr>() {
+            self.infer_index(index)
+        } else if let Some(member) = node.downcast_ref::<MemberExpr>() {
+            let typ = self.infer(member.object.as_ref());
+            self.member_type(&typ, member, false)
+        } else if let Some(struct_expr) = node.downcast_ref::<StructExpr>() {
+            self.infer_struct(struct_expr)
+        } else if let Some(if_expr) = node.downcast_ref::<IfExpr>() {
+            self.check_if(if_expr);
+            Type::Unknown
+        } else if let Some(while_expr) = node.downcast_ref::<WhileExpr>() {
+            self.check_condition(while_expr.condition.as_ref(), while_expr.location());
+            if let Some(body) = &while_expr.consequence {
+                self.scopes.push(HashMap::new());
+                self.loops += 1;
+                self.check_block(body);
+                self.loops -= 1;
+                self.scopes.pop();
+            }
+            Type::Unknown
+        } else if let Some(for_expr) = node.downcast_ref::<ForExpr>() {
+            self.check_for(for_expr);
+            Type::Unknown
+        } else if let Some(range) = node.downcast_ref::<RangeExpr>() {
+            self.check_range(range);
+            Type::Array
+        } else if let Some(try_expr) = node.downcast_ref::<TryExpr>() {
+            self.check_try(try_expr);
+            Type::Unknown
+        } else if let Some(match_expr) = node.downcast_ref::<MatchExpr>() {
+            self.check_match(match_expr);
+            Type::Unknown
+        } else if let Some(function) = node.downcast_ref::<FunctionExpr>() {
+            self.check_function(function);
+            Type::Function
+        } else if let Some(interp) = node.downcast_ref::<InterpExpr>() {
+            for part in interp.parts.iter() {
+                self.infer(part.as_ref());
+            }
+            Type::String
+        } else if let Some(array) = node.downcast_ref::<ArrayExpr>() {
+            for elem in array.value.iter() {
+                self.infer(elem.as_ref());
+            }
+            Type::Array
+        } else if let Some(hash) = node.downcast_ref::<HashExpr>() {
+            for (key, value) in hash.pairs.iter() {
+                let key_type = self.infer(key.as_ref());
+                if Self::is_known(&key_type) && !Self::is_hashable(&key_type) {
+                    self.error(format!("unusable as map key: {}", key_type), key.location());
+                }
+                self.infer(value.as_ref());
+            }
+            Type::Map
+        } else {
+            expr.get_type()
+        }
+    }
+
+    fn infer_infix(&mut self, infix: &InfixExpr) -> Type {
+        let (left, right) = match (&infix.left, &infix.right) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Type::Unknown,
+        };
+        if infix.operator == "=" {
+            return self.check_assign(left, right, infix.location());
+        }
+        let right = self.infer(right.as_ref());
+        let left = self.infer(left.as_ref());
+        let operator = infix.operator.as_str();
+        let is_comparison = matches!(operator, "==" | "!=" | ">" | "<" | ">=" | "<=");
+        let is_logic = matches!(operator, "&&" | "||");
+        if !Self::is_known(&left) || !Self::is_known(&right) {
+            return if is_comparison || is_logic {
+                Type::Bool
+            } else if Self::is_known(&left) {
+                left
+            } else {
+                right
+            };
+        }
+        // an int mixed with a float is promoted, as at runtime
+        let numeric = |typ: &Type| *typ == Type::Int || *typ == Type::Float;
+        let (left, right) = if left != right && numeric(&left) && numeric(&right) {
+            (Type::Float, Type::Float)
+        } else {
+            (left, right)
+        };
+        if left != right {
+            self.error(
+                format!("incompatible types {} and {}", right, left),
+                infix.location(),
+            );
+            return Type::Unknown;
+        }
+        let result = match &left {
+            Type::Int if is_comparison => Some(Type::Bool),
+            Type::Int if !is_logic => Some(Type::Int),
+            Type::Float if is_comparison => Some(Type::Bool),
+            Type::Float if matches!(operator, "+" | "-" | "*" | "/" | "%") => Some(Type::Float),
+            Type::Bool if is_comparison || is_logic => Some(Type::Bool),
+            Type::String if operator == "+" => Some(Type::String),
+            _ => None,
+        };
+        match result {
+            Some(typ) => typ,
+            None => {
+                self.error(
+                    format!("unsupported operation {} {} {}", left, operator, right),
+                    infix.location(),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_assign(
+        &mut self,
+        left: &Rc<dyn Expression>,
+        right: &Rc<dyn Expression>,
+        location: Option<Location>,
+    ) -> Type {
+        let typ = self.infer(right.as_ref());
+        if let Some(index) = left.as_any().downcast_ref::<IndexExpr>() {
+            self.infer_index(index);
+            return typ;
+        }
+        if let Some(member) = left.as_any().downcast_ref::<MemberExpr>() {
+            let object = self.infer(member.object.as_ref());
+            if object == Type::Module {
+                let msg = format!(
+                    "cannot assign to '{}.{}', module members are read only",
+                    member.object, member.member
+                );
+                self.error(msg, location);
+                return typ;
+            }
+            let field = self.member_type(&object, member, false);
+            if Self::is_known(&field) && Self::is_known(&typ) && field != typ {
+                let msg = format!(
+                    "field '{}' of {} expected {}, got {}",
+                    member.member, object, field, typ
+                );
+                self.error(msg, location);
+            }
+            return typ;
+        }
+        let name = left.to_string();
+        let signature = self.signature_of(right.as_ref());
+        if let Some(binding) = self
+            .resolve(&name)
+            .filter(|binding| !binding.declaration.is_mutable())
+        {
+            let msg = format!(
+                "cannot assign to '{}', it was declared with {}{}",
+                name,
+                binding.declaration,
+                declared_at(&binding.location)
+            );
+            self.error(msg, location);
+            return typ;
+        }
+        match self.resolve_mut(&name) {
+            Some(binding) if binding.declared => {
+                if Self::is_known(&typ) && binding.typ != typ {
+                    let msg = format!(
+                        "cannot assign {} to '{}' of type {}",
+                        typ, name, binding.typ
+                    );
+                    self.error(msg, location);
+                }
+            }
+            Some(binding) => {
+                if binding.typ != typ {
+                    binding.typ = Type::Unknown;
+                    binding.signature = None;
+                }
+            }
+            None => self.define(
+                name,
+                Binding {
+                    typ: typ.clone(),
+                    declared: false,
+                    signature,
+                    declaration: Declaration::Var,
+                    location: None,
+                },
+            ),
+        }
+        typ
+    }
+
+    fn infer_prefix(&mut self, prefix: &PrefixExpr) -> Type {
+        let right = match &prefix.right {
+            Some(right) => self.infer(right.as_ref()),
+            None => return Type::Unknown,
+        };
+        if !Self::is_known(&right) {
+            return Type::Unknown;
+        }
+        match (prefix.operator.as_str(), right) {
+            ("-", Type::Int) | ("!", Type::Int) => Type::Int,
+            ("-", Type::Float) => Type::Float,
+            ("!", Type::Bool) => Type::Bool,
+            (operator, typ) => {
+                self.error(
+                    format!("invalid expression '{}{}'", operator, typ),
+                    prefix.location(),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn infer_call(&mut self, call: &CallExpr) -> Type {
+        let arguments: Vec<Type> = call
+            .arguments
+            .iter()
+            .map(|arg| self.infer(arg.as_ref()))
+            .collect();
+        let callee = call.function.as_ref();
+        let name = match callee.as_any().downcast_ref::<MemberExpr>() {
+            Some(member) => format!("{}.{}", member.object, member.member),
+            None => callee.to_string(),
+        };
+        let (typ, signature) = if callee.as_any().is::<Identifier>() {
+            match self.resolve(&name) {
+                Some(binding) => (binding.typ.clone(), binding.signature.clone()),
+                None => return Self::built_in_ret(&name),
+            }
+        } else if let Some(member) = callee.as_any().downcast_ref::<MemberExpr>() {
+            let object = self.infer(member.object.as_ref());
+            let typ = self.member_type(&object, member, true);
+            if Self::has_built_in_methods(&object) && typ == Type::Function {
+                return Self::built_in_ret(&member.member.to_string());
+            }
+            let signature = self
+                .method(&object, member)
+                .or_else(|| self.variant_signature(&object, member));
+            (typ, signature)
+        } else {
+            (self.infer(callee), None)
+        };
+        if Self::is_known(&typ) && typ != Type::Function {
+            self.error(
+                format!("'{}' is not a function, got {}", name, typ),
+                call.location(),
+            );
+            return Type::Unknown;
+        }
+        let signature = match signature {
+            Some(signature) => signature,
+            None => return Type::Unknown,
+        };
+        if signature.parameters.len() != arguments.len() {
+            self.error(
+                format!(
+                    "wrong number of arguments to '{}': want={}, got={}",
+                    name,
+                    signature.parameters.len(),
+                    arguments.len()
+                ),
+                call.location(),
+            );
+            return signature.ret.clone();
+        }
+        for (idx, (param, arg)) in signature.parameters.iter().zip(arguments).enumerate() {
+            if Self::is_known(param) && Self::is_known(&arg) && *param != arg {
+                self.error(
+                    format!(
+                        "argument {} of '{}' expected {}, got {}",
+                        idx + 1,
+                        name,
+                        param,
+                        arg
+                    ),
+                    call.arguments[idx].location().or_else(|| call.location()),
+                );
+            }
+        }
+        signature.ret.clone()
+    }
+
+    fn infer_index(&mut self, index: &IndexExpr) -> Type {
+        let left = self.infer(index.left.as_ref());
+        let idx = self.infer(index.index.as_ref());
+        match left {
+            Type::Array | Type::String => {
+                if Self::is_known(&idx) && idx != Type::Int {
+                    self.error(
+                        format!("{} index must be int, got {}", left, idx),
+                        index.location(),
+                    );
+                }
+                if left == Type::String {
+                    Type::String
+                } else {
+                    Type::Unknown
+                }
+            }
+            Type::Map => {
+                if Self::is_known(&idx) && !Self::is_hashable(&idx) {
+                    self.error(format!("unusable as map key: {}", idx), index.location());
+                }
+                Type::Unknown
+            }
+            Type::Unknown => Type::Unknown,
+            typ => {
+                self.error(
+                    format!("index operation not suported: {}", typ),
+                    index.location(),
+                );
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_if(&mut self, if_expr: &IfExpr) {
+        self.check_condition(if_expr.condition.as_ref(), if_expr.location());
+        for block in [&if_expr.consequence, &if_expr.alternative]
+            .into_iter()
+            .flatten()
+        {
+            self.scopes.push(HashMap::new());
+            self.check_block(block);
+            self.scopes.pop();
+        }
+        if let Some(el_if) = &if_expr.el_if {
+            self.infer(el_if.as_ref());
+        }
+    }
+
+    fn check_for(&mut self, for_expr: &ForExpr) {
+        let item = if let Some(range) = for_expr.iterable.as_any().downcast_ref::<RangeExpr>() {
+            self.check_range(range);
+            Type::Int
+        } else {
+            match self.infer(for_expr.iterable.as_ref()) {
+                Type::String => Type::String,
+                typ if Self::is_known(&typ) && typ != Type::Array => {
+                    self.error(
+                        format!("cannot iterate over {}", typ),
+                        for_expr.iterable.location(),
+                    );
+                    Type::Unknown
+                }
+                _ => Type::Unknown,
+            }
+        };
+        let mut scope = HashMap::new();
+        scope.insert(
+            for_expr.variable.to_string(),
+            Binding {
+                typ: item,
+                declared: false,
+                signature: None,
+                declaration: Declaration::Var,
+                location: None,
+            },
+        );
+        self.scopes.push(scope);
+        self.loops += 1;
+        if let Some(body) = &for_expr.body {
+            self.check_block(body);
+        }
+        self.loops -= 1;
+        self.scopes.pop();
+    }
+
+    fn check_range(&mut self, range: &RangeExpr) {
+        let start = self.infer(range.start.as_ref());
+        let end = self.infer(range.end.as_ref());
+        let invalid = |typ: &Type| Self::is_known(typ) && *typ != Type::Int;
+        if invalid(&start) || invalid(&end) {
+            self.error(
+                format!("range bounds must be int, got {}..{}", start, end),
+                range.location(),
+            );
+        }
+    }
+
+    fn check_loop_control(&mut self, keyword: &str, location: Option<Location>) {
+        if self.loops == 0 {
+            self.error(format!("'{}' outside of a loop", keyword), location);
+        }
+    }
+
+    fn check_try(&mut self, try_expr: &TryExpr) {
+        if let Some(body) = &try_expr.body {
+            self.scopes.push(HashMap::new());
+            self.check_block(body);
+            self.scopes.pop();
+        }
+        let mut scope = HashMap::new();
+        scope.insert(
+            try_expr.name.to_string(),
+            Binding {
+                typ: Type::Map,
+                declared: false,
+                signature: None,
+                declaration: Declaration::Var,
+                location: None,
+            },
+        );
+        self.scopes.push(scope);
+        if let Some(handler) = &try_expr.handler {
+            self.check_block(handler);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_condition(&mut self, condition: &dyn Expression, location: Option<Location>) {
+        let typ = self.infer(condition);
+        if Self::is_known(&typ) && typ != Type::Bool {
+            self.error(
+                format!("condition must be bool, got {}", typ),
+                condition.location().or(location),
+            );
+        }
+    }
+
+    fn check_function(&mut self, function: &FunctionExpr) {
+        self.declare_function(function);
+        self.check_function_body(function, None);
+    }
+
+    /// Checks the parameters and body of a function, a `self` parameter of a
+    /// method without a declared type has the type of its `receiver`.
+    fn check_function_body(&mut self, function: &FunctionExpr, receiver: Option<&Type>) {
+        let mut scope = HashMap::new();
+        self.check_type(&function.ret_typ, function.location());
+        for (idx, param) in function.parameters.iter().enumerate() {
+            let mut typ = Self::declared_type(param.as_ref());
+            if let Some(receiver) = receiver.filter(|_| idx == 0 && param.to_string() == "self") {
+                if typ == Type::Unknown {
+                    typ = receiver.clone();
+                }
+            }
+            self.check_type(&typ, param.location());
+            scope.insert(
+                param.to_string(),
+                Binding {
+                    declared: Self::is_known(&typ),
+                    typ,
+                    signature: None,
+                    declaration: Declaration::Var,
+                    location: None,
+                },
+            );
+        }
+        self.scopes.push(scope);
+        self.returns.push(function.ret_typ.clone());
+        // a loop around the declaration does not reach into its body
+        let loops = mem::take(&mut self.loops);
+        if let Some(body) = &function.body {
+            self.check_block(body);
+        }
+        self.loops = loops;
+        self.returns.pop();
+        self.scopes.pop();
+    }
+
+    fn declare_function(&mut self, function: &FunctionExpr) {
+        let name = match &function.name {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        self.define(
+            name,
+            Binding {
+                typ: Type::Function,
+                declared: true,
+                signature: Some(Self::signature(function)),
+                declaration: Declaration::Var,
+                location: None,
+            },
+        );
+    }
+
+    fn signature(function: &FunctionExpr) -> Rc<Signature> {
+        Rc::new(Signature {
+            parameters: function
+                .parameters
+                .iter()
+                .map(|param| Self::declared_type(param.as_ref()))
+                .collect(),
+            ret: function.ret_typ.clone(),
+        })
+    }
+
+    fn signature_of(&self, expr: &dyn Expression) -> Option<Rc<Signature>> {
+        if expr.as_any().downcast_ref::<Identifier>().is_some() {
+            self.resolve(&expr.to_string())
+                .and_then(|binding| binding.signature.clone())
+        } else {
+            expr.as_any()
+                .downcast_ref::<FunctionExpr>()
+                .map(Self::signature)
+        }
+    }
+
+    fn as_function(stmt: &dyn Statement) -> Option<&FunctionExpr> {
+        stmt.as_any()
+            .downcast_ref::<ExpressionStatement>()
+            .and_then(|stmt| stmt.expression.as_ref())
+            .and_then(|expr| expr.as_any().downcast_ref::<FunctionExpr>())
+    }
+
+    fn declared_type(param: &dyn Expression) -> Type {
+        param
+            .as_any()
+            .downcast_ref::<Identifier>()
+            .and_then(|identifier| identifier.typ.clone())
+            .unwrap_or(Type::Unknown)
+    }
+
+    fn built_in_ret(name: &str) -> Type {
+        match name {
+            "len" | "to_int" | "floor" | "ceil" | "round" => Type::Int,
+            "to_float" | "sqrt" | "pow" => Type::Float,
+            "to_str" | "join" | "replace_all" | "upper" | "lower" | "repeat" | "pad_left"
+            | "pad_right" | "format" | "read_file" => Type::String,
+            "is_error" | "has_key" | "contains" | "starts_with" | "ends_with" | "any" | "all"
+            | "exists" => Type::Bool,
+            "keys" | "values" | "split" | "chars" | "bytes" | "map" | "filter" | "sort" | "zip"
+            | "enumerate" | "read_lines" | "list_dir" | "args" => Type::Array,
+            _ => Type::Unknown,
+        }
+    }
+
+    fn define(&mut self, name: String, binding: Binding) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, binding);
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn resolve_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    fn error(&mut self, message: String, location: Option<Location>) {
+        self.errors.push(TypeError::new(message, location));
+    }
+
+    fn is_known(typ: &Type) -> bool {
+        matches!(
+            typ,
+            Type::Int
+                | Type::Float
+                | Type::Bool
+                | Type::String
+                | Type::Array
+                | Type::Map
+                | Type::Function
+                | Type::Module
+                | Type::Struct(_)
+                | Type::Enum(_)
+        )
+    }
+
+    /// Whether values of `typ` take the built-in functions as methods.
+    fn has_built_in_methods(typ: &Type) -> bool {
+        matches!(
+            typ,
+            Type::Int
+                | Type::Float
+                | Type::Bool
+                | Type::String
+                | Type::Array
+                | Type::Map
+                | Type::Function
+        )
+    }
+
+    fn is_hashable(typ: &Type) -> bool {
+        matches!(typ, Type::Int | Type::Bool | Type::String)
+    }
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,47 @@
+use std::rc::Rc;
+
+use crate::{
+    compiler::code,
+    object::{closure::Closure, Object},
+};
+
+pub(super) struct Frame {
+    pub closure: Rc<dyn Object>,
+    pub instructions: Rc<[u8]>,
+    pub ip: usize,
+    pub base_pointer: usize,
+}
+
+impl Frame {
+    pub fn new(closure: Rc<dyn Object>, base_pointer: usize) -> Option<Self> {
+        let instructions = Rc::clone(
+            &closure
+                .as_any()
+                .downcast_ref::<Closure>()?
+                .function
+                .instructions,
+        );
+        Some(Self {
+            closure,
+            instructions,
+            ip: 0,
+            base_pointer,
+        })
+    }
+
+    pub fn closure(&self) -> &Closure {
+        self.closure.as_any().downcast_ref::<Closure>().unwrap()
+    }
+
+    pub fn read_u8(&mut self) -> usize {
+        let operand = self.instructions[self.ip] as usize;
+        self.ip += 1;
+        operand
+    }
+
+    pub fn read_u16(&mut self) -> usize {
+        let operand = code::read_u16(&self.instructions, self.ip);
+        self.ip += 2;
+        operand
+    }
+}
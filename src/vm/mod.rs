@@ -0,0 +1,692 @@
+mod frame;
+
+use std::{cell::RefCell, mem, rc::Rc};
+
+use crate::{
+    ast::types::Type,
+    compiler::{code::Opcode, Bytecode},
+    evaluator::{Evaluator, MAX_DEPTH},
+    object::{
+        array::Array,
+        boolean::Boolean,
+        built_in::{BuiltIn, Caller},
+        cell::Cell,
+        closure::Closure,
+        compiled_function::CompiledFunction,
+        enumeration::Constructor,
+        environment::Environment,
+        error::Error,
+        integer::Integer,
+        method::BoundMethod,
+        module::Module,
+        null::Null,
+        pattern::MatchPattern,
+        string::Str,
+        Object,
+    },
+};
+
+use self::frame::Frame;
+
+/// An active `try`: where its catch block starts and the state to unwind to.
+struct Handler {
+    frame_depth: usize,
+    stack_len: usize,
+    ip: usize,
+}
+
+pub struct Vm {
+    constants: Vec<Rc<dyn Object>>,
+    globals: Vec<Option<Rc<dyn Object>>>,
+    global_names: Vec<String>,
+    stack: Vec<Rc<dyn Object>>,
+    frames: Vec<Frame>,
+    // how many frames are under the one `execute` started with
+    base: usize,
+    handlers: Vec<Handler>,
+    evaluator: Evaluator,
+    pub max_depth: usize,
+}
+
+impl Vm {
+    pub fn new(bytecode: Bytecode) -> Self {
+        let mut main = CompiledFunction::new(bytecode.instructions, 0, 0, "main".to_string());
+        main.locations = bytecode.locations.into();
+        let main: Rc<dyn Object> = Rc::new(Closure::new(Rc::new(main), vec![]));
+        Self {
+            constants: bytecode.constants,
+            globals: vec![None; bytecode.globals.len()],
+            global_names: bytecode.globals,
+            stack: Vec::with_capacity(1024),
+            frames: Frame::new(main, 0).into_iter().collect(),
+            base: 0,
+            handlers: vec![],
+            evaluator: Evaluator::default(),
+            max_depth: MAX_DEPTH,
+        }
+    }
+
+    /// Runs the program and returns the value of its last statement, the same
+    /// value `Evaluator::eval` gives for the program.
+    pub fn run(&mut self) -> Result<Rc<dyn Object>, String> {
+        let frame = self.frames.pop().ok_or("no frame to execute")?;
+        self.execute(frame)
+    }
+
+    /// Runs `frame` until it returns, or to the end of the main frame.
+    fn execute(&mut self, mut frame: Frame) -> Result<Rc<dyn Object>, String> {
+        while frame.ip < frame.instructions.len() {
+            let op_ip = frame.ip;
+            let byte = frame.instructions[op_ip];
+            let op = Opcode::from_byte(byte).ok_or(format!("unknown opcode {}", byte))?;
+            frame.ip += 1;
+            match op {
+                Opcode::Constant => {
+                    let idx = frame.read_u16();
+                    let constant = Self::locate(Rc::clone(&self.constants[idx]), &frame, op_ip);
+                    self.push(constant);
+                }
+                Opcode::Pop => {
+                    let val = self.pop()?;
+                    // like `eval_statements`, a failed statement ends its function
+                    if val.get_type() == Type::Error {
+                        if let Some(val) = self.raise(&mut frame, val) {
+                            return Ok(val);
+                        }
+                    }
+                }
+                Opcode::Add
+                | Opcode::Sub
+                | Opcode::Mul
+                | Opcode::Div
+                | Opcode::Mod
+                | Opcode::BitWiseAnd
+                | Opcode::BitWiseOr
+                | Opcode::Xor
+                | Opcode::ShiftLeft
+                | Opcode::ShiftRight
+                | Opcode::Eq
+                | Opcode::Diff
+                | Opcode::Gt
+                | Opcode::Gte
+                | Opcode::Lt
+                | Opcode::Lte
+                | Opcode::And
+                | Opcode::Or => {
+                    let left = self.pop()?;
+                    let right = self.pop()?;
+                    let result = self.execute_binary_operation(op, left, right);
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Minus | Opcode::Bang => {
+                    let right = self.pop()?;
+                    let result = if right.get_type() == Type::Error {
+                        right
+                    } else {
+                        self.evaluator
+                            .eval_prefix_expr(right, op.operator())
+                            .unwrap_or_else(|| Rc::new(Null))
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::True => self.push(Rc::new(Boolean::new(true))),
+                Opcode::False => self.push(Rc::new(Boolean::new(false))),
+                Opcode::Null => self.push(Rc::new(Null)),
+                Opcode::Jump => {
+                    frame.ip = frame.read_u16();
+                }
+                Opcode::JumpNotTruthy => {
+                    let target = frame.read_u16();
+                    let condition = self.pop()?;
+                    if condition.get_type() == Type::Error {
+                        if let Some(val) = self.raise(&mut frame, condition) {
+                            return Ok(val);
+                        }
+                    } else if !Self::is_truthy(&condition) {
+                        frame.ip = target;
+                    }
+                }
+                Opcode::GetGlobal => {
+                    let idx = frame.read_u16();
+                    let val = match self.globals[idx] {
+                        Some(ref val) => Cell::get(val),
+                        None => self.unknown_global(idx),
+                    };
+                    self.push(Self::locate(val, &frame, op_ip));
+                }
+                Opcode::SetGlobal => {
+                    let idx = frame.read_u16();
+                    let val = self.peek()?;
+                    match self.globals[idx] {
+                        Some(ref mut slot) => Cell::set(slot, val),
+                        None => self.globals[idx] = Some(val),
+                    }
+                }
+                Opcode::DefineGlobal => {
+                    let idx = frame.read_u16();
+                    self.globals[idx] = Some(self.peek()?);
+                }
+                Opcode::CaptureGlobal => {
+                    let idx = frame.read_u16();
+                    let unknown = self.unknown_global(idx);
+                    let slot = self.globals[idx].get_or_insert(unknown);
+                    let cell = Cell::capture(slot);
+                    self.push(cell);
+                }
+                Opcode::GetLocal => {
+                    let idx = frame.read_u8();
+                    self.push(Cell::get(&self.stack[frame.base_pointer + idx]));
+                }
+                Opcode::SetLocal => {
+                    let idx = frame.read_u8();
+                    let val = self.peek()?;
+                    Cell::set(&mut self.stack[frame.base_pointer + idx], val);
+                }
+                Opcode::DefineLocal => {
+                    let idx = frame.read_u8();
+                    self.stack[frame.base_pointer + idx] = self.peek()?;
+                }
+                Opcode::CaptureLocal => {
+                    let idx = frame.read_u8();
+                    let cell = Cell::capture(&mut self.stack[frame.base_pointer + idx]);
+                    self.push(cell);
+                }
+                Opcode::GetFree => {
+                    let idx = frame.read_u8();
+                    let val = Cell::get(&frame.closure().free.borrow()[idx]);
+                    self.push(val);
+                }
+                Opcode::SetFree => {
+                    let idx = frame.read_u8();
+                    let val = self.peek()?;
+                    Cell::set(&mut frame.closure().free.borrow_mut()[idx], val);
+                }
+                Opcode::CaptureFree => {
+                    let idx = frame.read_u8();
+                    let cell = Rc::clone(&frame.closure().free.borrow()[idx]);
+                    self.push(cell);
+                }
+                Opcode::CurrentClosure => {
+                    self.push(Rc::clone(&frame.closure));
+                }
+                Opcode::Array => {
+                    let len = frame.read_u16();
+                    let elements = self.stack.split_off(self.stack.len() - len);
+                    self.push(Rc::new(Array::new(elements)));
+                }
+                Opcode::Interpolate => {
+                    let len = frame.read_u16();
+                    let parts = self.stack.split_off(self.stack.len() - len);
+                    match parts.iter().find(|part| part.get_type() == Type::Error) {
+                        Some(error) => self.push(Rc::clone(error)),
+                        None => {
+                            let string: String =
+                                parts.iter().map(|part| part.to_string()).collect();
+                            self.push(Rc::new(Str::new(string)));
+                        }
+                    }
+                }
+                Opcode::Hash => {
+                    let len = frame.read_u16();
+                    let mut elements = self.stack.split_off(self.stack.len() - len).into_iter();
+                    let mut pairs = Vec::with_capacity(len / 2);
+                    while let (Some(key), Some(value)) = (elements.next(), elements.next()) {
+                        pairs.push((key, value));
+                    }
+                    let hash = self.evaluator.build_hash(pairs);
+                    self.push(Self::locate(hash, &frame, op_ip));
+                }
+                Opcode::SetIndex => {
+                    let index = self.pop()?;
+                    let left = self.pop()?;
+                    let value = self.pop()?;
+                    let result = if left.get_type() == Type::Error {
+                        left
+                    } else if index.get_type() == Type::Error {
+                        index
+                    } else {
+                        self.evaluator
+                            .eval_index_assign(left, index, value)
+                            .unwrap_or_else(|| Rc::new(Null))
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Index => {
+                    let index = self.pop()?;
+                    let left = self.pop()?;
+                    let result = if left.get_type() == Type::Error {
+                        left
+                    } else if index.get_type() == Type::Error {
+                        index
+                    } else {
+                        self.evaluator
+                            .eval_index_expr(left, index)
+                            .unwrap_or_else(|| Rc::new(Null))
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Call => {
+                    let num_args = frame.read_u8();
+                    // builtins may run callbacks on top of this frame
+                    self.frames.push(frame);
+                    let called = self.call(num_args, false);
+                    frame = self.frames.pop().ok_or("no frame to execute")?;
+                    match called? {
+                        Some(callee) => self.frames.push(mem::replace(&mut frame, callee)),
+                        None => {
+                            let result = Self::locate(self.pop()?, &frame, op_ip);
+                            self.push(result);
+                        }
+                    }
+                }
+                Opcode::TailCall => {
+                    let num_args = frame.read_u8();
+                    self.frames.push(frame);
+                    let called = self.call(num_args, true);
+                    frame = self.frames.pop().ok_or("no frame to execute")?;
+                    match called? {
+                        // the callee takes the place of the frame returning its result
+                        Some(mut callee) => {
+                            self.stack
+                                .drain(frame.base_pointer - 1..callee.base_pointer - 1);
+                            callee.base_pointer = frame.base_pointer;
+                            frame = callee;
+                        }
+                        None => {
+                            let result = Self::locate(self.pop()?, &frame, op_ip);
+                            self.push(result);
+                        }
+                    }
+                }
+                Opcode::ReturnValue | Opcode::Return => {
+                    let val = if op == Opcode::ReturnValue {
+                        self.pop()?
+                    } else {
+                        Rc::new(Null)
+                    };
+                    let returned = if val.get_type() == Type::Error {
+                        self.raise(&mut frame, val)
+                    } else {
+                        self.return_from(&mut frame, val)
+                    };
+                    if let Some(val) = returned {
+                        return Ok(val);
+                    }
+                }
+                Opcode::Closure => {
+                    let idx = frame.read_u16();
+                    let num_free = frame.read_u8();
+                    let function = self.constants[idx]
+                        .as_any()
+                        .downcast_ref::<CompiledFunction>()
+                        .ok_or(format!("not a function: {}", self.constants[idx]))?
+                        .clone();
+                    let free = self.stack.split_off(self.stack.len() - num_free);
+                    self.push(Rc::new(Closure::new(Rc::new(function), free)));
+                }
+                Opcode::Import => {
+                    let idx = frame.read_u16();
+                    let slot = frame.read_u16();
+                    if let Some(ref module) = self.globals[slot] {
+                        self.push(Rc::clone(module));
+                        continue;
+                    }
+                    let function = self.constants[idx]
+                        .as_any()
+                        .downcast_ref::<CompiledFunction>()
+                        .ok_or(format!("not a module: {}", self.constants[idx]))?
+                        .clone();
+                    let closure: Rc<dyn Object> = Rc::new(Closure::new(Rc::new(function), vec![]));
+                    self.push(Rc::clone(&closure));
+                    let callee = Frame::new(closure, self.stack.len()).ok_or("not a module")?;
+                    self.frames.push(mem::replace(&mut frame, callee));
+                }
+                Opcode::Module => {
+                    let len = frame.read_u16();
+                    let mut members = self.stack.split_off(self.stack.len() - len * 2).into_iter();
+                    let name = self.pop()?;
+                    let mut env = Environment::default();
+                    while let (Some(member), Some(cell)) = (members.next(), members.next()) {
+                        env.store.insert(member.to_string(), cell);
+                    }
+                    let module = Module::new(name.to_string(), Rc::new(RefCell::new(env)));
+                    self.push(Rc::new(module));
+                }
+                Opcode::Member | Opcode::Method => {
+                    let idx = frame.read_u16();
+                    let object = self.pop()?;
+                    let result = if object.get_type() == Type::Error {
+                        object
+                    } else {
+                        let member = self.constants[idx].to_string();
+                        let called = op == Opcode::Method;
+                        self.evaluator.eval_member_expr(object, &member, called)
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Match => {
+                    let idx = frame.read_u16();
+                    let pattern = Rc::clone(&self.constants[idx]);
+                    let value = self.pop()?;
+                    let pattern = pattern
+                        .as_any()
+                        .downcast_ref::<MatchPattern>()
+                        .ok_or("match operand is not a pattern")?;
+                    match self.evaluator.match_pattern(&pattern.pattern, &value) {
+                        Some(values) => {
+                            // the first binding ends up on top, defined first
+                            self.stack.extend(values.into_iter().rev());
+                            self.push(Rc::new(Boolean::new(true)));
+                        }
+                        None => self.push(Rc::new(Boolean::new(false))),
+                    }
+                }
+                Opcode::NoMatch => {
+                    let value = self.pop()?;
+                    let error = self.evaluator.no_match(&value);
+                    self.push(Self::locate(error, &frame, op_ip));
+                }
+                Opcode::Struct => {
+                    let len = frame.read_u16();
+                    let mut elements = self.stack.split_off(self.stack.len() - len).into_iter();
+                    let def = self.pop()?;
+                    let mut fields = Vec::with_capacity(len / 2);
+                    while let (Some(name), Some(value)) = (elements.next(), elements.next()) {
+                        fields.push((name.to_string(), value));
+                    }
+                    let error = std::iter::once(&def)
+                        .chain(fields.iter().map(|(_, value)| value))
+                        .find(|value| value.get_type() == Type::Error)
+                        .cloned();
+                    let result = match error {
+                        Some(error) => error,
+                        None => self.evaluator.eval_struct(def, fields),
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Impl => {
+                    let len = frame.read_u16();
+                    let mut elements = self.stack.split_off(self.stack.len() - len).into_iter();
+                    let def = self.pop()?;
+                    let mut methods = Vec::with_capacity(len / 2);
+                    while let (Some(name), Some(function)) = (elements.next(), elements.next()) {
+                        methods.push((name.to_string(), function));
+                    }
+                    let result = if def.get_type() == Type::Error {
+                        def
+                    } else {
+                        self.evaluator.eval_impl(def, methods)
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::SetMember => {
+                    let idx = frame.read_u16();
+                    let object = self.pop()?;
+                    let value = self.pop()?;
+                    let result = if object.get_type() == Type::Error {
+                        object
+                    } else {
+                        let member = self.constants[idx].to_string();
+                        self.evaluator.eval_member_assign(object, &member, value)
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Range => {
+                    let end = self.pop()?;
+                    let start = self.pop()?;
+                    let result = if start.get_type() == Type::Error {
+                        start
+                    } else if end.get_type() == Type::Error {
+                        end
+                    } else {
+                        self.evaluator.eval_range(start, end)
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::Iterable => {
+                    let iterable = self.pop()?;
+                    let result = if iterable.get_type() == Type::Error {
+                        iterable
+                    } else {
+                        self.evaluator.iteration_items(iterable)
+                    };
+                    self.push(Self::locate(result, &frame, op_ip));
+                }
+                Opcode::SetupTry => {
+                    let ip = frame.read_u16();
+                    self.handlers.push(Handler {
+                        frame_depth: self.frames.len(),
+                        stack_len: self.stack.len(),
+                        ip,
+                    });
+                }
+                Opcode::PopTry => {
+                    let val = self.peek()?;
+                    if val.get_type() == Type::Error {
+                        self.pop()?;
+                        if let Some(val) = self.raise(&mut frame, val) {
+                            return Ok(val);
+                        }
+                    } else {
+                        self.handlers.pop();
+                    }
+                }
+                Opcode::Throw => {
+                    let val = self.pop()?;
+                    let error = if val.get_type() == Type::Error {
+                        val
+                    } else {
+                        self.evaluator.thrown_error(val)
+                    };
+                    let error = Self::locate(error, &frame, op_ip);
+                    if let Some(val) = self.raise(&mut frame, error) {
+                        return Ok(val);
+                    }
+                }
+            }
+        }
+        Ok(self.stack.last().cloned().unwrap_or_else(|| Rc::new(Null)))
+    }
+
+    /// Jumps to the innermost `catch` of `frame` with `error` bound, or leaves
+    /// `frame` like `return_from` when it has no active `try`.
+    fn raise(&mut self, frame: &mut Frame, error: Rc<dyn Object>) -> Option<Rc<dyn Object>> {
+        match self.handlers.last() {
+            Some(handler) if handler.frame_depth == self.frames.len() => {
+                let handler = self.handlers.pop().unwrap();
+                self.stack.truncate(handler.stack_len);
+                let caught = match error.as_any().downcast_ref::<Error>() {
+                    Some(error) => self.evaluator.error_value(error),
+                    None => error,
+                };
+                self.push(caught);
+                frame.ip = handler.ip;
+                None
+            }
+            _ => self.return_from(frame, error),
+        }
+    }
+
+    /// Resumes the caller of `frame` with `val` pushed as the call result, or
+    /// gives `val` back when `frame` is the main frame or a callback.
+    fn return_from(&mut self, frame: &mut Frame, val: Rc<dyn Object>) -> Option<Rc<dyn Object>> {
+        if self.frames.len() == self.base {
+            if self.base == 0 {
+                return Some(val);
+            }
+            self.stack.truncate(frame.base_pointer - 1);
+            let base = self.base;
+            self.handlers.retain(|handler| handler.frame_depth < base);
+            let name = frame.closure().function.name.clone();
+            return Some(Error::trace(val, name, None));
+        }
+        let caller = match self.frames.pop() {
+            Some(caller) => caller,
+            None => return Some(val),
+        };
+        let val = if val.get_type() == Type::Error {
+            // the call instruction is the opcode plus its one byte operand
+            let location = caller.closure().function.location_at(caller.ip - 2);
+            Error::trace(val, frame.closure().function.name.clone(), location)
+        } else {
+            val
+        };
+        self.stack.truncate(frame.base_pointer - 1);
+        let depth = self.frames.len();
+        self.handlers.retain(|handler| handler.frame_depth <= depth);
+        *frame = caller;
+        self.push(val);
+        None
+    }
+
+    /// Calls the function under the `num_args` arguments on the stack: the
+    /// frame to run for a closure, otherwise the result is pushed. A `tail`
+    /// call replaces the calling frame, so it doesn't count toward the depth.
+    fn call(&mut self, num_args: usize, tail: bool) -> Result<Option<Frame>, String> {
+        let callee_pos = self
+            .stack
+            .len()
+            .checked_sub(num_args + 1)
+            .ok_or("stack underflow")?;
+        let callee = Rc::clone(&self.stack[callee_pos]);
+        if let Some(method) = callee.as_any().downcast_ref::<BoundMethod>() {
+            self.stack[callee_pos] = Rc::clone(&method.function);
+            self.stack
+                .insert(callee_pos + 1, Rc::clone(&method.receiver));
+            return self.call(num_args + 1, tail);
+        }
+        if let Some(closure) = callee.as_any().downcast_ref::<Closure>() {
+            let function = &closure.function;
+            if function.num_parameters != num_args {
+                self.stack.truncate(callee_pos);
+                self.push(Rc::new(Error::new(format!(
+                    "wrong number of arguments: want={}, got={}",
+                    function.num_parameters, num_args
+                ))));
+                return Ok(None);
+            }
+            // the frames under a call are main and the functions running
+            if !tail && self.frames.len() > self.max_depth {
+                self.stack.truncate(callee_pos);
+                self.push(Rc::new(Error::new("stack overflow".to_string())));
+                return Ok(None);
+            }
+            for _ in num_args..function.num_locals {
+                self.push(Rc::new(Null));
+            }
+            return Ok(Frame::new(callee, callee_pos + 1));
+        }
+        let args = self.stack.split_off(callee_pos + 1);
+        self.stack.truncate(callee_pos);
+        let built_in = callee.as_any().downcast_ref::<BuiltIn>();
+        let accepts_errors = matches!(built_in, Some(built_in) if built_in.accepts_errors);
+        let error = args
+            .iter()
+            .find(|arg| arg.get_type() == Type::Error)
+            .cloned();
+        let constructor = callee.as_any().downcast_ref::<Constructor>();
+        let result: Rc<dyn Object> = match (error, built_in, constructor) {
+            (Some(error), _, _) if !accepts_errors => error,
+            (_, Some(built_in), _) => built_in.call(&args, self),
+            (_, _, Some(constructor)) => self.evaluator.eval_variant(constructor, args),
+            _ if callee.get_type() == Type::Error => Rc::clone(&callee),
+            _ => Rc::new(Error::new(format!("not a function {}", callee.get_type()))),
+        };
+        self.push(result);
+        Ok(None)
+    }
+
+    fn execute_binary_operation(
+        &self,
+        op: Opcode,
+        left: Rc<dyn Object>,
+        right: Rc<dyn Object>,
+    ) -> Rc<dyn Object> {
+        if right.get_type() == Type::Error {
+            return right;
+        }
+        if left.get_type() == Type::Error {
+            return left;
+        }
+        if let (Some(l), Some(r)) = (
+            left.as_any().downcast_ref::<Integer>(),
+            right.as_any().downcast_ref::<Integer>(),
+        ) {
+            let (l, r) = (l.val, r.val);
+            // on overflow the evaluator promotes to a bigint
+            let val = match op {
+                Opcode::Add => l.checked_add(r),
+                Opcode::Sub => l.checked_sub(r),
+                Opcode::Mul => l.checked_mul(r),
+                Opcode::Eq => return Rc::new(Boolean::new(l == r)),
+                Opcode::Diff => return Rc::new(Boolean::new(l != r)),
+                Opcode::Gt => return Rc::new(Boolean::new(l > r)),
+                Opcode::Gte => return Rc::new(Boolean::new(l >= r)),
+                Opcode::Lt => return Rc::new(Boolean::new(l < r)),
+                Opcode::Lte => return Rc::new(Boolean::new(l <= r)),
+                _ => None,
+            };
+            if let Some(val) = val {
+                return Rc::new(Integer::new(val));
+            }
+        }
+        self.evaluator
+            .eval_infix_expr(left, right, op.operator())
+            .unwrap_or_else(|| Rc::new(Null))
+    }
+
+    fn unknown_global(&self, idx: usize) -> Rc<dyn Object> {
+        Rc::new(Error::new(format!(
+            "unknown word '{}'",
+            self.global_names[idx]
+        )))
+    }
+
+    fn locate(obj: Rc<dyn Object>, frame: &Frame, ip: usize) -> Rc<dyn Object> {
+        Error::locate(obj, || frame.closure().function.location_at(ip))
+    }
+
+    fn is_truthy(obj: &Rc<dyn Object>) -> bool {
+        matches!(obj.as_any().downcast_ref::<Boolean>(), Some(boolean) if boolean.val)
+    }
+
+    fn push(&mut self, obj: Rc<dyn Object>) {
+        self.stack.push(obj)
+    }
+
+    fn pop(&mut self) -> Result<Rc<dyn Object>, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| "stack underflow".to_string())
+    }
+
+    fn peek(&self) -> Result<Rc<dyn Object>, String> {
+        self.stack
+            .last()
+            .cloned()
+            .ok_or_else(|| "stack underflow".to_string())
+    }
+}
+
+impl Caller for Vm {
+    fn call_function(
+        &mut self,
+        function: Rc<dyn Object>,
+        args: Vec<Rc<dyn Object>>,
+    ) -> Rc<dyn Object> {
+        let num_args = args.len();
+        self.push(function);
+        self.stack.extend(args);
+        let result = match self.call(num_args, false) {
+            Ok(Some(frame)) => {
+                let base = mem::replace(&mut self.base, self.frames.len());
+                let result = self.execute(frame);
+                self.base = base;
+                result
+            }
+            Ok(None) => self.pop(),
+            Err(err) => Err(err),
+        };
+        result.unwrap_or_else(|err| Rc::new(Error::new(err)))
+    }
+}
@@ -0,0 +1,134 @@
+use std::{fs, process::Command};
+
+fn run(name: &str, source: &str, args: &[&str]) -> (Option<i32>, String) {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_bzr"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.code(), stderr)
+}
+
+#[test]
+fn test_runtime_error_exit_code() {
+    let source = "fn f(x) {\n    ret x + \"a\";\n}\nputs(\"\");\nf(1);\nputs(\"unreachable\");\n";
+    for args in [&[][..], &["--vm"][..]] {
+        let (code, stderr) = run("bzr_runtime_error.bzr", source, args);
+        assert_eq!(Some(2), code);
+        assert!(
+            stderr.contains(":2:11: incompatible types str and int"),
+            "{}",
+            stderr
+        );
+        assert!(stderr.contains("stack trace:\n    f at "), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_script_args_env_and_exit() {
+    let path = std::env::temp_dir().join("bzr_script_args.bzr");
+    let source = "puts(join(args(), \",\"), \" \", env_get(\"BZR_CLI_TEST\"));\nenv_set(\"BZR_CLI_TEST\", \"b\");\nputs(\" \", env_get(\"BZR_CLI_TEST\"));\nexit(len(args()));\nputs(\"unreachable\");\n";
+    fs::write(&path, source).unwrap();
+    for flags in [&[][..], &["--vm"][..]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_bzr"))
+            .args(flags)
+            .arg(&path)
+            .args(["one", "two words", "--vm"])
+            .env("BZR_CLI_TEST", "a")
+            .output()
+            .unwrap();
+        assert_eq!(Some(3), output.status.code());
+        assert_eq!(
+            "one,two words,--vm a b",
+            String::from_utf8(output.stdout).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_compile_error_exit_code() {
+    let (code, stderr) = run("bzr_type_error.bzr", "let a int = \"a\" + \"b\";\n", &[]);
+    assert_eq!(Some(1), code);
+    assert!(
+        stderr.contains("'a' declared as int, got str"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_error_in_imported_file() {
+    let path = std::env::temp_dir().join("bzr_imported_error.bzr");
+    fs::write(&path, "let ok = 1;\nthrow \"broken module\";\n").unwrap();
+    for args in [&[][..], &["--vm"][..]] {
+        let (code, stderr) = run("bzr_importer.bzr", "import bzr_imported_error;\n", args);
+        assert_eq!(Some(2), code);
+        assert!(
+            stderr.contains(":2:1: broken module\nthrow \"broken module\";"),
+            "{}",
+            stderr
+        );
+        assert!(
+            stderr.contains("<module bzr_imported_error.bzr> at "),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_fmt_check() {
+    let (code, stderr) = run(
+        "bzr_fmt_unformatted.bzr",
+        "let a = [1,2];\n",
+        &["fmt", "--check"],
+    );
+    assert_eq!(Some(1), code);
+    assert!(
+        stderr.contains("bzr_fmt_unformatted.bzr is not formatted"),
+        "{}",
+        stderr
+    );
+
+    let (code, stderr) = run(
+        "bzr_fmt_formatted.bzr",
+        "let a = [1, 2];\n",
+        &["fmt", "--check"],
+    );
+    assert_eq!(Some(0), code, "{}", stderr);
+}
+
+#[test]
+fn test_fmt_rewrites_file() {
+    let (code, _) = run(
+        "bzr_fmt_rewrite.bzr",
+        "fn f(x int) int{ret x*2;}\n",
+        &["fmt"],
+    );
+    assert_eq!(Some(0), code);
+    let path = std::env::temp_dir().join("bzr_fmt_rewrite.bzr");
+    let formatted = fs::read_to_string(path).unwrap();
+    assert_eq!("fn f(x int) int {\n    ret x * 2;\n}\n", formatted);
+}
+
+#[test]
+fn test_stack_overflow_exit_code() {
+    let source = "fn deep(n int) int {\n    if n == 0 {\n        ret 0;\n    }\n    ret 1 + deep(n - 1);\n}\ndeep(1000000);\n";
+    for args in [&[][..], &["--vm"][..], &["--max-depth", "50"][..]] {
+        let (code, stderr) = run("bzr_stack_overflow.bzr", source, args);
+        assert_eq!(Some(2), code);
+        assert!(stderr.contains(":5:13: stack overflow"), "{}", stderr);
+        assert!(stderr.contains("    deep at "), "{}", stderr);
+        assert!(stderr.contains(" more times\n    main at "), "{}", stderr);
+    }
+    let (code, stderr) = run("bzr_stack_overflow.bzr", source, &["--max-depth", "lots"]);
+    assert_eq!(Some(1), code);
+    assert!(
+        stderr.contains("--max-depth needs a number of calls"),
+        "{}",
+        stderr
+    );
+}
@@ -1,12 +1,12 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, thread};
 
 use bzr::{
     ast::expression::Node,
     evaluator::Evaluator,
     lexer::Lexer,
     object::{
-        array::Array, boolean::Boolean, environment::Environment, function::Function,
-        integer::Integer, string::Str, Object,
+        array::Array, boolean::Boolean, environment::Environment, error::Error, function::Function,
+        hash::Hash, integer::Integer, string::Str, Object,
     },
     parser::Parser,
 };
@@ -223,6 +223,224 @@ fn test_errors() {
     }
 }
 
+#[test]
+fn test_error_report() {
+    let source = "let a = 1;\nlet b = \"b\";\n  a + b;\nputs(a);";
+    let evaluated = test_eval(source.to_string());
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<bzr::object::error::Error>()
+        .unwrap();
+    assert_eq!(
+        "foo.bzr:3:5: incompatible types str and int\n  a + b;\n    ^",
+        error.report(source)
+    );
+}
+
+#[test]
+fn test_error_stack_trace() {
+    let source = "fn inner(x) {\n    ret x + \"a\";\n}\nfn outer(y) {\n    ret inner(y) + 1;\n}\nouter(1);\nputs(\"unreachable\");";
+    let evaluated = test_eval(source.to_string());
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<bzr::object::error::Error>()
+        .unwrap();
+    let trace: Vec<String> = error
+        .trace
+        .iter()
+        .map(|entry| format!("{} {}", entry.function, entry.location.as_ref().unwrap()))
+        .collect();
+    assert_eq!(vec!["inner foo.bzr:5:9", "outer foo.bzr:7:1"], trace);
+    assert!(error
+        .report(source)
+        .ends_with("stack trace:\n    inner at foo.bzr:2:11\n    outer at foo.bzr:5:9\n    main at foo.bzr:7:1"));
+}
+
+#[test]
+fn test_empty_blocks_and_early_return() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push(("if false { 1; }".to_string(), "null"));
+    tests.push(("fn f() {} f();".to_string(), "null"));
+    tests.push(("while false {}".to_string(), "null"));
+    tests.push((
+        "fn f() int { if true { ret 1; } ret 2; } f();".to_string(),
+        "1",
+    ));
+    tests.push((
+        "fn f() int { var i = 0; while true { if i == 3 { ret i; } i = i + 1; } ret 99; } f();"
+            .to_string(),
+        "3",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_try_catch() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push(("try { 1 + 2 } catch e { 0 }".to_string(), "3"));
+    tests.push((
+        "try { 10 / 0; 1 } catch e { e[\"message\"] }".to_string(),
+        "division by zero",
+    ));
+    tests.push((
+        "try { [1][to_int(\"x\")] } catch e { e[\"message\"] }".to_string(),
+        "invalid value to parse int: x",
+    ));
+    tests.push((
+        "try { throw \"boom\"; } catch e { e[\"message\"] }".to_string(),
+        "boom",
+    ));
+    tests.push((
+        "try { throw {\"message\": \"m\", \"code\": 1}; } catch e { e[\"message\"] }".to_string(),
+        "m",
+    ));
+    tests.push((
+        "try { throw {\"message\": \"m\", \"code\": 3}; } catch e { e[\"code\"] }".to_string(),
+        "3",
+    ));
+    tests.push((
+        "fn f(n int) int { if n == 0 { throw \"bottom\"; } ret f(n - 1); } try { f(3) } catch e { e[\"message\"] }"
+            .to_string(),
+        "bottom",
+    ));
+    tests.push((
+        "try { try { throw \"a\"; } catch x { throw x[\"message\"] + \"b\"; } } catch y { y[\"message\"] }"
+            .to_string(),
+        "ab",
+    ));
+    tests.push((
+        "var e = 1; try { throw 5; } catch e { e; } e".to_string(),
+        "1",
+    ));
+    tests.push(("10 % 0".to_string(), "division by zero"));
+    tests.push((
+        "try { [1][5] } catch e { e[\"message\"] }".to_string(),
+        "invalid array index 5",
+    ));
+    tests.push((
+        "try { [1][-1] } catch e { e[\"message\"] }".to_string(),
+        "invalid array index -1",
+    ));
+    tests.push((
+        "try { \"abc\"[9] } catch e { e[\"message\"] }".to_string(),
+        "invalid str index 9",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_caught_error_location() {
+    let source =
+        "let a = 1;\ntry {\n  a / 0;\n} catch e { [e[\"location\"], e[\"line\"], e[\"column\"]] }";
+    let evaluated = test_eval(source.to_string());
+    assert_eq!("[foo.bzr:3:5, 3, 5]", evaluated.to_string());
+
+    let source = "try {\n  try {\n    1 / 0;\n  } catch e {\n    throw e;\n  }\n} catch e { [e[\"location\"], e[\"message\"]] }";
+    let evaluated = test_eval(source.to_string());
+    assert_eq!("[foo.bzr:3:7, division by zero]", evaluated.to_string());
+}
+
+#[test]
+fn test_for_loops() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push((
+        "var s = 0; for i in 0..5 { s = s + i; } s".to_string(),
+        "10",
+    ));
+    tests.push(("var s = 0; for i in 3..1 { s = s + 1; } s".to_string(), "0"));
+    tests.push(("let r = 1..4; r".to_string(), "[1, 2, 3]"));
+    tests.push((
+        "var s = 0; for x in [4, 5, 6] { s = s + x; } s".to_string(),
+        "15",
+    ));
+    tests.push((
+        "var s = \"\"; for ch in \"abc\" { s = ch + s; } s".to_string(),
+        "cba",
+    ));
+    tests.push((
+        "let a = [1, 2]; for x in a { append(a, x); } a".to_string(),
+        "[1, 2, 1, 2]",
+    ));
+    tests.push((
+        "var s = 0; for i in 0..10 { if i == 2 { continue; } if i == 5 { break; } s = s + i; } s"
+            .to_string(),
+        "8",
+    ));
+    tests.push((
+        "var i = 0; while true { i = i + 1; if i < 4 { continue; } break; } i".to_string(),
+        "4",
+    ));
+    tests.push(("for i in 0..3 { i * 2 }".to_string(), "4"));
+    tests.push(("let i = 7; for i in 0..3 { i; } i".to_string(), "7"));
+    tests.push((
+        "for i in 0..2 { let x = i; } x".to_string(),
+        "unknown word 'x'",
+    ));
+    tests.push((
+        "fn f() int { for i in 0..9 { if i == 3 { ret i; } } ret -1; } f()".to_string(),
+        "3",
+    ));
+    tests.push(("for i in 5 { }".to_string(), "cannot iterate over int"));
+    tests.push((
+        "for i in 0..true { }".to_string(),
+        "range bounds must be int, got int..bool",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_floats() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push(("3.14".to_string(), "3.14"));
+    tests.push(("1e-9".to_string(), "1e-9"));
+    tests.push(("-0.5 * 2".to_string(), "-1.0"));
+    tests.push(("1 + 0.5".to_string(), "1.5"));
+    tests.push(("7 / 2.0".to_string(), "3.5"));
+    tests.push(("7 / 2".to_string(), "3"));
+    tests.push(("2.0 == 2".to_string(), "true"));
+    tests.push(("1 < 1.5".to_string(), "true"));
+    tests.push(("1.0 / 0".to_string(), "inf"));
+    tests.push(("1.5 & 1".to_string(), "unsupported operation 1.5 & 1.0"));
+    tests.push(("to_str(0.5, \" \", 2.0)".to_string(), "0.5 2.0"));
+    tests.push((
+        "[to_float(3), to_float(\"2.25\"), to_int(-3.9)]".to_string(),
+        "[3.0, 2.25, -3]",
+    ));
+    tests.push((
+        "to_float(\"x\")".to_string(),
+        "invalid value to parse float: x",
+    ));
+    tests.push((
+        "[sqrt(16), pow(2, 10), pow(4, 0.5)]".to_string(),
+        "[4.0, 1024.0, 2.0]",
+    ));
+    tests.push((
+        "[floor(2.7), ceil(2.1), round(2.5), round(-2.5)]".to_string(),
+        "[2, 3, 3, -3]",
+    ));
+    tests.push(("[abs(-3), abs(-2.5)]".to_string(), "[3, 2.5]"));
+    tests.push(("sqrt(-1)".to_string(), "sqrt of negative number -1.0"));
+    tests.push(("floor(1e300)".to_string(), "1e300 does not fit in an int"));
+    tests.push(("sqrt(\"4\")".to_string(), "sqrt expects a number, got str"));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.clone());
+        assert_eq!(expected, evaluated.to_string(), "{}", source)
+    }
+}
+
 #[test]
 fn test_let_statement() {
     let mut tests: Vec<(String, i64)> = Vec::new();
@@ -365,6 +583,135 @@ fn test_closure() {
     }
 }
 
+#[test]
+fn test_anonymous_functions_and_closures() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push((
+        "let add = fn(a int, b int) int { ret a + b; }; add(2, 3)".to_string(),
+        "5",
+    ));
+    tests.push(("fn(x int) int { ret x * 2; }(21)".to_string(), "42"));
+    tests.push((
+        "fn counter() fn { var n = 0; ret fn() int { n = n + 1; ret n; }; }
+        let a = counter(); let b = counter(); a(); a(); [a(), b()]"
+            .to_string(),
+        "[3, 1]",
+    ));
+    tests.push((
+        "fn adder(n int) fn { ret fn(x int) int { ret x + n; }; } adder(5)(10)".to_string(),
+        "15",
+    ));
+    tests.push((
+        "fn pair() array { var n = 0; ret [fn() { n = n + 1; }, fn() int { ret n; }]; }
+        let p = pair(); p[0](); p[0](); p[1]()"
+            .to_string(),
+        "2",
+    ));
+    tests.push((
+        "let fs = []; for i in 0..3 { append(fs, fn() int { ret i; }); }; [fs[0](), fs[2]()]"
+            .to_string(),
+        "[0, 2]",
+    ));
+    tests.push((
+        "fn f() int { let fact = fn(n int) int { if n < 2 { ret 1; } ret n * fact(n - 1); }; ret fact(5); } f()"
+            .to_string(),
+        "120",
+    ));
+    tests.push((
+        "fn apply(f fn, v int) int { ret f(v); } apply(fn(x int) int { ret x * x; }, 9)"
+            .to_string(),
+        "81",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_imports() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push((
+        "import \"examples/geometry.bzr\"; geometry.area(1.0)".to_string(),
+        "3.14159",
+    ));
+    tests.push((
+        "import \"examples/geometry.bzr\" as g; let area = g.area; area(2.0) == g.area(2.0)"
+            .to_string(),
+        "true",
+    ));
+    // the file runs once, both names share its bindings
+    tests.push((
+        "import \"examples/geometry.bzr\" as a; import \"examples/geometry.bzr\" as b;
+        a.area(1.0); b.circumference(1.0); [a.calls, b.calls]"
+            .to_string(),
+        "[2, 2]",
+    ));
+    tests.push((
+        "fn f() float { import \"examples/geometry.bzr\" as g; ret g.pi; } f()".to_string(),
+        "3.14159",
+    ));
+    tests.push((
+        "import \"examples/geometry.bzr\"; geometry.volume".to_string(),
+        "module 'geometry' has no member 'volume'",
+    ));
+    tests.push(("let a = 1; a.b".to_string(), "int has no member 'b'"));
+    tests.push((
+        "import \"examples/missing.bzr\"".to_string(),
+        "cannot import 'examples/missing.bzr': No such file or directory (os error 2)",
+    ));
+    tests.push((
+        "import \"examples/geometry.bzr\" as g; let g = 1;".to_string(),
+        "'g' is already declared with import at foo.bzr:1:35",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_string_escapes_and_interpolation() {
+    let tests = [
+        (r#""line\nnext\t\u{e9}""#, "line\nnext\t\u{e9}"),
+        (r#"let a = 2; let b = 3; "total = ${a + b}""#, "total = 5"),
+        (
+            r#"let name = "bzr"; "${name}${"!" * 1}""#,
+            "incompatible types int and str",
+        ),
+        (
+            r#"fn f(x) { ret x * 2; } "${f(2)} ${f(1.5)} ${true}""#,
+            "4 3.0 true",
+        ),
+        (r#""\${a}""#, "${a}"),
+        (r#""${[1, 2]}${{"k": 1}["k"]}""#, "[1, 2]1"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_import_cycle() {
+    let dir = std::env::temp_dir().join("bzr_import_cycle");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.bzr"), "import b;\nlet x = 1;\n").unwrap();
+    std::fs::write(dir.join("b.bzr"), "import a;\nlet y = 2;\n").unwrap();
+    let main = dir.join("a.bzr").to_string_lossy().to_string();
+    let lexer = Lexer::new(Rc::new("import b;".to_string()), Rc::new(main.clone()));
+    let program: Box<dyn Node> = Parser::new(lexer).parse_program();
+    let env = Rc::new(RefCell::new(Environment::default()));
+    let evaluated = Evaluator::default()
+        .eval(Some(program.as_ref()), env)
+        .unwrap();
+    let b = dir.join("b.bzr").to_string_lossy().to_string();
+    let expected = format!("import cycle: {} -> {} -> {}", main, b, main);
+    assert_eq!(expected, evaluated.to_string());
+}
+
 #[test]
 fn test_string_concatenation() {
     let source = "\"Paulo\" + \" \" + \"Gabriel\"".to_string();
@@ -390,7 +737,7 @@ fn test_built_in() {
 #[test]
 fn test_set_value() {
     let mut tests: Vec<(String, i64)> = Vec::new();
-    tests.push(("let a int = 10; a = 0; a;".to_string(), 0));
+    tests.push(("var a int = 10; a = 0; a;".to_string(), 0));
 
     for (source, expected) in tests {
         let evaluated = test_eval(source);
@@ -400,6 +747,41 @@ fn test_set_value() {
     }
 }
 
+#[test]
+fn test_let_immutability() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push((
+        "let a = 1;\na = 2;".to_string(),
+        "cannot assign to 'a', it was declared with let at foo.bzr:1:5",
+    ));
+    tests.push((
+        "let a = 1;\nlet a = 2;".to_string(),
+        "'a' is already declared with let at foo.bzr:1:5",
+    ));
+    tests.push((
+        "let a = 1; fn f() { a = 3; } f()".to_string(),
+        "cannot assign to 'a', it was declared with let at foo.bzr:1:5",
+    ));
+    tests.push(("var a = 1; var a = 2; a = a + 1; a".to_string(), "3"));
+    tests.push(("let a = 1; if true { let a = 2; a }".to_string(), "2"));
+    tests.push(("let a = 1; if true { let a = 2; } a".to_string(), "1"));
+    tests.push(("var a = 1; if true { a = 2; } a".to_string(), "2"));
+    tests.push(("let a = [1]; append(a, 2); a".to_string(), "[1, 2]"));
+    tests.push((
+        "let x = 1; fn f(x) { x = x + 1; x }; [f(5), x]".to_string(),
+        "[6, 1]",
+    ));
+    tests.push((
+        "var i = 0; while i < 3 { let j = i; i = i + 1; } i".to_string(),
+        "3",
+    ));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
 #[test]
 fn test_eval_array() {
     let source = "[1, 2 * 2, 3 + 3]".to_string();
@@ -426,3 +808,589 @@ fn test_array_index() {
         assert_eq!(expected, value)
     }
 }
+
+#[test]
+fn test_eval_hash() {
+    let source = "let m map = {\"one\": 1, \"two\": 1 + 1, 3: true}; m".to_string();
+    let evaluated = test_eval(source);
+    let evaluated = evaluated.as_any().downcast_ref::<Hash>();
+    assert!(evaluated.is_some(), "Not a map");
+    assert_eq!(evaluated.unwrap().pairs.borrow().len(), 3, "Wrong size");
+}
+
+#[test]
+fn test_hash_index() {
+    let mut tests: Vec<(String, i64)> = Vec::new();
+    tests.push(("let m = {\"a\": 1, \"b\": 2}; m[\"b\"]".to_string(), 2));
+    tests.push(("let m = {1: 10, true: 20}; m[true]".to_string(), 20));
+    tests.push(("let m = {}; m[\"a\"] = 5; m[\"a\"]".to_string(), 5));
+    tests.push((
+        "let m = {\"a\": 1}; m[\"a\"] = m[\"a\"] + 1; m[\"a\"]".to_string(),
+        2,
+    ));
+    tests.push(("let a = [1, 2, 3]; a[1] = 10; a[1]".to_string(), 10));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        let evaluated = evaluated.as_any().downcast_ref::<Integer>().unwrap();
+        let value = evaluated.val;
+        assert_eq!(expected, value)
+    }
+}
+
+#[test]
+fn test_print_values_holding_themselves() {
+    let tests = [
+        ("let m = {}; m[\"self\"] = m; m", "{self: {...}}"),
+        ("let a = [1]; append(a, a); a", "[1, [...]]"),
+        (
+            "struct N { next array }\nlet n = N { next: [] }; append(n.next, n); n",
+            "N { next: [N { ... }] }",
+        ),
+        ("let b = [1]; [b, {\"b\": b}]", "[[1], {b: [1]}]"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_hash_built_in() {
+    let mut tests: Vec<(String, &str)> = Vec::new();
+    tests.push(("keys({\"b\": 2, \"a\": 1})".to_string(), "[a, b]"));
+    tests.push(("values({\"b\": 2, \"a\": 1})".to_string(), "[1, 2]"));
+    tests.push(("has_key({\"a\": 1}, \"a\")".to_string(), "true"));
+    tests.push(("has_key({\"a\": 1}, \"b\")".to_string(), "false"));
+    tests.push(("delete({\"a\": 1, \"b\": 2}, \"a\")".to_string(), "{b: 2}"));
+    tests.push(("len({\"a\": 1, \"b\": 2})".to_string(), "2"));
+    tests.push(("{\"a\": 1}[\"b\"]".to_string(), "null"));
+    tests.push(("{[1]: 1}".to_string(), "unusable as map key: array"));
+
+    for (source, expected) in tests {
+        let evaluated = test_eval(source);
+        assert_eq!(expected, evaluated.to_string())
+    }
+}
+
+#[test]
+fn test_string_built_ins() {
+    let tests = [
+        ("split(\"a,b,,c\", \",\")", "[a, b, , c]"),
+        ("split(\"héllo\", \"\")", "[h, é, l, l, o]"),
+        ("join([\"x\", 1, true], \"-\")", "x-1-true"),
+        ("contains(\"héllo\", \"ll\")", "true"),
+        ("starts_with(\"héllo\", \"hé\")", "true"),
+        ("ends_with(\"abc\", \"b\")", "false"),
+        ("find(\"héllo\", \"l\")", "2"),
+        ("find(\"abc\", \"z\")", "-1"),
+        ("replace_all(\"a.b.c\", \".\", \"::\")", "a::b::c"),
+        ("upper(\"straße\")", "STRASSE"),
+        ("lower(\"ÀB\")", "àb"),
+        ("repeat(\"ab\", 3)", "ababab"),
+        ("chars(\"hé\")", "[h, é]"),
+        ("bytes(\"hé\")", "[104, 195, 169]"),
+        ("pad_left(\"7\", 3, \"0\")", "007"),
+        ("pad_right(\"é\", 3) + \"|\"", "é  |"),
+        ("pad_left(\"héllo\", 2)", "héllo"),
+        (
+            "format(\"{} + {} = {2}, {{ok}}\", 1, 2, 3)",
+            "1 + 2 = 3, {ok}",
+        ),
+        ("slice(\"héllo\", 1, 3)", "él"),
+        ("len(\"héllo\")", "5"),
+        ("\"héllo\"[4]", "o"),
+        ("\"héllo\"[5]", "invalid str index 5"),
+        (
+            "slice(\"abc\", 2, 1)",
+            "slice 2..1 out of bounds for length 3",
+        ),
+        (
+            "slice(\"é\", 0, 2)",
+            "slice 0..2 out of bounds for length 1",
+        ),
+        (
+            "slice([1, 2], -1, 1)",
+            "slice -1..1 out of bounds for length 2",
+        ),
+        (
+            "repeat(\"a\", -1)",
+            "repeat count must not be negative, got -1",
+        ),
+        (
+            "repeat(\"ab\", 1 << 40)",
+            "repeat result of 1099511627776 x 2 bytes is too long",
+        ),
+        (
+            "pad_left(\"a\", 3, \"ab\")",
+            "pad_left fill must be one char, got 'ab'",
+        ),
+        (
+            "split(1, \",\")",
+            "split expects str as argument 1, got int",
+        ),
+        ("upper()", "wrong number of arguments, upper needs 1 args"),
+        (
+            "replace_all(\"a\", \"\", \"b\")",
+            "replace_all can't replace an empty str",
+        ),
+        (
+            "format(\"{}\")",
+            "format placeholder 0 has no argument, got 0",
+        ),
+        ("format(\"{x}\", 1)", "invalid placeholder '{x}' in format"),
+        ("format(\"a\", 1)", "format argument 0 is never used"),
+        ("format(\"a {\")", "unclosed '{' in format"),
+        ("format(\"}\")", "unmatched '}' in format, use '}}'"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_array_built_ins() {
+    let tests = [
+        ("map([1, 2, 3], fn(x int) int { ret x * x; })", "[1, 4, 9]"),
+        ("map([1, 2], to_str)", "[1, 2]"),
+        ("filter([1, 2, 3, 4], fn(x int) bool { ret x % 2 == 0; })", "[2, 4]"),
+        ("reduce([1, 2, 3], fn(a int, b int) int { ret a + b; })", "6"),
+        ("reduce([], fn(a int, b int) int { ret a + b; }, 10)", "10"),
+        ("find([1, 2, 3], fn(x int) bool { ret x > 1; })", "2"),
+        ("find([1], fn(x int) bool { ret x > 1; })", "null"),
+        ("find(\"banana\", \"na\")", "2"),
+        ("any([1, 2], fn(x int) bool { ret x > 1; })", "true"),
+        ("all([1, 2], fn(x int) bool { ret x > 1; })", "false"),
+        ("all([], fn(x int) bool { ret false; })", "true"),
+        ("sort([3, 1.5, 2])", "[1.5, 2, 3]"),
+        ("sort([\"b\", \"é\", \"a\"])", "[a, b, é]"),
+        ("sort([1, 3, 2], fn(a int, b int) int { ret b - a; })", "[3, 2, 1]"),
+        ("sort([[2, \"a\"], [1, \"b\"], [2, \"c\"]], fn(a array, b array) int { ret a[0] - b[0]; })", "[[1, b], [2, a], [2, c]]"),
+        ("let a = [2, 1]; sort(a); a", "[2, 1]"),
+        ("reverse([1, 2, 3])", "[3, 2, 1]"),
+        ("reverse(\"héllo\")", "olléh"),
+        ("zip([1, 2, 3], [\"a\", \"b\"])", "[[1, a], [2, b]]"),
+        ("enumerate([\"a\", \"b\"])", "[[0, a], [1, b]]"),
+        ("var n = 0; map([1, 2], fn(x int) int { n = n + x; ret x; }); n", "3"),
+        ("map([1, 0], fn(x int) int { ret 1 / x; })", "division by zero"),
+        ("try { map([0], fn(x int) int { throw \"bad\"; }) } catch e { e[\"message\"] }", "bad"),
+        ("map([1], fn(a int, b int) int { ret a; })", "wrong number of arguments: want=2, got=1"),
+        ("map(1, to_str)", "map expects array as argument 1, got int"),
+        ("map([1], 1)", "map expects fn as argument 2, got int"),
+        ("filter([1], fn(x int) int { ret x; })", "filter callback must return bool, got int"),
+        ("reduce([], fn(a int, b int) int { ret a; })", "reduce of an empty array needs an initial value"),
+        ("sort([1, \"a\"])", "sort can't compare int and str"),
+        ("sort([1, 2], fn(a int, b int) bool { ret true; })", "sort comparator must return int, got bool"),
+        ("zip([1], 2)", "zip expects array as argument 2, got int"),
+        ("reverse(1)", "reverse expects array or str as argument 1, got int"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_process_built_ins() {
+    let tests = [
+        ("args()", "[]"),
+        ("env_get(\"BZR_EVALUATOR_UNSET\")", "null"),
+        (
+            "if env_get(\"BZR_EVALUATOR_UNSET\") == \"1\" { \"debug\" } else { \"quiet\" }",
+            "quiet",
+        ),
+        (
+            "let v = env_get(\"BZR_EVALUATOR_UNSET\"); [v != \"1\", v == v, 1 == v]",
+            "[true, true, false]",
+        ),
+        (
+            "env_set(\"BZR_EVALUATOR_SET\", \"é 1\"); env_get(\"BZR_EVALUATOR_SET\")",
+            "é 1",
+        ),
+        ("env_set(\"\", \"a\")", "env_set invalid variable name ''"),
+        (
+            "env_set(\"a=b\", \"a\")",
+            "env_set invalid variable name 'a=b'",
+        ),
+        (
+            "env_set(\"a\", \"\\0\")",
+            "env_set value of 'a' can't contain '\\0'",
+        ),
+        ("exit(\"1\")", "exit expects int as argument 1, got str"),
+        ("exit(1 << 40)", "exit code 1099511627776 is out of range"),
+        ("args(1)", "wrong number of arguments, args needs 0 args"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_file_built_ins() {
+    let dir = std::env::temp_dir().join(format!("bzr_fs_test_{}", std::process::id()));
+    let dir = dir.to_str().unwrap().to_string();
+    let tests = [
+        (format!("exists(\"{}\")", dir), "false"),
+        (format!("mkdir(\"{}/sub\")", dir), "null"),
+        (
+            format!("write_file(\"{}/sub/a.txt\", \"one\\r\\ntwo\\n\")", dir),
+            "null",
+        ),
+        (
+            format!("append_file(\"{}/sub/a.txt\", \"three\")", dir),
+            "null",
+        ),
+        (
+            format!("append_file(\"{}/sub/b.txt\", \"new\")", dir),
+            "null",
+        ),
+        (format!("read_file(\"{}/sub/b.txt\")", dir), "new"),
+        (
+            format!("read_lines(\"{}/sub/a.txt\")", dir),
+            "[one, two, three]",
+        ),
+        (format!("list_dir(\"{}/sub\")", dir), "[a.txt, b.txt]"),
+        (format!("remove_file(\"{}/sub/b.txt\")", dir), "null"),
+        (
+            format!(
+                "[exists(\"{0}/sub/a.txt\"), exists(\"{0}/sub/b.txt\")]",
+                dir
+            ),
+            "[true, false]",
+        ),
+        (
+            format!("is_error(remove_file(\"{}/sub/b.txt\"))", dir),
+            "true",
+        ),
+        (format!("is_error(read_file(\"{}/sub\"))", dir), "true"),
+        (format!("is_error(list_dir(\"{}/sub/a.txt\"))", dir), "true"),
+        (
+            "write_file(\"a.txt\", 1)".to_string(),
+            "write_file expects str as argument 2, got int",
+        ),
+        (
+            "read_file()".to_string(),
+            "wrong number of arguments, read_file needs 1 args",
+        ),
+    ];
+    for (source, expected) in &tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(*expected, evaluated.to_string(), "{}", source);
+    }
+    let missing = test_eval(format!("read_file(\"{}/missing.txt\")", dir)).to_string();
+    assert!(
+        missing.starts_with(&format!("read_file '{}/missing.txt': ", dir)),
+        "{}",
+        missing
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_structs() {
+    let point = "struct Point { x int, y int }\n";
+    let tests = [
+        ("let p = Point { x: 1, y: 2 }; p", "Point { x: 1, y: 2 }"),
+        ("Point { y: 2, x: 1 }.y", "2"),
+        ("var p = Point { x: 1, y: 2 }; p.x = p.x + 10; p", "Point { x: 11, y: 2 }"),
+        // values are shared like arrays and maps
+        ("let p = Point { x: 1, y: 2 }; let q = p; q.y = 5; p.y", "5"),
+        (
+            "struct Line { from Point, to Point, name str }\nlet l = Line { from: Point { x: 0, y: 0 }, to: Point { x: 3, y: 4 }, name: \"l\" }; l.to.x = 9; l",
+            "Line { from: Point { x: 0, y: 0 }, to: Point { x: 9, y: 4 }, name: l }",
+        ),
+        ("fn f(p Point) int { ret p.x * p.y; }\nf(Point { x: 3, y: 4 })", "12"),
+        ("let p = Point { x: 1, y: 2 }; if p.x < p.y { \"less\" } else { \"more\" }", "less"),
+        ("Point", "<struct Point>"),
+        ("Point { x: 1, y: \"2\" }", "field 'y' of Point expected int, got str"),
+        ("Point { x: 1 }", "missing field 'y' in Point"),
+        ("Point { x: 1, y: 2, z: 3 }", "Point has no field 'z'"),
+        ("Point { x: 1, x: 1, y: 2 }", "field 'x' of Point is given twice"),
+        ("let p = Point { x: 1, y: 2 }; p.x = 1.5; p", "field 'x' of Point expected int, got float"),
+        ("let p = Point { x: 1, y: 2 }; p.z", "Point has no field 'z'"),
+        ("let n = 1; n { x: 1 }", "int is not a struct"),
+        ("let a = [1]; a.x = 1", "cannot assign to member 'x' of array"),
+        ("let Point = 1;", "'Point' is already declared with struct at foo.bzr:1:8"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(format!("{}{}", point, source));
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_enums_and_match() {
+    let shape = "enum Shape { Circle(int), Rect(int, int), Empty }\n";
+    let tests = [
+        ("Shape.Circle(2)", "Shape.Circle(2)"),
+        ("Shape.Empty", "Shape.Empty"),
+        ("Shape", "<enum Shape>"),
+        ("Shape.Rect", "<variant Shape.Rect>"),
+        (
+            "fn area(s) { ret match s { Shape.Circle(r) => 3 * r * r, Shape.Rect(w, h) => w * h, Shape.Empty => 0 }; };\n[area(Shape.Circle(2)), area(Shape.Rect(2, 5)), area(Shape.Empty)]",
+            "[12, 10, 0]",
+        ),
+        ("match Shape.Rect(3, 3) { Shape.Rect(w, h) if w == h => \"square\", Shape.Rect(_, _) => \"rect\", _ => \"other\" }", "square"),
+        ("match 7 { 0 => \"zero\", 1..10 => \"digit\", _ => \"big\" }", "digit"),
+        ("match -2 { -5..0 => \"neg\", _ => \"other\" }", "neg"),
+        ("match 10 { 1..10 => \"digit\", n => n * 2 }", "20"),
+        ("match \"b\" { \"a\" => 1, \"b\" => 2, _ => 3 }", "2"),
+        ("match 1.5 { 1.5 => true, _ => false }", "true"),
+        ("match false { true => 1, false => 0 }", "0"),
+        // a literal of another type is not an error, it just doesn't match
+        ("match \"1\" { 1 => \"int\", _ => \"other\" }", "other"),
+        ("match [1, 2, 3] { [] => 0, [a] => a, [a, b, ..rest] => a + b + len(rest) }", "4"),
+        ("match [1, 2] { [a, b, c] => 3, [a, ..] => a }", "1"),
+        ("match [[1, 2], 3] { [[a, b], c] => a + b + c, _ => 0 }", "6"),
+        ("match Shape.Circle(1) { Shape.Rect(w, h) => w, Shape.Circle(1) => 100, _ => 0 }", "100"),
+        // bindings live in the arm, the outer `x` is untouched
+        ("let x = 1; let y = match 5 { x => x * 2 }; [x, y]", "[1, 10]"),
+        ("match 5 { n if n > 10 => \"big\", n => { let m = n + 1; m } }", "6"),
+        ("var total = 0; for i in 0..6 { match i { 2 => { continue; } 4 => { break; } n => { total = total + n; } } } total", "4"),
+        ("fn f(s) { match s { Shape.Empty => { ret \"early\"; } _ => {} } ret \"late\"; }\nf(Shape.Empty)", "early"),
+        ("match 3 { 1 => \"one\" }", "no match arm for 3"),
+        ("match 1 / 0 { _ => 1 }", "division by zero"),
+        ("match 5 { n if n / 0 == 1 => 1, _ => 2 }", "division by zero"),
+        ("try { match Shape.Empty { Shape.Circle(r) => r } } catch e { e[\"message\"] }", "no match arm for Shape.Empty"),
+        ("Shape.Circle(1, 2)", "wrong number of arguments to Shape.Circle: want=1, got=2"),
+        ("Shape.Circle(\"big\")", "argument 1 of Shape.Circle expected int, got str"),
+        ("Shape.Square", "Shape has no variant 'Square'"),
+        ("enum Tree { Leaf, Node(Tree, int, Tree) }\nfn sum(t) { ret match t { Tree.Leaf => 0, Tree.Node(l, v, r) => sum(l) + v + sum(r) }; }\nsum(Tree.Node(Tree.Node(Tree.Leaf, 1, Tree.Leaf), 2, Tree.Leaf))", "3"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(format!("{}{}", shape, source));
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_methods() {
+    let types = "struct Point { x int, y int }\nenum Shape { Circle(int), Rect(int, int) }\nimpl Point {\n    fn len(self) int { ret self.x * self.x + self.y * self.y; }\n    fn add(self, other Point) Point { ret Point { x: self.x + other.x, y: self.y + other.y }; }\n    fn origin() Point { ret Point { x: 0, y: 0 }; }\n    fn move(self, dx int) { self.x = self.x + dx; }\n}\nimpl Shape {\n    fn area(self) int { ret match self { Shape.Circle(r) => 3 * r * r, Shape.Rect(w, h) => w * h }; }\n}\n";
+    let tests = [
+        ("Point { x: 3, y: 4 }.len()", "25"),
+        ("let p = Point { x: 1, y: 2 }; p.add(Point { x: 2, y: 2 }).len()", "25"),
+        ("Point.origin()", "Point { x: 0, y: 0 }"),
+        ("let p = Point { x: 1, y: 2 }; p.move(5); p.x", "6"),
+        ("[Shape.Circle(1).area(), Shape.Rect(2, 3).area()]", "[3, 6]"),
+        ("let p = Point { x: 1, y: 0 }; let len = p.len; len()", "1"),
+        ("Point { x: 1, y: 0 }.len", "<method Point.len>"),
+        ("[Shape.Circle(1), Shape.Rect(1, 1)].map(fn(s) { ret s.area(); })", "[3, 1]"),
+        // a method calling a built-in of the same name gets the built-in
+        ("struct S { items array }\nimpl S { fn len(self) int { ret len(self.items); } }\nS { items: [1, 2] }.len()", "2"),
+        ("struct N { n int }\nimpl N { fn fact(self) int { if self.n < 2 { ret 1; } ret self.n * N { n: self.n - 1 }.fact(); } }\nN { n: 5 }.fact()", "120"),
+        ("[1, 2].len()", "2"),
+        ("[1, 2].append(3)", "[1, 2, 3]"),
+        ("\"  hi \".trim()", "hi"),
+        ("\"a,b\".split(\",\").len()", "2"),
+        ("[3, 1, 2].sort().map(fn(x) { ret x * 2; })", "[2, 4, 6]"),
+        ("{\"a\": 1}.keys()", "[a]"),
+        ("1.to_str()", "1"),
+        ("Point { x: 1, y: 2 }.size()", "Point has no method 'size'"),
+        ("Point { x: 1, y: 2 }.size", "Point has no field 'size'"),
+        ("Shape.Circle(1).size()", "Shape has no method 'size'"),
+        ("[1].size()", "array has no method 'size'"),
+        ("impl Point { fn len(self) int { ret 0; } }", "method 'len' of Point is declared twice"),
+        ("impl Point { fn a(self) {} fn a(self) {} }", "method 'a' of Point is declared twice"),
+        ("Point { x: 1, y: 2 }.len(1)", "wrong number of arguments: want=1, got=2"),
+        ("let n = 1; impl n {}", "impl needs a struct or enum, got int"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(format!("{}{}", types, source));
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_big_integers() {
+    let factorial =
+        "fn factorial(x int) int {\n    if x <= 1 { ret 1; }\n    ret x * factorial(x - 1);\n}\n";
+    let tests = [
+        ("factorial(25)", "15511210043330985984000000"),
+        ("factorial(30) / factorial(28)", "870"),
+        ("factorial(25) % 1000007", "913534"),
+        ("factorial(25) * -1 / 7", "-2215887149047283712000000"),
+        ("factorial(21) > factorial(20)", "true"),
+        ("factorial(21) == factorial(21)", "true"),
+        ("9223372036854775807 + 1", "9223372036854775808"),
+        ("9223372036854775807 + 1 - 1", "9223372036854775807"),
+        ("-9223372036854775807 - 1", "-9223372036854775808"),
+        (
+            "let min = -9223372036854775807 - 1; -min",
+            "9223372036854775808",
+        ),
+        (
+            "let min = -9223372036854775807 - 1; min / -1",
+            "9223372036854775808",
+        ),
+        ("abs(-9223372036854775807 - 1)", "9223372036854775808"),
+        ("1 << 63", "9223372036854775808"),
+        ("to_float(factorial(25))", "1.5511210043330986e25"),
+        ("{factorial(21): 1}[factorial(21)]", "1"),
+        ("1 << 70", "1180591620717411303424"),
+        ("1 << 64 >> 64", "1"),
+        ("-5 >> 70", "-1"),
+        ("0 << (1 << 70)", "0"),
+        ("1 >> -1", "invalid shift amount -1"),
+        ("factorial(21) << -1", "invalid shift amount -1"),
+        ("1 << (1 << 40)", "shift amount 1099511627776 is too large"),
+        ("factorial(21) >> 3", "6386367771463680000"),
+        ("let f = factorial(21); -f >> 70", "-1"),
+        ("factorial(21) & 255", "0"),
+        ("factorial(21) / 0", "division by zero"),
+        (
+            "let i = factorial(21); [1, 2][i]",
+            "invalid array index 51090942171709440000",
+        ),
+        (
+            "\"ab\"[-factorial(21)]",
+            "invalid str index -51090942171709440000",
+        ),
+        (
+            "\"a\".repeat(factorial(21))",
+            "repeat argument 2 51090942171709440000 is out of range",
+        ),
+        (
+            "pad_left(\"a\", factorial(21), \" \")",
+            "pad_left argument 2 51090942171709440000 is out of range",
+        ),
+        (
+            "exit(factorial(21))",
+            "exit argument 1 51090942171709440000 is out of range",
+        ),
+        (
+            "0..factorial(21)",
+            "range bound 51090942171709440000 is out of range",
+        ),
+        (
+            "for i in factorial(21)..1 {}",
+            "range bound 51090942171709440000 is out of range",
+        ),
+        (
+            "let big = factorial(21); [1, 2].sort(fn(a, b) { ret (b - a) * big; })",
+            "[2, 1]",
+        ),
+        ("factorial(21) & -factorial(21)", "262144"),
+        ("factorial(21) | 1", "51090942171709440001"),
+        ("factorial(21) ^ factorial(21)", "0"),
+        ("let f = factorial(21); -f & 4294967295", "1195114496"),
+        ("let b = 1 << 70; -b | 255", "-1180591620717411303169"),
+        (
+            "123456789012345678901234567890",
+            "123456789012345678901234567890",
+        ),
+        (
+            "to_int(\"99999999999999999999\") == 99999999999999999999",
+            "true",
+        ),
+        (
+            "to_int(\" -99999999999999999999 \")",
+            "-99999999999999999999",
+        ),
+        ("to_int(to_str(factorial(21))) == factorial(21)", "true"),
+        (
+            "to_int(\"9x9999999999999999999\")",
+            "invalid value to parse int: 9x9999999999999999999",
+        ),
+        (
+            "let b = -99999999999999999999; b + 1",
+            "-99999999999999999998",
+        ),
+        ("let b = -9223372036854775809; b", "-9223372036854775809"),
+        ("match 1 << 64 { 18446744073709551616 => 1, _ => 2 }", "1"),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(format!("{}{}", factorial, source));
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_tail_calls() {
+    let tests = [
+        (
+            "fn count(n int, acc int) int {\n    if n == 0 { ret acc; }\n    ret count(n - 1, acc + 1);\n}\ncount(1000000, 0)",
+            "1000000",
+        ),
+        (
+            "fn even(n int) bool {\n    if n == 0 { ret true; }\n    ret odd(n - 1);\n}\nfn odd(n int) bool {\n    if n == 0 { ret false; }\n    ret even(n - 1);\n}\neven(100001)",
+            "false",
+        ),
+        (
+            "struct C { step int }\nimpl C {\n    fn down(self, n int) int {\n        if n <= 0 { ret n; }\n        ret self.down(n - self.step);\n    }\n}\nC { step: 2 }.down(100001)",
+            "-1",
+        ),
+        (
+            "fn loop(n int) int {\n    for i in 0..1 {\n        if n > 0 { ret loop(n - 1); }\n    }\n    ret n;\n}\nloop(100000)",
+            "0",
+        ),
+        ("fn size(xs array) int { ret len(xs); }\nsize([1, 2])", "2"),
+        ("fn f(n int) int { ret f(); }\nf(1)", "wrong number of arguments: want=1, got=0"),
+        // a call in a try still raises inside it
+        (
+            "fn boom(n int) int { ret n / 0; }\nfn safe(n int) int {\n    try { ret boom(n); } catch e { ret -1; }\n}\nsafe(1)",
+            "-1",
+        ),
+    ];
+    for (source, expected) in tests {
+        let evaluated = test_eval(source.to_string());
+        assert_eq!(expected, evaluated.to_string(), "{}", source);
+    }
+    // the function ending on a call leaves the stack trace
+    let source =
+        "fn inner(x) {\n    ret x + \"a\";\n}\nfn outer(y) {\n    ret inner(y);\n}\nouter(1);";
+    let evaluated = test_eval(source.to_string());
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    let trace: Vec<String> = error
+        .trace
+        .iter()
+        .map(|entry| format!("{} {}", entry.function, entry.location.as_ref().unwrap()))
+        .collect();
+    assert_eq!(vec!["inner foo.bzr:7:1"], trace);
+}
+
+#[test]
+fn test_stack_overflow_fits_the_thread_stack() {
+    // the stack Rust gives a spawned thread, whatever RUST_MIN_STACK says
+    let running = thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(|| {
+            let source = concat!(
+                "fn deep(n int) int {\n    if n == 0 { ret 0; }\n    ret 1 + deep(n - 1);\n}\n",
+                "try { deep(100000) } catch e { e[\"message\"] }",
+            );
+            let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+            let program: Box<dyn Node> = Parser::new(lexer).parse_program();
+            let env = RefCell::new(Environment::default());
+            let evaluated = Evaluator::default().eval(Some(program.as_ref()), Rc::new(env));
+            evaluated.unwrap().to_string()
+        });
+    assert_eq!("stack overflow", running.unwrap().join().unwrap());
+}
+
+#[test]
+fn test_stack_overflow() {
+    let source = "fn deep(n int) int {\n    if n == 0 { ret 0; }\n    ret 1 + deep(n - 1);\n}\n";
+    let run = |source: String| {
+        let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+        let program: Box<dyn Node> = Parser::new(lexer).parse_program();
+        let mut eval = Evaluator::default();
+        eval.max_depth = 20;
+        let env = RefCell::new(Environment::default());
+        eval.eval(Some(program.as_ref()), Rc::new(env)).unwrap()
+    };
+    assert_eq!("19", run(format!("{}deep(19)", source)).to_string());
+    let caught = run(format!(
+        "{}try {{ deep(20) }} catch e {{ e[\"message\"] }}",
+        source
+    ));
+    assert_eq!("stack overflow", caught.to_string());
+    let source = format!("{}deep(100)", source);
+    let evaluated = run(source.clone());
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    assert_eq!(20, error.trace.len());
+    assert!(error.report(&source).ends_with(
+        "stack trace:\n    deep at foo.bzr:3:13\n    deep at foo.bzr:3:13\n    deep at foo.bzr:3:13\n    ... repeated 17 more times\n    main at foo.bzr:5:1"
+    ));
+}
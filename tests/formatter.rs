@@ -0,0 +1,169 @@
+use std::fs;
+
+use bzr::formatter::format_source;
+
+fn format(source: &str) -> String {
+    format_source(source, "test.bzr").unwrap()
+}
+
+#[test]
+fn test_format_spacing_and_indent() {
+    let tests = [
+        ("let a = 1+2*3;", "let a = 1 + 2 * 3;\n"),
+        ("let  a array = [1,2, 3];", "let a array = [1, 2, 3];\n"),
+        (
+            "var m = {\"a\":1,\"b\" : 2};",
+            "var m = {\"a\": 1, \"b\": 2};\n",
+        ),
+        ("putsln( a [0] )", "putsln(a[0]);\n"),
+        ("let x = -a*!b;", "let x = -a * !b;\n"),
+        ("let f = 1.50;", "let f = 1.5;\n"),
+        (
+            "let b = 99999999999999999999*-99999999999999999999;",
+            "let b = 99999999999999999999 * -99999999999999999999;\n",
+        ),
+        (
+            "fn add(a int,b int) int{\nret a+b;}",
+            "fn add(a int, b int) int {\n    ret a + b;\n}\n",
+        ),
+        (
+            "if a>1{\nputsln(a);\n}else if a<0 {\n0\n}else{\n}",
+            "if a > 1 {\n    putsln(a);\n} else if a < 0 {\n    0\n} else {}\n",
+        ),
+        (
+            "for i in 0..n - 1 { if i==2 { continue; } }",
+            "for i in 0..n - 1 {\n    if i == 2 {\n        continue;\n    }\n}\n",
+        ),
+        (
+            "let r = try { f() } catch e { e[\"message\"] };",
+            "let r = try {\n    f()\n} catch e {\n    e[\"message\"]\n};\n",
+        ),
+        (
+            "let sq = fn(x) { x * x };",
+            "let sq = fn(x) {\n    x * x\n};\n",
+        ),
+        (
+            "struct Point { x int,y int }\nstruct Empty{}",
+            "struct Point {\n    x int,\n    y int,\n}\nstruct Empty {}\n",
+        ),
+        (
+            "let p Point = Point{x:1,y:2};p.x = 3;",
+            "let p Point = Point { x: 1, y: 2 };\np.x = 3;\n",
+        ),
+        (
+            "enum Shape { Circle(int),Rect(int,int),Empty }\nenum Never{}",
+            "enum Shape {\n    Circle(int),\n    Rect(int, int),\n    Empty,\n}\nenum Never {}\n",
+        ),
+        (
+            "let a = match s { Shape.Circle(r) if r>1=>r*2, Shape.Rect(w,h) => { w*h } [x,..rest]=>x, -1..3=>0, _=>1 };",
+            "let a = match s {\n    Shape.Circle(r) if r > 1 => r * 2,\n    Shape.Rect(w, h) => {\n        w * h\n    }\n    [x, ..rest] => x,\n    -1..3 => 0,\n    _ => 1,\n};\n",
+        ),
+        ("match x {} ;\n[1];", "match x {};\n[1];\n"),
+        (
+            "impl Point { fn len(self) int { ret self.x; }\n\nfn origin() { ret 0; } }\nimpl Empty{}",
+            "impl Point {\n    fn len(self) int {\n        ret self.x;\n    }\n\n    fn origin() {\n        ret 0;\n    }\n}\nimpl Empty {}\n",
+        ),
+        ("p.len( );arr.map(f);", "p.len();\narr.map(f);\n"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(expected, format(input), "formatting {}", input);
+    }
+}
+
+#[test]
+fn test_format_keeps_needed_parens() {
+    let tests = [
+        ("let a = (1 + 2) * 3;", "let a = (1 + 2) * 3;\n"),
+        ("let a = ((1 * 2)) + 3;", "let a = 1 * 2 + 3;\n"),
+        ("let a = 1 - (2 - 3);", "let a = 1 - (2 - 3);\n"),
+        ("let a = -(1 + b);", "let a = -(1 + b);\n"),
+        ("let a = (x << 1) | y;", "let a = (x << 1) | y;\n"),
+        ("let a = (f)(1)[0];", "let a = f(1)[0];\n"),
+        ("let a = (1..3);", "let a = 1..3;\n"),
+        ("if p == (P {x: 1}) { }", "if p == (P { x: 1 }) {}\n"),
+        ("let a = (P {x: 1}).x;", "let a = P { x: 1 }.x;\n"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(expected, format(input), "formatting {}", input);
+    }
+}
+
+#[test]
+fn test_format_statements() {
+    let input = "import \"geometry.bzr\";\nimport \"lib/geometry.bzr\" as g;\n\n\n\nlet a = 1\nputsln(a)\nwhile a < 3 { a = a + 1 };\n-a;\n";
+    let expected = "import geometry;\nimport \"lib/geometry.bzr\" as g;\n\nlet a = 1;\nputsln(a);\nwhile a < 3 {\n    a = a + 1\n};\n-a;\n";
+    assert_eq!(expected, format(input));
+}
+
+#[test]
+fn test_format_keeps_comments() {
+    let input = "// header\n\n/// Adds.\nfn add(a,b) { // open\n  // inside\n  ret a+b; /* sum */\n  // before close\n}\nif true {\n// only\n}\n/* multi\n   line */\nlet x = add(1, 2);\n// tail\n";
+    let expected = "// header\n\n/// Adds.\nfn add(a, b) { // open\n    // inside\n    ret a + b; /* sum */\n    // before close\n}\nif true {\n    // only\n}\n/* multi\n   line */\nlet x = add(1, 2);\n// tail\n";
+    let formatted = format(input);
+    assert_eq!(expected, formatted);
+    assert_eq!(formatted, format(&formatted));
+}
+
+#[test]
+fn test_format_keeps_trailing_comments_on_their_line() {
+    let input = "fn f(x int) int { ret (x + 1) * 2; } // trailing\nwhile x > 0 { x = x - 1; /* inside */ }\nwhile x > 0 {\n  x = x - 1;\n  /* last */ }\nif x { a; } else {\n  b; c; // c\n} // done\n";
+    let expected = "fn f(x int) int {\n    ret (x + 1) * 2;\n} // trailing\nwhile x > 0 {\n    x = x - 1; /* inside */\n}\nwhile x > 0 {\n    x = x - 1;\n    /* last */\n}\nif x {\n    a;\n} else {\n    b;\n    c; // c\n} // done\n";
+    let formatted = format(input);
+    assert_eq!(expected, formatted);
+    assert_eq!(formatted, format(&formatted));
+}
+
+#[test]
+fn test_format_keeps_comments_in_enums_and_match() {
+    let input = "enum E {\n// first\nA, // a\nB\n}\nmatch e {\n// arms\nE.A => 1, // one\nE.B => { 2 }\n// end\n}\n";
+    let expected = "enum E {\n    // first\n    A, // a\n    B,\n}\nmatch e {\n    // arms\n    E.A => 1, // one\n    E.B => {\n        2\n    }\n    // end\n}\n";
+    let formatted = format(input);
+    assert_eq!(expected, formatted);
+    assert_eq!(formatted, format(&formatted));
+}
+
+#[test]
+fn test_format_keeps_comments_in_impl() {
+    let input = "impl P {\n// first\n/// Length.\nfn len(self) { // open\nret 1;\n}\n// end\n}\n";
+    let expected = "impl P {\n    // first\n    /// Length.\n    fn len(self) { // open\n        ret 1;\n    }\n    // end\n}\n";
+    let formatted = format(input);
+    assert_eq!(expected, formatted);
+    assert_eq!(formatted, format(&formatted));
+}
+
+#[test]
+fn test_format_strings() {
+    let tests = [
+        (r#"let s = "a\u{9}\"b\"\\";"#, r#"let s = "a\t\"b\"\\";"#),
+        (
+            r#"let s = "\${x} ${ x+1 } ${"in"}";"#,
+            r#"let s = "\${x} ${x + 1} in";"#,
+        ),
+        ("let s = \"two\nlines\";", r#"let s = "two\nlines";"#),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(
+            format!("{}\n", expected),
+            format(input),
+            "formatting {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        let formatted = format(&source);
+        assert_eq!(source, formatted, "{} is not formatted", path.display());
+        assert_eq!(formatted, format(&formatted));
+    }
+}
+
+#[test]
+fn test_format_parse_error() {
+    let errors = format_source("let = 1;", "test.bzr").unwrap_err();
+    assert!(!errors.is_empty());
+}
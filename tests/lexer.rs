@@ -5,9 +5,10 @@ use bzr::lexer::{
     *,
 };
 
-const FILENAME: &'static str = "foo.bzr";
+const FILENAME: &str = "foo.bzr";
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn assert_true() {
     assert!(true);
 }
@@ -15,7 +16,7 @@ fn assert_true() {
 #[test]
 fn test_tokens() {
     let source = Rc::new("{}(),; ,".to_string());
-    let tokens = vec![
+    let tokens = [
         Token::LBrace(Some(Location::new(0, 1, Rc::new(FILENAME.to_string())))),
         Token::Rbrace(Some(Location::new(1, 1, Rc::new(FILENAME.to_string())))),
         Token::LParen(Some(Location::new(2, 1, Rc::new(FILENAME.to_string())))),
@@ -34,12 +35,10 @@ fn test_tokens() {
 #[test]
 fn test_is_whitespace() {
     let source = Rc::new(" \r\t\n".to_string());
-    let eof = if let Token::EOF(_) = *Lexer::new(source, Rc::new(FILENAME.to_string())).next_token()
-    {
-        true
-    } else {
-        false
-    };
+    let eof = matches!(
+        *Lexer::new(source, Rc::new(FILENAME.to_string())).next_token(),
+        Token::EOF(_)
+    );
     assert!(eof);
 }
 
@@ -140,8 +139,18 @@ fn test_gte_token() {
 }
 
 #[test]
-fn test_gte_illegal_order() {
+fn test_fat_arrow_token() {
     let source = Rc::new("=>".to_string());
+    let mut lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    assert_eq!(
+        Token::FatArrow(Some(Location::new(0, 1, Rc::new(FILENAME.to_string())))),
+        *lexer.next_token()
+    );
+}
+
+#[test]
+fn test_lte_illegal_order() {
+    let source = Rc::new("=<".to_string());
     let mut lexer = Lexer::new(Rc::clone(&source), Rc::new(FILENAME.to_string()));
     assert_eq!(
         Token::Illegal(
@@ -204,3 +213,206 @@ fn test_while_token() {
         *lexer.next_token()
     );
 }
+
+#[test]
+fn test_try_catch_throw_tokens() {
+    let source = Rc::new("try catch throw".to_string());
+    let tokens = [
+        Token::Try(Some(Location::new(0, 1, Rc::new(FILENAME.to_string())))),
+        Token::Catch(Some(Location::new(4, 1, Rc::new(FILENAME.to_string())))),
+        Token::Throw(Some(Location::new(10, 1, Rc::new(FILENAME.to_string())))),
+    ];
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+
+    for (i, token) in lexer.into_iter().enumerate() {
+        assert_eq!(*token, tokens[i]);
+    }
+}
+
+#[test]
+fn test_for_tokens() {
+    let source = Rc::new("for i in 0..10 { break; continue; }".to_string());
+    let tokens = [
+        Token::For(None),
+        Token::Ident(Some(Rc::new("i".to_string())), None),
+        Token::In(None),
+        Token::Number(Some(Rc::new("0".to_string())), None),
+        Token::DotDot(Some(Location::new(11, 1, Rc::new(FILENAME.to_string())))),
+        Token::Number(Some(Rc::new("10".to_string())), None),
+        Token::LBrace(None),
+        Token::Break(None),
+        Token::Semicolon(None),
+        Token::Continue(None),
+        Token::Semicolon(None),
+        Token::Rbrace(None),
+    ];
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+
+    for (i, token) in lexer.into_iter().enumerate() {
+        assert_eq!(*token, tokens[i]);
+        if i == 4 {
+            assert_eq!(tokens[i].location(), token.location());
+        }
+    }
+}
+
+#[test]
+fn test_float_tokens() {
+    let source = Rc::new("3.14 1e-9 2.5E3 0..10 -0.5".to_string());
+    let expected = ["3.14", "1e-9", "2.5E3", "0", "..", "10", "-0.5"];
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+
+    for (i, token) in lexer.into_iter().enumerate() {
+        match token.as_ref() {
+            Token::Number(Some(number), _) => assert_eq!(expected[i], number.as_str()),
+            Token::DotDot(_) => assert_eq!(expected[i], ".."),
+            token => panic!("unexpected token {}", token),
+        }
+    }
+}
+
+#[test]
+fn test_import_tokens() {
+    let source = Rc::new("import \"lib.bzr\" as l; l.x".to_string());
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    let tokens: Vec<String> = lexer.map(|token| format!("{:?}", token)).collect();
+    let expected = [
+        "Import",
+        "String",
+        "As",
+        "Ident",
+        "Semicolon",
+        "Ident",
+        "Dot",
+        "Ident",
+    ];
+    assert_eq!(expected.len(), tokens.len());
+    for (expected, token) in expected.iter().zip(tokens) {
+        assert!(token.starts_with(expected), "{}", token);
+    }
+}
+
+#[test]
+fn test_enum_match_tokens() {
+    let source = Rc::new("enum E { A } match e { E.A => 1, _ => 2 } a == b".to_string());
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    let tokens: Vec<String> = lexer.map(|token| format!("{:?}", token)).collect();
+    let expected = [
+        "Enum", "Ident", "LBrace", "Ident", "Rbrace", "Match", "Ident", "LBrace", "Ident", "Dot",
+        "Ident", "FatArrow", "Number", "Comma", "Ident", "FatArrow", "Number", "Rbrace", "Ident",
+        "Eq", "Ident",
+    ];
+    assert_eq!(expected.len(), tokens.len());
+    for (expected, token) in expected.iter().zip(tokens) {
+        assert!(token.starts_with(expected), "{}", token);
+    }
+}
+
+#[test]
+fn test_impl_tokens() {
+    let source = Rc::new("impl P { fn len(self) {} }".to_string());
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    let tokens: Vec<String> = lexer.map(|token| format!("{:?}", token)).collect();
+    let expected = [
+        "Impl", "Ident", "LBrace", "Function", "Ident", "LParen", "Ident", "RParen", "LBrace",
+        "Rbrace", "Rbrace",
+    ];
+    assert_eq!(expected.len(), tokens.len());
+    for (expected, token) in expected.iter().zip(tokens) {
+        assert!(token.starts_with(expected), "{}", token);
+    }
+}
+
+#[test]
+fn test_map_tokens() {
+    let source = Rc::new("map {1: 2}".to_string());
+    let tokens = [
+        Token::Map(Some(Location::new(0, 1, Rc::new(FILENAME.to_string())))),
+        Token::LBrace(Some(Location::new(4, 1, Rc::new(FILENAME.to_string())))),
+        Token::Number(
+            Some(Rc::new("1".to_string())),
+            Some(Location::new(5, 1, Rc::new(FILENAME.to_string()))),
+        ),
+        Token::Colon(Some(Location::new(6, 1, Rc::new(FILENAME.to_string())))),
+        Token::Number(
+            Some(Rc::new("2".to_string())),
+            Some(Location::new(8, 1, Rc::new(FILENAME.to_string()))),
+        ),
+        Token::Rbrace(Some(Location::new(9, 1, Rc::new(FILENAME.to_string())))),
+    ];
+    let lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+
+    for (i, token) in lexer.into_iter().enumerate() {
+        assert_eq!(*token, tokens[i]);
+    }
+}
+
+#[test]
+fn test_comments_are_skipped() {
+    let source = "// line\na /* block /* nested */ still */ / b // end\n/* é */ c";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new(FILENAME.to_string()));
+    let tokens: Vec<String> = lexer.map(|token| format!("{:?}", token)).collect();
+    let expected = ["Ident", "Slash", "Ident", "Ident"];
+    assert_eq!(expected.len(), tokens.len(), "{:?}", tokens);
+    for (expected, token) in expected.iter().zip(tokens) {
+        assert!(token.starts_with(expected), "{}", token);
+    }
+
+    let mut lexer = Lexer::new(Rc::new(source.to_string()), Rc::new(FILENAME.to_string()));
+    while lexer.next().is_some() {}
+    let comments: Vec<(&str, usize, bool)> = lexer
+        .comments()
+        .iter()
+        .map(|comment| {
+            (
+                comment.text.as_str(),
+                comment.location.line,
+                comment.trailing,
+            )
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            ("// line", 1, false),
+            ("/* block /* nested */ still */", 2, true),
+            ("// end", 2, true),
+            ("/* é */", 3, false),
+        ],
+        comments
+    );
+}
+
+#[test]
+fn test_doc_comment_token() {
+    let source = Rc::new("/// Adds.\nfn".to_string());
+    let mut lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    let doc = Token::DocComment(
+        Some(Rc::new("/// Adds.".to_string())),
+        Some(Location::new(0, 1, Rc::new(FILENAME.to_string()))),
+    );
+    assert_eq!(doc, *lexer.next_token());
+    assert_eq!(Token::Function(None), *lexer.next_token());
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let source = Rc::new("a\n  /* open /* nested */ b".to_string());
+    let mut lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    lexer.next_token();
+    let token = lexer.next_token();
+    assert_eq!(Token::Illegal(None, None), *token);
+    assert_eq!("'illegal (/*)' in foo.bzr:2:3", token.to_string());
+    assert_eq!(Token::EOF(None), *lexer.next_token());
+}
+
+#[test]
+fn test_string_escapes_and_interpolation_tokens() {
+    let source = Rc::new(r#""a \"b\" ${m["k"]}" "open \""#.to_string());
+    let mut lexer = Lexer::new(source, Rc::new(FILENAME.to_string()));
+    match lexer.next_token().as_ref() {
+        Token::String(Some(value), _) => assert_eq!(r#"a \"b\" ${m["k"]}"#, value.as_str()),
+        token => panic!("expected a string, got {}", token),
+    }
+    assert_eq!(Token::Illegal(None, None), *lexer.next_token());
+    assert_eq!(Token::EOF(None), *lexer.next_token());
+}
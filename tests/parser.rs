@@ -1,13 +1,17 @@
 use bzr::ast::{
-    expr::if_expr::IfExpr, expression::Expression, stmt::expression_stmt::ExpressionStatement,
+    expr::{function_expr::FunctionExpr, if_expr::IfExpr},
+    expression::Expression,
+    stmt::expression_stmt::ExpressionStatement,
 };
 use std::rc::Rc;
 
 use bzr::{
     ast::{
-        expr::int_expr::IntExpr, identifier::Identifier, program::Program, statement::Statement,
-        stmt::let_stmt::Let, types::Type,
+        expr::float_expr::FloatExpr, expr::int_expr::IntExpr, identifier::Identifier,
+        program::Program, statement::Statement, stmt::let_stmt::Let, stmt::var_stmt::Var,
+        types::Type,
     },
+    diagnostic::Severity,
     lexer::Lexer,
     parser::Parser,
 };
@@ -18,8 +22,8 @@ fn test_parse_let_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -28,8 +32,8 @@ fn test_parse_var_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -38,8 +42,8 @@ fn test_parse_let_negative_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -48,8 +52,8 @@ fn test_parse_var_negative_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -58,8 +62,8 @@ fn test_parse_var_int_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -68,8 +72,8 @@ fn test_parse_let_int_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -78,8 +82,8 @@ fn test_parse_var_int_letter_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -88,8 +92,8 @@ fn test_parse_let_int_letter_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -98,8 +102,8 @@ fn test_parse_str_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -108,8 +112,8 @@ fn test_parse_var_str_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -118,8 +122,8 @@ fn test_parse_var_str_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -128,8 +132,8 @@ fn test_parse_let_str_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 //
@@ -140,8 +144,8 @@ fn test_parse_bool_int_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -150,8 +154,8 @@ fn test_parse_var_bool_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -160,8 +164,8 @@ fn test_parse_var_bool_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -170,8 +174,8 @@ fn test_parse_let_bool_type_erros() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() > 0);
-    assert!(program.statements.len() <= 0);
+    assert!(!program.errors.is_empty());
+    assert!(program.statements.is_empty());
 }
 
 #[test]
@@ -180,8 +184,8 @@ fn test_parse_return_type() {
     let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
     let parser = Parser::new(lexer);
     let program = parser.parse_program();
-    assert!(program.errors.len() <= 0);
-    assert!(program.statements.len() > 0);
+    assert!(program.errors.is_empty());
+    assert!(!program.statements.is_empty());
 }
 
 #[test]
@@ -254,7 +258,7 @@ fn test_parse_precedence() {
     let program = parser.parse_program();
     for stmt in program.statements {
         output.push_str(&stmt.to_string());
-        output.push_str("\n");
+        output.push('\n');
     }
     assert_eq!(&expected, &output);
 }
@@ -328,6 +332,47 @@ fn test_function_literal_parsing_with_type() {
     assert_eq!(1, program.statements.len());
 }
 
+#[test]
+fn test_anonymous_function_parsing() {
+    let source = "let add fn = fn(a int, b int) int { a + b; }; fn(x) { x; }(1);".to_string();
+    let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert_eq!(0, program.errors.len());
+    assert_eq!(2, program.statements.len());
+    assert_eq!(
+        "let add function = fn (a,b) int (a + b);",
+        program.statements[0].to_string()
+    );
+}
+
+#[test]
+fn test_import_parsing() {
+    let tests = [
+        (
+            "import \"lib/math.bzr\";",
+            "import \"lib/math.bzr\" as math;",
+        ),
+        ("import \"math.bzr\" as m;", "import \"math.bzr\" as m;"),
+        ("import math as m;", "import \"math.bzr\" as m;"),
+        ("import math", "import \"math.bzr\" as math;"),
+        ("m.pi", "(m.pi)"),
+        ("a.b.c[0]", "(((a.b).c)[0])"),
+        ("m.add(1, 2) * 2", "((m.add)(1,2) * 2)"),
+    ];
+    for (source, expected) in tests {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        assert_eq!(0, program.errors.len(), "{}", source);
+        assert_eq!(expected, program.statements[0].to_string());
+    }
+    let lexer = Lexer::new(
+        Rc::new("import 5;".to_string()),
+        Rc::new("foo.bzr".to_string()),
+    );
+    assert!(!Parser::new(lexer).parse_program().errors.is_empty());
+}
+
 #[test]
 fn test_function_call_with_args_parsing() {
     let source = "sum (1, 2 * 3);".to_string();
@@ -356,3 +401,314 @@ fn test_if_while_expression() {
     assert_eq!(1, program.statements.len());
     assert_eq!(0, program.errors.len());
 }
+
+#[test]
+fn test_parse_hash_literal() {
+    let source = "let m map = {\"one\": 1, \"two\": 2}; {}; {1: true}".to_string();
+    let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert_eq!(0, program.errors.len());
+    assert_eq!(3, program.statements.len());
+    assert_eq!(
+        "let m map = {one: 1, two: 2};",
+        program.statements[0].to_string()
+    );
+}
+
+#[test]
+fn test_parse_for_expression() {
+    let source = "for i in 0..n + 1 { break; } for ch in \"ab\" { continue; }".to_string();
+    let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert_eq!(0, program.errors.len());
+    assert_eq!(2, program.statements.len());
+    assert_eq!(
+        "for i in (0..(n + 1)) break;",
+        program.statements[0].to_string()
+    );
+    assert_eq!("for ch in ab continue;", program.statements[1].to_string());
+}
+
+#[test]
+fn test_parse_hash_literal_errors() {
+    let source = "let m = {\"one\" 1};".to_string();
+    let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    assert!(!program.errors.is_empty());
+}
+
+#[test]
+fn test_parse_float_literal() {
+    let source = "let a float = 2.5e-1; let b float = -1.5 + 2;".to_string();
+    let lexer = Lexer::new(Rc::new(source), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    let value = &program.statements[0]
+        .as_any()
+        .downcast_ref::<Let>()
+        .unwrap()
+        .value;
+    let float = value.as_any().downcast_ref::<FloatExpr>().unwrap();
+    assert_eq!(0.25, float.value);
+    assert_eq!(Type::Float, float.get_type());
+}
+
+#[test]
+fn test_doc_comments_attach() {
+    let source = "/// Adds.\n///\n///   Indented.\nfn add(a, b) { a + b }\n/// The answer.\nlet x = 42;\n// plain\nvar y = 1;\n/// stray\n1 + 1;\n";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert_eq!(0, program.errors.len(), "{:?}", program.errors);
+    assert_eq!(4, program.statements.len());
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let function = stmt
+        .expression
+        .as_ref()
+        .unwrap()
+        .as_any()
+        .downcast_ref::<FunctionExpr>()
+        .unwrap();
+    assert_eq!(Some("Adds.\n\n  Indented."), function.doc.as_deref());
+    let let_stmt = program.statements[1]
+        .as_any()
+        .downcast_ref::<Let>()
+        .unwrap();
+    assert_eq!(Some("The answer."), let_stmt.doc.as_deref());
+    let var_stmt = program.statements[2]
+        .as_any()
+        .downcast_ref::<Var>()
+        .unwrap();
+    assert_eq!(None, var_stmt.doc);
+}
+
+#[test]
+fn test_string_literal_parsing() {
+    let tests = [
+        (r#""tab\tquote\"slash\\""#, "tab\tquote\"slash\\"),
+        (r#""\u{48}\u{1F600}\${""#, "H\u{1F600}${"),
+        (r#""total = ${a + b}!""#, "\"total = ${(a + b)}!\""),
+        (r#""${m["k"]}${"in ${x}"}""#, "\"${(m[k])}${\"in ${x}\"}\""),
+    ];
+    for (source, expected) in tests {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        assert_eq!(0, program.errors.len(), "{}: {:?}", source, program.errors);
+        assert_eq!(expected, program.statements[0].to_string());
+    }
+    let errors = [
+        (r#""\q""#, "foo.bzr:1:1: invalid escape '\\q'"),
+        (r#""\u{110000}""#, "invalid unicode escape"),
+        (r#""${}""#, "empty '${}' in string"),
+        (r#""${1 +}""#, "foo.bzr:1:7"),
+        (r#""${let a = 1}""#, "expected one expression in '${}'"),
+    ];
+    for (source, expected) in errors {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        let error = program.errors[0].to_string();
+        assert!(error.contains(expected), "{}: {}", source, error);
+    }
+}
+
+#[test]
+fn test_typos_report_one_error() {
+    let tests = [
+        (
+            "let x = ;\nputs(1);",
+            "foo.bzr:1:9: expected an expression, found ';'",
+            None,
+        ),
+        (
+            "let b = (2 + 3;\nputs(b);",
+            "foo.bzr:1:15: expected ')', found ';'",
+            Some("to close the '(' opened at foo.bzr:1:9"),
+        ),
+        (
+            "fn f(a int {\n    ret a;\n}\nf(1);",
+            "foo.bzr:1:12: expected ')', found '{'",
+            None,
+        ),
+        (
+            "puts(1 2);",
+            "foo.bzr:1:8: expected ')', found '2'",
+            Some("separate the items with ','"),
+        ),
+        (
+            "let m = {\"a\" 1};\nputs(m);",
+            "foo.bzr:1:14: expected ':', found '1'",
+            Some("write map entries as 'key: value'"),
+        ),
+        (
+            "if true puts(1);\nputs(2);",
+            "foo.bzr:1:9: expected '{', found 'puts'",
+            Some("the body of 'if' goes in braces"),
+        ),
+        (
+            "let x int 5;\nputs(x);",
+            "foo.bzr:1:11: expected '=', found '5'",
+            Some("give 'x' a value with '='"),
+        ),
+        (
+            "fn g() {\n    let = 2;\n    puts(3);\n}",
+            "foo.bzr:2:9: expected a name, found '='",
+            None,
+        ),
+        (
+            "fn h() {\n    puts(1);\n",
+            "foo.bzr:1:8: unclosed '{'",
+            Some("add a '}' to close the block"),
+        ),
+        (
+            "let s = \"abc;\nputs(s);",
+            "foo.bzr:1:9: unterminated string",
+            None,
+        ),
+        (
+            "puts(1) @ 2;\nputs(2);",
+            "foo.bzr:1:9: unexpected '@'",
+            None,
+        ),
+        (
+            "}\nputs(1);",
+            "foo.bzr:1:1: expected an expression, found '}'",
+            None,
+        ),
+    ];
+    for (source, expected, hint) in tests {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        let errors: Vec<String> = program.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(1, errors.len(), "{}: {:?}", source, errors);
+        let error = &program.errors[0];
+        assert_eq!(Severity::Error, error.severity);
+        assert!(errors[0].starts_with(expected), "{}: {}", source, errors[0]);
+        assert_eq!(hint, error.hint.as_deref(), "{}", source);
+    }
+}
+
+#[test]
+fn test_parser_recovers_after_errors() {
+    let source =
+        "let a = ;\nlet b = 2;\nfn f() {\n    let = 1;\n    ret b\n}\nputs(1 +);\nlet c = 3;";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    let spans: Vec<String> = program
+        .errors
+        .iter()
+        .map(|e| e.span.as_ref().unwrap().to_string())
+        .collect();
+    assert_eq!(vec!["foo.bzr:1:9", "foo.bzr:4:9", "foo.bzr:7:9"], spans);
+    let statements: Vec<String> = program.statements.iter().map(|s| s.to_string()).collect();
+    assert_eq!(3, statements.len(), "{:?}", statements);
+    assert_eq!("let c int = 3;", statements[2]);
+    let report = program.errors[1].report(source);
+    assert_eq!(
+        "foo.bzr:4:9: expected a name, found '='\n    let = 1;\n        ^",
+        report
+    );
+}
+
+#[test]
+fn test_parse_structs() {
+    let source = "struct Point {\n    x int,\n    y int,\n}\nlet p Point = Point { x: 1, y: 2 };\nif p == (Point { x: 1, y: 2 }) { p.x = 3; }\nwhile ok { }\nfn f(a Point) Point { ret a; }";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    assert_eq!(5, program.statements.len());
+    assert_eq!("struct Point { x, y }", program.statements[0].to_string());
+    let name = &program.statements[1]
+        .as_any()
+        .downcast_ref::<Let>()
+        .unwrap()
+        .name;
+    let name = name.as_any().downcast_ref::<Identifier>().unwrap();
+    assert_eq!(Some(Type::Struct(Rc::from("Point"))), name.typ);
+    assert_eq!(
+        "let p Point = Point { x: 1, y: 2 };",
+        program.statements[1].to_string()
+    );
+    // in a condition the `{` starts the body
+    let stmt = program.statements[3]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let while_expr = stmt.expression.as_ref().unwrap();
+    assert_eq!("while ok ", while_expr.to_string());
+
+    for source in ["struct P { x }", "struct P { x int y int }", "P { x 1 }"] {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        assert!(!program.errors.is_empty(), "{}", source);
+    }
+}
+
+#[test]
+fn test_parse_enums_and_match() {
+    let source = "enum Shape {\n    Circle(int),\n    Rect(int, int),\n    Empty,\n}\nlet a = match s { Shape.Circle(r) if r > 1 => r, Shape.Rect(_, h) => { h } Shape.Empty => 0 };\nmatch xs { [] => 0, [x, ..] => x, [x, ..rest] => rest, -2..3 => 1, \"a\" => 2, 1.5 => 3, true => 4, n => n }";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    assert_eq!(3, program.statements.len());
+    assert_eq!(
+        "enum Shape { Circle(int), Rect(int, int), Empty }",
+        program.statements[0].to_string()
+    );
+    assert_eq!(
+        "let a expression = match s { Shape.Circle(r) if (r > 1) => r, Shape.Rect(_, h) => h, Shape.Empty => 0 };",
+        program.statements[1].to_string()
+    );
+    assert_eq!(
+        "match xs { [] => 0, [x, ..] => x, [x, ..rest] => rest, -2..3 => 1, a => 2, 1.5 => 3, true => 4, n => n }",
+        program.statements[2].to_string()
+    );
+
+    let tests = [
+        ("match x { 1 2 }", "foo.bzr:1:13: expected '=>', found '2'\nhint: write match arms as 'pattern => value'"),
+        ("match x { 1 => 1 2 => 2 }", "foo.bzr:1:18: expected ',' or '}', found '2'\nhint: separate the arms with ','"),
+        ("match x { + => 1 }", "foo.bzr:1:11: expected a pattern, found '+'"),
+        ("match x { 1.5..2 => 1 }", "foo.bzr:1:11: range patterns take int bounds"),
+        ("match x { [..rest, a] => 1 }", "foo.bzr:1:18: expected ']', found ','\nhint: '..' goes last in an array pattern"),
+        ("match x { \"${y}\" => 1 }", "foo.bzr:1:11: string patterns can't embed code with '${}'"),
+        ("enum E { A(1) }", "foo.bzr:1:12: expected a type, found '1'\nhint: write the values of a variant as types, 'Circle(int)'"),
+        ("enum E { A B }", "foo.bzr:1:12: expected ',', found 'B'"),
+    ];
+    for (source, expected) in tests {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        assert!(!program.errors.is_empty(), "{}", source);
+        assert_eq!(expected, program.errors[0].to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_parse_impl() {
+    let source = "impl Point {\n    fn len(self) int { ret self.x; }\n    fn origin() { ret 0; }\n}\np.len();\narr.map(f)";
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    assert_eq!(3, program.statements.len());
+    assert_eq!(
+        "impl Point { fn len (self) int ret(self.x) ; fn origin () ret0 ; }",
+        program.statements[0].to_string()
+    );
+    assert_eq!("(p.len)()", program.statements[1].to_string());
+    assert_eq!("(arr.map)(f)", program.statements[2].to_string());
+
+    let tests = [
+        ("impl P { let a = 1; }", "foo.bzr:1:10: expected a method, found 'let'\nhint: write methods as 'fn name(self) { ... }'"),
+        ("impl P { fn (self) {} }", "foo.bzr:1:10: a method of P needs a name"),
+        ("impl { }", "foo.bzr:1:6: expected a name, found '{'"),
+    ];
+    for (source, expected) in tests {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        assert!(!program.errors.is_empty(), "{}", source);
+        assert_eq!(expected, program.errors[0].to_string(), "{}", source);
+    }
+}
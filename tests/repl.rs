@@ -0,0 +1,68 @@
+use std::io::Cursor;
+
+use bzr::repl::Repl;
+
+fn run_repl(input: &str) -> String {
+    let mut output = Vec::new();
+    Repl::default()
+        .start(Cursor::new(input.to_string()), &mut output)
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_repl_keeps_environment() {
+    let output = run_repl("let a = 5;\na + 10\n");
+    assert_eq!(">> 5\n>> 15\n>> \n", output);
+}
+
+#[test]
+fn test_repl_multi_line_input() {
+    let output = run_repl("fn add(a int, b int) int {\nret a + b;\n}\nadd(2, 3)\n");
+    assert!(output.contains(".. .. "));
+    assert!(output.ends_with("5\n>> \n"));
+}
+
+#[test]
+fn test_repl_reports_errors_and_continues() {
+    let output = run_repl("let a int = \"b\";\n1 + 1\n");
+    assert!(output.contains("expected int, got str"));
+    assert!(output.ends_with("2\n>> \n"));
+}
+
+#[test]
+fn test_repl_ignores_brackets_in_comments() {
+    let output = run_repl("let x = 1; /* { */\nx // {\n/* a /* ( */\n] */ x + 1\n");
+    assert_eq!(">> 1\n>> 1\n>> .. 2\n>> \n", output);
+}
+
+#[test]
+fn test_repl_escaped_quotes() {
+    let output = run_repl("let s = \"a\\\"b\";\nlen(\"\\\\\")\n");
+    assert_eq!(">> a\"b\n>> 1\n>> \n", output);
+}
+
+#[test]
+fn test_repl_runs_tail_calls_of_top_level_ret() {
+    let output = run_repl("let x = 1;\nfn f(n) { ret n + x; }\nret f(1);\nif true { ret f(2); }\n");
+    assert!(output.ends_with(">> 2\n>> 3\n>> \n"), "{}", output);
+}
+
+#[test]
+fn test_repl_forgets_declarations_of_failed_input() {
+    let output = run_repl("fn one() int { ret 1; }\nlet z str = one();\nlet z = 5;\nz + 1\n");
+    assert!(
+        output.contains("'z' declared as str, got int"),
+        "{}",
+        output
+    );
+    assert!(output.ends_with(">> 5\n>> 6\n>> \n"), "{}", output);
+    let output =
+        run_repl("let y = 1 / 0;\nlet y = 2;\nlet a = 1; let b = 1 / 0;\nlet b = a + y;\n");
+    assert!(
+        output.contains(">> 2\n>> 1\nrepl:1:22: division by zero"),
+        "{}",
+        output
+    );
+    assert!(output.ends_with(">> 3\n>> \n"), "{}", output);
+}
@@ -0,0 +1,319 @@
+use std::rc::Rc;
+
+use bzr::{lexer::Lexer, parser::Parser, typeck::TypeChecker};
+
+fn check(input: &str) -> Vec<String> {
+    let lexer = Lexer::new(Rc::new(input.to_string()), Rc::new("test".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+    TypeChecker::default()
+        .check(&program)
+        .iter()
+        .map(|error| error.to_string())
+        .collect()
+}
+
+#[test]
+fn test_typeck_valid_program() {
+    let input = "fn add(a int, b int) int {
+        ret a + b;
+    }
+    fn calculate(a int, b int, p fn) int {
+        ret p(a, b);
+    }
+    let total int = calculate(1, add(2, 3), add);
+    var name = \"bzr\";
+    if total > 5 && len(name) == 3 {
+        name = name + \"!\";
+    }";
+    assert_eq!(Vec::<String>::new(), check(input));
+}
+
+#[test]
+fn test_typeck_reports_mismatches_with_location() {
+    let tests = [
+        (
+            "fn add(a int, b int) int { ret a + b; }\nadd(1, \"2\");",
+            "test:2:8: argument 2 of 'add' expected int, got str",
+        ),
+        (
+            "fn add(a int, b int) int { ret a + b; }\nadd(1);",
+            "test:2:1: wrong number of arguments to 'add': want=2, got=1",
+        ),
+        (
+            "fn name() str {\n    ret 10;\n}",
+            "test:2:5: expected return type str, got int",
+        ),
+        (
+            "let a = 1 + true;",
+            "test:1:11: incompatible types bool and int",
+        ),
+        (
+            "fn one() int { ret 1; }\nlet a str = one();",
+            "test:2:5: 'a' declared as str, got int",
+        ),
+        (
+            "var a int = 1;\na = \"b\";",
+            "test:2:3: cannot assign str to 'a' of type int",
+        ),
+        ("while 1 {}", "test:1:7: condition must be bool, got int"),
+        (
+            "let a = 1;\na = 2;",
+            "test:2:3: cannot assign to 'a', it was declared with let at test:1:5",
+        ),
+        (
+            "let a = 1;\nlet a = 2;",
+            "test:2:5: 'a' is already declared with let at test:1:5",
+        ),
+        (
+            "let a float = sqrt(2) * 3;\nlet b int = a;",
+            "test:2:5: 'b' declared as int, got float",
+        ),
+        (
+            "let a = 1.5 + \"a\";",
+            "test:1:13: incompatible types str and float",
+        ),
+        (
+            "let add = fn(a int, b int) int { ret a + b; };\nadd(1, \"2\");",
+            "test:2:8: argument 2 of 'add' expected int, got str",
+        ),
+        ("for i in 5 {}", "test:1:10: cannot iterate over int"),
+        (
+            "for i in 0..\"a\" {}",
+            "test:1:11: range bounds must be int, got int..str",
+        ),
+        (
+            "while true {}\nbreak;",
+            "test:2:1: 'break' outside of a loop",
+        ),
+        (
+            "while true { fn f() { continue; } }",
+            "test:1:23: 'continue' outside of a loop",
+        ),
+        ("let a = 1;\na.b;", "test:2:2: int has no member 'b'"),
+        (
+            "import m;\nm.x = 1;",
+            "test:2:5: cannot assign to 'm.x', module members are read only",
+        ),
+        (
+            "import m;\nlet m = 1;",
+            "test:2:5: 'm' is already declared with import at test:1:8",
+        ),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(vec![expected.to_string()], check(input), "{}", input);
+    }
+}
+
+#[test]
+fn test_typeck_reports_all_errors() {
+    let input = "let a = 1 + \"a\";\nlet b = -true;\nlet c = 1;\nc(2);";
+    assert_eq!(3, check(input).len());
+}
+
+#[test]
+fn test_typeck_structs() {
+    let valid = "fn origin() Point { ret Point { x: 0, y: 0 }; }
+    struct Point { x int, y int }
+    struct Line { from Point, to Point }
+    let line Line = Line { from: origin(), to: Point { y: 2, x: 1 } };
+    line.to.x = line.from.y + 1;
+    fn length(line Line) int { ret line.to.x - line.from.x; }
+    length(line);";
+    assert_eq!(Vec::<String>::new(), check(valid));
+    let tests = [
+        (
+            "struct P { x int }\nlet p = P { x: \"1\" };",
+            "test:2:13: field 'x' of P expected int, got str",
+        ),
+        (
+            "struct P { x int }\nlet p = P { x: 1, y: 2 };",
+            "test:2:19: P has no field 'y'",
+        ),
+        (
+            "struct P { x int, y int, z int }\nlet p = P { y: 1 };",
+            "test:2:9: missing fields 'x', 'z' in P",
+        ),
+        ("let p = Q { x: 1 };", "test:1:9: unknown struct 'Q'"),
+        (
+            "struct P { x int }\nlet p = P { x: 1 };\np.x = true;",
+            "test:3:5: field 'x' of P expected int, got bool",
+        ),
+        (
+            "struct P { x int }\nlet p = P { x: 1 };\np.y;",
+            "test:3:2: P has no field 'y'",
+        ),
+        (
+            "struct P { x int }\nlet a int = P { x: 1 }.x + 1.5;",
+            "test:2:5: 'a' declared as int, got float",
+        ),
+        ("struct P { x Q }", "test:1:12: unknown type 'Q'"),
+        ("fn f(p Q) {}", "test:1:6: unknown type 'Q'"),
+        (
+            "struct P { x int, x str }",
+            "test:1:19: field 'x' of P is declared twice",
+        ),
+        (
+            "struct P { x int }\nfn f(p P) int { ret p.x; }\nf(1);",
+            "test:3:3: argument 1 of 'f' expected P, got int",
+        ),
+        (
+            "struct P { x int }\nstruct P { y int }",
+            "test:2:8: 'P' is already declared with struct at test:1:8",
+        ),
+        (
+            "enum E { A }\nE = 1;",
+            "test:2:3: cannot assign to 'E', it was declared with enum at test:1:6",
+        ),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(vec![expected.to_string()], check(input), "{}", input);
+    }
+}
+
+#[test]
+fn test_typeck_match() {
+    let valid = "fn area(s Shape) int {
+        ret match s {
+            Shape.Circle(r) if r > 10 => 0,
+            Shape.Circle(r) => 3 * r * r,
+            Shape.Rect(w, h) => w * h,
+            Shape.Empty => 0,
+        };
+    }
+    enum Shape { Circle(int), Rect(int, int), Empty }
+    let c Shape = Shape.Circle(2);
+    area(c);
+    let kind str = match [1, 2] { [a, ..rest] => \"many\", _ => \"none\" };
+    match 3 { 1..5 => 1, -1 => 2 };";
+    assert_eq!(Vec::<String>::new(), check(valid));
+    let shape = "enum Shape { Circle(int), Rect(int, int), Empty }\n";
+    let tests = [
+        (
+            "let a = match Shape.Empty { Shape.Circle(r) => r, Shape.Empty => 0 };",
+            "test:2:9: non-exhaustive match, missing Shape.Rect",
+        ),
+        (
+            "fn f(s Shape) { match s { Shape.Circle(1) => 1, Shape.Rect(_, _) => 2, _ if true => 3 }; }",
+            "test:2:17: non-exhaustive match, missing Shape.Circle, Shape.Empty",
+        ),
+        (
+            "match 1 { \"one\" => 1, _ => 2 };",
+            "test:2:11: mismatched pattern: expected int, got str",
+        ),
+        (
+            "match \"a\" { 1..3 => 1, _ => 2 };",
+            "test:2:13: mismatched pattern: expected str, got int",
+        ),
+        (
+            "match 1 { Shape.Empty => 1, _ => 2 };",
+            "test:2:11: mismatched pattern: expected int, got Shape",
+        ),
+        (
+            "match Shape.Empty { Color.Red => 1, _ => 2 };",
+            "test:2:21: unknown enum 'Color'",
+        ),
+        (
+            "match Shape.Empty { Shape.Square => 1, _ => 2 };",
+            "test:2:27: Shape has no variant 'Square'",
+        ),
+        (
+            "match Shape.Empty { Shape.Rect(w) => w, _ => 2 };",
+            "test:2:27: Shape.Rect holds 2 values, got 1 in the pattern",
+        ),
+        (
+            "match [1, 2] { [a, a] => a, _ => 2 };",
+            "test:2:20: 'a' is bound twice in the pattern",
+        ),
+        (
+            "match Shape.Circle(1) { Shape.Circle(r) => r + \"s\", _ => 0 };",
+            "test:2:46: incompatible types str and int",
+        ),
+        (
+            "match 1 { n if n => 1, _ => 2 };",
+            "test:2:16: condition must be bool, got int",
+        ),
+        (
+            "let s = Shape.Circle(\"big\");",
+            "test:2:22: argument 1 of 'Shape.Circle' expected int, got str",
+        ),
+        (
+            "let s = Shape.Rect(1);",
+            "test:2:14: wrong number of arguments to 'Shape.Rect': want=2, got=1",
+        ),
+        ("let s = Shape.Square;", "test:2:14: Shape has no variant 'Square'"),
+        ("let n int = Shape.Empty;", "test:2:5: 'n' declared as int, got Shape"),
+        ("enum E { A(Q) }", "test:2:10: unknown type 'Q'"),
+        ("enum E { A, A }", "test:2:13: variant 'A' of E is declared twice"),
+    ];
+    for (input, expected) in tests {
+        let input = format!("{}{}", shape, input);
+        assert_eq!(vec![expected.to_string()], check(&input), "{}", input);
+    }
+}
+
+#[test]
+fn test_typeck_methods() {
+    let valid = "impl Point {
+        fn len(self) int { ret self.x * self.x + self.y * self.y; }
+        fn origin() Point { ret Point { x: 0, y: 0 }; }
+    }
+    struct Point { x int, y int }
+    let n int = Point.origin().len();
+    let s str = [1, 2].len().to_str();
+    let t str = \" a \".trim();";
+    assert_eq!(Vec::<String>::new(), check(valid));
+    let types = "struct Point { x int, y int }\nenum Shape { Circle(int), Empty }\nimpl Point { fn add(self, other Point) Point { ret other; } }\n";
+    let tests = [
+        (
+            "let p = Point { x: 1, y: 2 }; p.add(1);",
+            "test:4:37: argument 1 of 'p.add' expected Point, got int",
+        ),
+        (
+            "let p = Point { x: 1, y: 2 }; p.add();",
+            "test:4:32: wrong number of arguments to 'p.add': want=1, got=0",
+        ),
+        (
+            "let p = Point { x: 1, y: 2 }; let n int = p.add(p);",
+            "test:4:35: 'n' declared as int, got Point",
+        ),
+        (
+            "let n int = [1].len() + \"s\";",
+            "test:4:23: incompatible types str and int",
+        ),
+        ("[1].size();", "test:4:4: array has no method 'size'"),
+        (
+            "let p = Point { x: 1, y: 2 }; p.nope();",
+            "test:4:32: Point has no method 'nope'",
+        ),
+        (
+            "impl Point { fn add(self) {} }",
+            "test:4:17: method 'add' of Point is declared twice",
+        ),
+        (
+            "impl Point { fn len(self) int { ret self.z; } }",
+            "test:4:41: Point has no field 'z'",
+        ),
+        (
+            "impl Point { fn x(self) {} }",
+            "test:4:17: method 'x' of Point has the name of a field",
+        ),
+        (
+            "impl Shape { fn Empty(self) {} }",
+            "test:4:17: method 'Empty' of Shape has the name of a variant",
+        ),
+        (
+            "impl Point { fn a(self) {} fn a(self) {} }",
+            "test:4:31: method 'a' of Point is declared twice",
+        ),
+        ("impl Circle {}", "test:4:6: unknown type 'Circle'"),
+        (
+            "let n = 1;\nimpl n {}",
+            "test:5:6: impl needs a struct or enum, got int",
+        ),
+    ];
+    for (input, expected) in tests {
+        let input = format!("{}{}", types, input);
+        assert_eq!(vec![expected.to_string()], check(&input), "{}", input);
+    }
+}
@@ -0,0 +1,538 @@
+use std::{cell::RefCell, rc::Rc};
+
+use bzr::{
+    ast::expression::Node,
+    compiler::{
+        code::{self, Opcode},
+        Compiler,
+    },
+    evaluator::Evaluator,
+    lexer::Lexer,
+    object::{environment::Environment, error::Error, Object},
+    parser::Parser,
+    vm::Vm,
+};
+
+fn test_eval(source: &str) -> Rc<dyn Object> {
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program: Box<dyn Node> = parser.parse_program();
+    let eval = Evaluator::default();
+    let env = RefCell::new(Environment::default());
+    eval.eval(Some(program.as_ref()), Rc::new(env)).unwrap()
+}
+
+fn test_vm(source: &str) -> Rc<dyn Object> {
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let parser = Parser::new(lexer);
+    let program = parser.parse_program();
+    let bytecode = Compiler::default().compile(&program).unwrap();
+    Vm::new(bytecode).run().unwrap()
+}
+
+fn assert_same_result(sources: &[&str]) {
+    for source in sources {
+        let expected = test_eval(source);
+        let evaluated = test_vm(source);
+        assert_eq!(expected.get_type(), evaluated.get_type(), "{}", source);
+        assert_eq!(expected.to_string(), evaluated.to_string(), "{}", source);
+    }
+}
+
+#[test]
+fn test_make_instruction() {
+    let instruction = code::make(Opcode::Constant, &[65534]);
+    assert_eq!(vec![Opcode::Constant as u8, 255, 254], instruction);
+    let instruction = code::make(Opcode::Closure, &[65534, 255]);
+    assert_eq!(vec![Opcode::Closure as u8, 255, 254, 255], instruction);
+}
+
+#[test]
+fn test_disassemble() {
+    let mut instructions = code::make(Opcode::Constant, &[1]);
+    instructions.extend(code::make(Opcode::GetLocal, &[2]));
+    instructions.extend(code::make(Opcode::Add, &[]));
+    assert_eq!(
+        "0000 Constant 1\n0003 GetLocal 2\n0005 Add\n",
+        code::disassemble(&instructions)
+    );
+}
+
+#[test]
+fn test_vm_literals_and_operators() {
+    assert_same_result(&[
+        "5",
+        "-10",
+        "true",
+        "\"bzr\"",
+        "!!false",
+        "(5 + 10 * 2 + 15 / 3) * 2 + -10",
+        "7 % 4 + (1 << 3) - (8 >> 1) + (7 & 5) + (7 | 5) + (7 ^ 5)",
+        "(1 > 2) || (2 < 1);",
+        "true && false;",
+        "\"Paulo\" + \" \" + \"Gabriel\"",
+    ]);
+}
+
+#[test]
+fn test_vm_conditionals_and_loops() {
+    assert_same_result(&[
+        "if false { 1 } else { 10 } ",
+        "let x = 10; if 1 > x { 10 } else { 100 }",
+        "if 1 > 2 { 10; } else if 10 != 10 { 100; } else { 45; }",
+        "let x = 0; while x < 100 { x = x + 1; } x",
+        "let x = 0; while x < 3 { x = x + 1; let y = x * 2; } y",
+        "if false { 1; }",
+        "while false {}",
+        "fn f() int { if true { ret 1; } ret 2; } f();",
+        "fn f() int { var i = 0; while true { if i == 3 { ret i; } i = i + 1; } ret 99; } f();",
+    ]);
+}
+
+#[test]
+fn test_vm_bindings() {
+    assert_same_result(&[
+        "let a int = 5; let b = 20; if a > b { 10; } else { 5; }",
+        "var a = 5; var b = 10; a;",
+        "let a int = 10; a = 0; a;",
+        "ret 2 * 5; 9;",
+        "5 * 5 * 5; ret 10; 10 * 10;",
+    ]);
+}
+
+#[test]
+fn test_vm_errors() {
+    assert_same_result(&[
+        "5 + true;",
+        "-true;",
+        "true + false;",
+        "\"Hello\" - \" World\";",
+        "foobar",
+        "if 1 == 1 { let a = 10; } a;",
+    ]);
+}
+
+#[test]
+fn test_vm_try_catch() {
+    assert_same_result(&[
+        "try { 1 + 2 } catch e { 0 }",
+        "try { 10 / 0; 1 } catch e { e[\"message\"] }",
+        "try { throw \"boom\"; } catch e { [e[\"message\"], e[\"location\"]] }",
+        "try { throw {\"message\": \"m\"}; } catch e { e[\"message\"] }",
+        "try { throw {\"message\": \"m\", \"code\": 3}; } catch e { [e[\"code\"], e[\"line\"]] }",
+        "try {\n  try {\n    1 / 0;\n  } catch e {\n    throw e;\n  }\n} catch e { e[\"location\"] }",
+        "try { throw {\"message\": \"m\", \"line\": 9}; } catch e { e }",
+        "fn f(n int) int { if n == 0 { throw \"x\"; } ret f(n - 1); } try { f(3) } catch e { e[\"line\"] }",
+        "fn f() int { let v = try { ret 7; } catch e { 0 }; ret v + 1; } f()",
+        "fn f() { try { 1 / 0 } catch e { ret 1; } } let a = f(); try { a / 0 } catch e { e[\"message\"] }",
+        "try { try { throw \"a\"; } catch x { throw x[\"message\"] + \"b\"; } } catch y { y[\"message\"] }",
+        "var e = 1; try { throw 5; } catch e { e; } e",
+        "let a = [1, 2]; try { while true { a[5][0]; } } catch e { e[\"message\"] }",
+        "throw \"uncaught\";",
+    ]);
+}
+
+#[test]
+fn test_vm_for_loops() {
+    assert_same_result(&[
+        "var s = 0; for i in 0..5 { s = s + i; } s",
+        "let r = 1..4; r",
+        "var s = \"\"; for ch in \"abc\" { s = ch + s; } s",
+        "let a = [1, 2]; for x in a { append(a, x); } a",
+        "var s = 0; for i in 0..10 { if i == 2 { continue; } if i == 5 { break; } s = s + i; } s",
+        "var i = 0; while true { i = i + 1; if i < 4 { continue; } break; } i",
+        "for i in 0..3 { i * 2 }",
+        "for i in 0..3 { if i == 1 { break; } i }",
+        "let i = 7; for i in 0..3 { i; } i",
+        "fn f() int { for i in 0..9 { if i == 3 { ret i; } } ret -1; } f()",
+        "var s = 0; for i in 0..3 { for j in 0..3 { if j > i { break; } s = s + 1; } } s",
+        "var a = []; for i in 0..4 { try { if i == 2 { break; } append(a, i); } catch e { 0 } } try { throw \"x\"; } catch e { [a, e[\"message\"]] }",
+        "for i in 5 { }",
+        "for i in 0..true { }",
+    ]);
+}
+
+#[test]
+fn test_vm_floats() {
+    assert_same_result(&[
+        "3.14",
+        "-0.5 * 2",
+        "1 + 0.5",
+        "[7 / 2.0, 7 / 2, 5.5 % 2]",
+        "[2.0 == 2, 1 < 1.5, 2.5 >= 3]",
+        "1.5 & 1",
+        "let x = 2.0; -x",
+        "[sqrt(16), floor(2.7), ceil(2.1), round(2.5), pow(2, 10), abs(-2.5)]",
+        "floor(1e300)",
+    ]);
+}
+
+#[test]
+fn test_vm_closures() {
+    assert_same_result(&[
+        "let add = fn(a int, b int) int { ret a + b; }; add(2, 3)",
+        "fn(x int) int { ret x * 2; }(21)",
+        "fn() {}",
+        "let f = fn(x int) int { ret x * 2; }; [f, len]",
+        "fn counter() fn { var n = 0; ret fn() int { n = n + 1; ret n; }; } let a = counter(); let b = counter(); a(); a(); [a(), b()]",
+        "fn pair() array { var n = 0; ret [fn() { n = n + 1; }, fn() int { ret n; }]; } let p = pair(); p[0](); p[0](); p[1]()",
+        "fn late() int { var n = 0; let f = fn() int { ret n; }; n = 5; ret f(); } late()",
+        "fn nest() int { var a = 1; let f = fn() fn { ret fn() { a = a + 10; }; }; f()(); ret a; } nest()",
+        "let fs = []; for i in 0..3 { append(fs, fn() int { ret i; }); }; [fs[0](), fs[2]()]",
+        "fn f() array { let fs = []; for i in 0..3 { append(fs, fn() int { ret i; }); }; ret [fs[0](), fs[2]()]; } f()",
+        "fn f() int { let fact = fn(n int) int { if n < 2 { ret 1; } ret n * fact(n - 1); }; ret fact(5); } f()",
+        "var total = 0; let bump = fn() { total = total + 1; }; bump(); bump(); total",
+        "let boom = fn() { throw \"bad\"; }; try { boom(); } catch e { e[\"message\"] }",
+    ]);
+}
+
+#[test]
+fn test_vm_imports() {
+    assert_same_result(&[
+        "import \"examples/geometry.bzr\"; geometry.area(1.0)",
+        "import \"examples/geometry.bzr\" as g; let area = g.area; area(2.0) == g.area(2.0)",
+        "import \"examples/geometry.bzr\" as a; import \"examples/geometry.bzr\" as b; a.area(1.0); b.circumference(1.0); [a.calls, b.calls]",
+        "fn f() float { import \"examples/geometry.bzr\" as g; ret g.pi; } [f(), f()]",
+        "import \"examples/geometry.bzr\" as g; g",
+        "import \"examples/geometry.bzr\"; geometry.volume",
+        "let a = 1; a.b",
+        "import \"examples/missing.bzr\"",
+        "import \"examples/geometry.bzr\" as g; let g = 1;",
+        "try { import \"examples/missing.bzr\"; } catch e { e[\"message\"] }",
+    ]);
+}
+
+#[test]
+fn test_vm_string_interpolation() {
+    assert_same_result(&[
+        r#"let a = 2; "a + 1 = ${a + 1}, ${[a, 3.5]}""#,
+        r#"fn f(x) { "<${x}>" } f("\t${f(1)}")"#,
+        r#"let m = {"k": "v"}; "${m["k"]}${"-${m["k"]}"}""#,
+        r#""${1 + "a"} never""#,
+    ]);
+}
+
+#[test]
+fn test_vm_let_immutability() {
+    assert_same_result(&[
+        "let a = 1;\na = 2;",
+        "let a = 1;\nlet a = 2;",
+        "let a = 1; fn f() { a = 3; } f()",
+        "var a = 1; var a = 2; a = a + 1; a",
+        "let a = 1; if true { let a = 2; a }",
+        "let a = 1; if true { let a = 2; } a",
+        "var a = 1; if true { a = 2; } a",
+        "let x = 1; fn f(x) { x = x + 1; x }; [f(5), x]",
+        "var i = 0; while i < 3 { let j = i; i = i + 1; } i",
+        "var i = 0; while i < 3 { let j = i; i = i + 1; } j",
+    ]);
+}
+
+#[test]
+fn test_vm_functions() {
+    assert_same_result(&[
+        "fn x_plus_two(x int) { x + 2; }",
+        "fn x_plus_two(x int) {  x + 2; } x_plus_two(10);",
+        "fn add(a int, b int) { ret a + b; } add(5, 5);",
+        "fn factorial(x int) int {
+            if x <= 1 {
+                ret 1;
+            } else {
+                ret x * factorial(x - 1);
+            }
+        }
+        factorial(10);",
+        "fn calculate(a int, b int, p int) int {
+            ret p(a, b);
+        }
+        fn add(a int, b int) int {
+            ret a + b;
+        }
+        calculate(5, 5, add);",
+        "fn first() int { ret second() + 1; } fn second() int { ret 1; } first();",
+        "fn outer(a int) int { fn inner(b int) int { ret a + b; } ret inner(2); } outer(40);",
+    ]);
+}
+
+#[test]
+fn test_vm_arrays_and_built_ins() {
+    assert_same_result(&[
+        "[1, 2 * 2, 3 + 3]",
+        "let a = [1, 2 * 2, 3 + 3]; a[1]",
+        "let a = [1, 2, 3]; a[10]",
+        "try { [1][-1] } catch e { e[\"message\"] }",
+        "try { \"abc\"[9] } catch e { e[\"message\"] }",
+        "len(\"abc\")",
+        "let a = [1]; append(a, 2, 3); len(a)",
+        "\"bzr\"[1]",
+        "join(split(\"a b c\", \" \"), \"-\")",
+        "format(\"{1}{0}\", upper(\"é\"), pad_left(\"1\", 3, \"0\"))",
+        "slice(\"héllo\", 1, 9)",
+        "let s = \"héllo\"; var n = 0; for ch in s { n = n + len(ch); } n == len(s)",
+        "if env_get(\"BZR_VM_UNSET\") == \"1\" { \"debug\" } else { \"quiet\" }",
+        "let v = env_get(\"BZR_VM_UNSET\"); [v != \"1\", v == v, 1 == v]",
+    ]);
+}
+
+#[test]
+fn test_vm_callback_built_ins() {
+    assert_same_result(&[
+        "map([1, 2, 3], fn(x int) int { ret x * 2; })",
+        "filter([1, 2, 3], fn(x int) bool { ret x != 2; })",
+        "reduce([\"a\", \"b\"], fn(acc str, s str) str { ret acc + s; }, \">\")",
+        "find([1, 2, 3], fn(x int) bool { ret x > 1; })",
+        "[any([1], fn(x int) bool { ret x > 1; }), all([2], fn(x int) bool { ret x > 1; })]",
+        "sort([3, 1, 2], fn(a int, b int) int { ret b - a; })",
+        "[reverse([1, 2]), zip([1], [2]), enumerate([\"a\"])]",
+        "map([[1, 2], [3]], fn(a array) array { ret map(a, fn(x int) int { ret x + 1; }); })",
+        "fn add(n int) fn { ret fn(x int) int { ret x + n; }; } map([1, 2], add(10))",
+        "var n = 0; map([1, 2], fn(x int) int { n = n + x; ret x; }); n",
+        "map([1, 0], fn(x int) int { ret 1 / x; })",
+        "try { map([0], fn(x int) int { ret 1 / x; }) } catch e { e[\"message\"] }",
+        "map([1, 2], fn(x int) int { ret try { throw \"in\"; } catch e { x }; })",
+        "fn f(x int) int { let y = try { reduce([1, 2], fn(a int, b int) int { throw \"boom\"; }) } catch e { -1 }; ret y + x; } map([1, 2], f)",
+        "map([1], fn(a int, b int) int { ret a; })",
+        "filter([1], fn(x int) int { ret x; })",
+        "sort([1, \"a\"])",
+    ]);
+}
+
+#[test]
+fn test_vm_file_built_ins() {
+    let dir = std::env::temp_dir().join(format!("bzr_vm_fs_test_{}", std::process::id()));
+    let dir = dir.to_str().unwrap().to_string();
+    let sources = [
+        format!("mkdir(\"{0}\"); write_file(\"{0}/a.txt\", \"a\\nb\"); read_lines(\"{0}/a.txt\")", dir),
+        format!("write_file(\"{0}/b.txt\", \"\"); append_file(\"{0}/b.txt\", \"c\"); [read_file(\"{0}/b.txt\"), list_dir(\"{0}\")]", dir),
+        format!("try {{ read_file(\"{0}/missing\") }} catch e {{ is_error(e) }}", dir),
+        format!("read_file(\"{}/missing\")", dir),
+    ];
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    assert_same_result(&sources);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_vm_hashes() {
+    assert_same_result(&[
+        "{\"one\": 1, \"two\": 1 + 1, 3: true}",
+        "let m = {\"a\": 1, \"b\": 2}; m[\"b\"]",
+        "let m = {}; m[\"a\"] = 5; m[\"a\"]",
+        "let a = [1, 2, 3]; a[1] = 10; a",
+        "let m = {}; m[\"self\"] = m; [m, m[\"self\"]]",
+        "keys({\"b\": 2, \"a\": 1})",
+        "{[1]: 1}",
+    ]);
+}
+
+#[test]
+fn test_vm_error_locations() {
+    let sources = [
+        "let a = 1;\na + true;",
+        "fn f(x) {\n    ret x + \"a\";\n}\nf(1);",
+        "fn f(x) {\n    let a = x[1];\n    ret a;\n}\nfn g() {\n    ret f(1);\n}\ng();",
+        "puts(foo);",
+    ];
+    for source in sources {
+        let expected = test_eval(source);
+        let evaluated = test_vm(source);
+        let expected = expected.as_any().downcast_ref::<Error>().unwrap();
+        let evaluated = evaluated.as_any().downcast_ref::<Error>().unwrap();
+        assert!(expected.location.is_some(), "{}", source);
+        assert_eq!(expected.location, evaluated.location, "{}", source);
+        assert_eq!(expected.trace, evaluated.trace, "{}", source);
+    }
+}
+
+#[test]
+fn test_vm_structs() {
+    assert_same_result(&[
+        "struct Point { x int, y int }\nPoint { x: 1, y: 2 }",
+        "struct Point { x int, y int }\nvar p = Point { y: 2, x: 1 }; p.x = p.x + p.y; p",
+        "struct Point { x int, y int }\nlet p = Point { x: 1, y: 2 }; let q = p; q.y = 5; p.y",
+        "struct Point { x int, y int }\nstruct Line { from Point, to Point }\nlet l = Line { from: Point { x: 0, y: 0 }, to: Point { x: 3, y: 4 } }; l.to.x = 9; l",
+        "fn area(r Rect) int { ret r.w * r.h; }\nstruct Rect { w int, h int }\nlet rects = [Rect { w: 2, h: 3 }, Rect { w: 4, h: 5 }]; map(rects, area)",
+        "struct Point { x int, y int }\nfn f() { let p = Point { x: 1, y: 2 }; ret fn() int { ret p.x + p.y; }; }\nf()()",
+        "struct Point { x int, y int }\nlet p = Point { x: 1, y: 2 }; if p.x < p.y { \"less\" } else { \"more\" }",
+        "struct Point { x int, y int }\nPoint { x: 1, y: \"2\" }",
+        "struct Point { x int, y int }\nPoint { x: 1 }",
+        "struct Point { x int, y int }\nlet p = Point { x: 1, y: 2 }; p.x = 1.5; p",
+        "struct Point { x int, y int }\ntry { Point { x: 1, y: 2 }.z } catch e { e[\"message\"] }",
+        "struct Point { x int, y int }\nlet Point = 1;",
+        "fn f() { struct Local { a str } ret Local { a: \"in\" }; }\nf().a",
+    ]);
+}
+
+#[test]
+fn test_vm_match() {
+    assert_same_result(&[
+        "enum Shape { Circle(int), Rect(int, int), Empty }\nShape.Rect(2, 3)",
+        "enum Shape { Circle(int), Rect(int, int), Empty }\nfn area(s) { ret match s { Shape.Circle(r) => 3 * r * r, Shape.Rect(w, h) => w * h, Shape.Empty => 0 }; };\n[area(Shape.Circle(2)), area(Shape.Rect(2, 5)), area(Shape.Empty)]",
+        "enum Shape { Circle(int), Rect(int, int) }\nmatch Shape.Rect(3, 3) { Shape.Rect(w, h) if w == h => \"square\", Shape.Rect(_, _) => \"rect\", _ => \"other\" }",
+        "match 7 { 0 => \"zero\", 1..10 => \"digit\", _ => \"big\" }",
+        "match -2 { -5..0 => \"neg\", n => n }",
+        "match \"b\" { \"a\" => 1, \"b\" => 2, _ => 3 }",
+        "match [1, 2, 3] { [] => 0, [a] => a, [a, b, ..rest] => [a + b, rest] }",
+        "match [[1, 2], 3] { [[a, b], c] => a + b + c, _ => 0 }",
+        "let x = 1; let y = match 5 { x => x * 2 }; [x, y]",
+        "fn f(a) { let b = 10; ret match a { [x, y] => fn() { ret x + y + b; }, _ => fn() { ret 0; } }; }\nf([1, 2])()",
+        "var total = 0; for i in 0..6 { match i { 2 => { continue; } 4 => { break; } n => { total = total + n; } } } total",
+        "enum E { A, B }\nfn f(e) { match e { E.A => { ret \"early\"; } _ => {} } ret \"late\"; }\n[f(E.A), f(E.B)]",
+        "enum Tree { Leaf, Node(Tree, int, Tree) }\nfn sum(t) { ret match t { Tree.Leaf => 0, Tree.Node(l, v, r) => sum(l) + v + sum(r) }; }\nsum(Tree.Node(Tree.Node(Tree.Leaf, 1, Tree.Leaf), 2, Tree.Leaf))",
+        "match 3 { 1 => \"one\" }",
+        "match 1 / 0 { _ => 1 }",
+        "match 5 { n if n / 0 == 1 => 1, _ => 2 }",
+        "try { match 5 { n if n / 0 == 1 => 1, _ => 2 } } catch e { e[\"message\"] }",
+        "enum E { A, B }\ntry { match E.B { E.A => 1 } } catch e { e[\"message\"] }",
+        "enum Shape { Circle(int) }\nShape.Circle(1, 2)",
+        "enum Shape { Circle(int) }\nShape.Circle(\"big\")",
+        "enum Shape { Circle(int) }\nShape.Square",
+        // an error in a condition is raised, not taken as false
+        "if 1 / 0 == 0 { 1 } else { 2 }",
+        "var i = 0; while i / 0 < 3 { i = i + 1; } i",
+    ]);
+}
+
+#[test]
+fn test_vm_methods() {
+    let point = "struct Point { x int, y int }\nimpl Point {\n    fn len(self) int { ret self.x * self.x + self.y * self.y; }\n    fn scale(self, k int) Point { ret Point { x: self.x * k, y: self.y * k }; }\n    fn origin() Point { ret Point { x: 0, y: 0 }; }\n}\n";
+    let sources: Vec<String> = [
+        "Point { x: 3, y: 4 }.len()",
+        "Point { x: 1, y: 2 }.scale(2).len()",
+        "Point.origin()",
+        "let p = Point { x: 1, y: 0 }; let len = p.len; [len, len()]",
+        "[Point { x: 1, y: 1 }, Point { x: 2, y: 0 }].map(fn(p) { ret p.len(); })",
+        "fn outer() { let k = 3; impl Point { fn far(self) int { ret self.x * k; } } ret Point { x: 2, y: 0 }.far(); }\nouter()",
+        "enum E { A, B(int) }\nimpl E { fn value(self) int { ret match self { E.A => 0, E.B(n) => n }; } }\n[E.A.value(), E.B(7).value()]",
+        "struct S { items array }\nimpl S { fn len(self) int { ret len(self.items); } }\nS { items: [1, 2] }.len()",
+        "[1, 2].append(3).len()",
+        "\"a,b\".split(\",\").map(fn(s) { ret s.upper(); })",
+        "Point { x: 1, y: 2 }.size()",
+        "[1].size()",
+        "Point { x: 1, y: 2 }.size",
+        "Point { x: 1, y: 2 }.len(1)",
+        "impl Point { fn len(self) int { ret 0; } }\nPoint { x: 1, y: 2 }.len()",
+        "impl Point { fn boom(self) int { ret self.x / 0; } }\ntry { Point { x: 1, y: 2 }.boom() } catch e { e[\"message\"] }",
+    ]
+    .iter()
+    .map(|source| format!("{}{}", point, source))
+    .collect();
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    assert_same_result(&sources);
+}
+
+#[test]
+fn test_vm_big_integers() {
+    assert_same_result(&[
+        "fn factorial(x int) int { if x <= 1 { ret 1; } ret x * factorial(x - 1); }\nfactorial(30)",
+        "9223372036854775807 + 1",
+        "-9223372036854775807 - 2",
+        "3037000500 * 3037000500",
+        "-(-9223372036854775807 - 1)",
+        "(9223372036854775807 + 1) / 2",
+        "1 << 70",
+        "[1 << 64 >> 60, -(1 << 70) >> 200]",
+        "1 << -1",
+        "[(1 << 70) & -4, (1 << 70) | 1, (1 << 70) ^ -1]",
+        "[123456789012345678901234567890, -99999999999999999999 - 1]",
+        "to_int(\"99999999999999999999\") + 1",
+        "match 1 << 64 { 18446744073709551616 => 1, _ => 2 }",
+        "try { 1 / 0 } catch e { e[\"message\"] }",
+        "[1, 2][9223372036854775807 + 1]",
+        "\"a\".repeat(9223372036854775807 + 1)",
+        "0..9223372036854775807 + 1",
+        "for i in 0..9223372036854775807 + 1 {}",
+    ]);
+}
+
+#[test]
+fn test_vm_tail_calls() {
+    assert_same_result(&[
+        "fn count(n int, acc int) int {\n    if n == 0 { ret acc; }\n    ret count(n - 1, acc + 1);\n}\ncount(1000000, 0)",
+        "fn even(n int) bool {\n    if n == 0 { ret true; }\n    ret odd(n - 1);\n}\nfn odd(n int) bool {\n    if n == 0 { ret false; }\n    ret even(n - 1);\n}\neven(100001)",
+        "struct C { step int }\nimpl C {\n    fn down(self, n int) int {\n        if n <= 0 { ret n; }\n        ret self.down(n - self.step);\n    }\n}\nC { step: 2 }.down(100001)",
+        "fn loop(n int) int {\n    for i in 0..1 {\n        if n > 0 { ret loop(n - 1); }\n    }\n    ret n;\n}\nloop(100000)",
+        "fn size(xs array) int { ret len(xs); }\nsize([1, 2])",
+        "fn f(n int) int { ret f(); }\nf(1)",
+        "fn boom(n int) int { ret n / 0; }\nfn safe(n int) int {\n    try { ret boom(n); } catch e { ret -1; }\n}\nsafe(1)",
+        "[1, 2].map(fn(x int) int { ret to_str(x).len(); })",
+    ]);
+    let sources = [
+        "fn inner(x) {\n    ret x + \"a\";\n}\nfn outer(y) {\n    ret inner(y);\n}\nouter(1);",
+        "fn size(xs) int {\n    ret len(xs, 1);\n}\nsize([1]);",
+    ];
+    for source in sources {
+        let expected = test_eval(source);
+        let evaluated = test_vm(source);
+        let expected = expected.as_any().downcast_ref::<Error>().unwrap();
+        let evaluated = evaluated.as_any().downcast_ref::<Error>().unwrap();
+        assert_eq!(expected.location, evaluated.location, "{}", source);
+        assert_eq!(expected.trace, evaluated.trace, "{}", source);
+    }
+}
+
+#[test]
+fn test_vm_stack_overflow() {
+    let deep = "fn deep(n int) int {\n    if n == 0 { ret 0; }\n    ret 1 + deep(n - 1);\n}\n";
+    let sources = [
+        format!("{}deep(29)", deep),
+        format!("{}try {{ deep(30) }} catch e {{ e[\"message\"] }}", deep),
+        format!("{}[1].map(fn(x int) int {{ ret deep(28 + x); }})", deep),
+        format!("{}deep(100)", deep),
+    ];
+    for source in sources.iter() {
+        let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+        let program = Parser::new(lexer).parse_program();
+        let mut eval = Evaluator::default();
+        eval.max_depth = 30;
+        let env = RefCell::new(Environment::default());
+        let expected = eval.eval(Some(program.as_ref()), Rc::new(env)).unwrap();
+        let mut vm = Vm::new(Compiler::default().compile(&program).unwrap());
+        vm.max_depth = 30;
+        let evaluated = vm.run().unwrap();
+        assert_eq!(expected.to_string(), evaluated.to_string(), "{}", source);
+        if let Some(expected) = expected.as_any().downcast_ref::<Error>() {
+            let evaluated = evaluated.as_any().downcast_ref::<Error>().unwrap();
+            assert_eq!(expected.location, evaluated.location, "{}", source);
+            assert_eq!(expected.trace, evaluated.trace, "{}", source);
+        }
+    }
+}
+
+fn compile_error(source: &str) -> String {
+    let lexer = Lexer::new(Rc::new(source.to_string()), Rc::new("foo.bzr".to_string()));
+    let program = Parser::new(lexer).parse_program();
+    match Compiler::default().compile(&program) {
+        Ok(_) => panic!("compiled: {}", source),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn test_vm_operand_limits() {
+    let locals = |count: usize| {
+        let mut source = String::from("fn f() int {\n");
+        for idx in 0..count {
+            source.push_str(&format!("    let v{} = {};\n", idx, idx));
+        }
+        format!("{}    ret v0 + v{};\n}}\nf()", source, count - 1)
+    };
+    assert_same_result(&[&locals(256)]);
+    assert_eq!(
+        "program too large for the vm: local index 256 is over 255",
+        compile_error(&locals(300))
+    );
+
+    let mut source = String::from("var s = 0;\nif s == 0 {\n");
+    for idx in 0..7000 {
+        source.push_str(&format!("    s = s + {};\n", idx));
+    }
+    source.push_str("}\ns");
+    let error = compile_error(&source);
+    assert!(
+        error.starts_with("program too large for the vm: jump target "),
+        "{}",
+        error
+    );
+    assert!(error.ends_with(" is over 65535"), "{}", error);
+}